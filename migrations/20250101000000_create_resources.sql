@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS resources (
+    service_name TEXT NOT NULL,
+    service_type TEXT NOT NULL,
+    org_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    properties JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (service_name, service_type, org_id, name)
+);
@@ -0,0 +1,2 @@
+-- Incremented on every update, exposed as the resource's ETag
+ALTER TABLE resources ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
@@ -0,0 +1,3 @@
+-- Stable identifier that survives renames; FGA objects keep using the composite key
+ALTER TABLE resources ADD COLUMN IF NOT EXISTS id UUID NOT NULL DEFAULT gen_random_uuid();
+CREATE UNIQUE INDEX IF NOT EXISTS resources_id_key ON resources (id);
@@ -0,0 +1,13 @@
+-- Tuple writes and deletes made through the API, correlated by the client's operation ID
+CREATE TABLE IF NOT EXISTS tuple_changes (
+    id BIGSERIAL PRIMARY KEY,
+    operation_id TEXT,
+    operation TEXT NOT NULL,
+    tuple_user TEXT NOT NULL,
+    relation TEXT NOT NULL,
+    object TEXT NOT NULL,
+    changed_by TEXT NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS tuple_changes_operation_id ON tuple_changes (operation_id)
+    WHERE operation_id IS NOT NULL;
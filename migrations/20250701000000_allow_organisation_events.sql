@@ -0,0 +1,2 @@
+-- Organisation events such as a reparent have no resource
+ALTER TABLE outbox ALTER COLUMN resource_id DROP NOT NULL;
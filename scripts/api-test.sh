@@ -57,6 +57,8 @@ show_usage() {
     echo "  resource-name - Resource name (default: $DEFAULT_RESOURCE_NAME)"
     echo "  payload-file  - JSON file for POST/PUT requests (optional)"
     echo ""
+    echo "Updates send If-Match: \$IF_MATCH, the ETag returned by get (default: *)"
+    echo ""
     echo "Examples:"
     echo "  $0 alice health"
     echo "  $0 alice create"
@@ -89,6 +91,11 @@ make_request() {
     if [ "$method" = "POST" ] || [ "$method" = "PUT" ]; then
         curl_cmd="$curl_cmd -H \"Content-Type: $CONTENT_TYPE\""
     fi
+
+    # Updates must name the version they are based on
+    if [ "$method" = "PUT" ]; then
+        curl_cmd="$curl_cmd -H 'If-Match: ${IF_MATCH:-*}'"
+    fi
     
     # Add payload if provided
     if [ ! -z "$payload_file" ] && [ -f "$payload_file" ]; then
@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use axum::{
+    Extension,
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use sqlx::PgPool;
+use std::sync::{Arc, Mutex};
+use time::OffsetDateTime;
+
+use crate::auth::AuthUser;
+use crate::context::Ctx;
+use crate::error::{ApiError, ErrorCode};
+use crate::store::StoreError;
+
+/// Reason the caller gave for a mutating request, from `X-Action-Reason`
+#[derive(Clone, Debug)]
+pub struct ActionReason(pub Option<String>);
+
+/// Record mutating requests in the audit log together with the caller's
+/// stated reason and the outcome
+///
+/// When `REQUIRE_ACTION_REASON` is set, mutations without an `X-Action-Reason`
+/// header are rejected with 400 `reason_required`. Must run after
+/// `auth_middleware`.
+pub async fn action_reason_middleware(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let is_mutation = matches!(
+        *request.method(),
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    );
+    if !is_mutation {
+        return Ok(next.run(request).await);
+    }
+
+    let reason = request
+        .headers()
+        .get("x-action-reason")
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|reason| !reason.is_empty())
+        .map(String::from);
+
+    if reason.is_none() && ctx.require_action_reason {
+        return Err(ApiError::new(
+            ErrorCode::ReasonRequired,
+            "X-Action-Reason header is required for this operation",
+        ));
+    }
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    request
+        .extensions_mut()
+        .insert(ActionReason(reason.clone()));
+
+    let response = next.run(request).await;
+
+    tracing::info!(
+        target: "audit",
+        user_id = %auth_user.user_id,
+        method = %method,
+        path = %path,
+        status = response.status().as_u16(),
+        allowed = response.status() != StatusCode::FORBIDDEN,
+        reason = reason.as_deref().unwrap_or(""),
+        "Mutating request completed"
+    );
+
+    Ok(response)
+}
+
+/// A tuple written or deleted through the API, with the business operation it
+/// belongs to when the client supplied one
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TupleChange {
+    pub operation_id: Option<String>,
+    /// `write` or `delete`
+    pub operation: String,
+    #[sqlx(rename = "tuple_user")]
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+    /// User ID of the caller who made the change
+    pub changed_by: String,
+    pub changed_at: OffsetDateTime,
+}
+
+impl TupleChange {
+    /// Whether both changes apply the same operation to the same tuple
+    pub fn same_change(&self, other: &TupleChange) -> bool {
+        self.operation == other.operation
+            && self.user == other.user
+            && self.relation == other.relation
+            && self.object == other.object
+    }
+}
+
+/// Persistent log of the tuple changes made through the API
+#[async_trait]
+pub trait TupleChangeLog: Send + Sync {
+    async fn record(&self, change: TupleChange) -> Result<(), StoreError>;
+
+    /// Every change recorded under an operation ID, oldest first
+    async fn by_operation(&self, operation_id: &str) -> Result<Vec<TupleChange>, StoreError>;
+}
+
+/// Tuple changes stored in the `tuple_changes` table
+pub struct PgTupleChangeLog {
+    db: PgPool,
+}
+
+impl PgTupleChangeLog {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl TupleChangeLog for PgTupleChangeLog {
+    async fn record(&self, change: TupleChange) -> Result<(), StoreError> {
+        sqlx::query(
+            "INSERT INTO tuple_changes
+                 (operation_id, operation, tuple_user, relation, object, changed_by, changed_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(&change.operation_id)
+        .bind(&change.operation)
+        .bind(&change.user)
+        .bind(&change.relation)
+        .bind(&change.object)
+        .bind(&change.changed_by)
+        .bind(change.changed_at)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn by_operation(&self, operation_id: &str) -> Result<Vec<TupleChange>, StoreError> {
+        let changes = sqlx::query_as::<_, TupleChange>(
+            "SELECT operation_id, operation, tuple_user, relation, object, changed_by, changed_at
+             FROM tuple_changes
+             WHERE operation_id = $1
+             ORDER BY id",
+        )
+        .bind(operation_id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(changes)
+    }
+}
+
+/// Tuple changes kept in process memory, used with the in-memory resource store
+#[derive(Default)]
+pub struct MemoryTupleChangeLog {
+    changes: Mutex<Vec<TupleChange>>,
+}
+
+impl MemoryTupleChangeLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TupleChangeLog for MemoryTupleChangeLog {
+    async fn record(&self, change: TupleChange) -> Result<(), StoreError> {
+        self.changes.lock().unwrap().push(change);
+        Ok(())
+    }
+
+    async fn by_operation(&self, operation_id: &str) -> Result<Vec<TupleChange>, StoreError> {
+        Ok(self
+            .changes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|change| change.operation_id.as_deref() == Some(operation_id))
+            .cloned()
+            .collect())
+    }
+}
@@ -1,14 +1,24 @@
 use axum::{
-    Json,
     extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    http::{HeaderMap, header},
     middleware::Next,
     response::Response,
 };
-use serde_json::{Value, json};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use crate::context::Ctx;
+use crate::error::{ApiError, ErrorCode};
+
+/// User ID given to callers without any identity on optionally authenticated
+/// routes. Never a valid authenticated ID, since `*` is reserved in FGA users.
+pub const ANONYMOUS_USER_ID: &str = "*";
 
 /// User information extracted from authentication
 #[derive(Clone, Debug)]
@@ -16,49 +26,220 @@ pub struct AuthUser {
     pub user_id: String,
 }
 
-/// Authentication middleware that extracts user ID from headers
-pub async fn auth_middleware(
-    State(_ctx): State<Arc<Ctx>>,
-    headers: HeaderMap,
-    mut request: Request,
-    next: Next,
-) -> Result<Response, (StatusCode, Json<Value>)> {
-    // Extract user ID from the "X-User-Id" header
-    let user_id = match headers.get("x-user-id") {
+impl AuthUser {
+    /// Whether the caller supplied no identity on an optionally authenticated route
+    pub fn is_anonymous(&self) -> bool {
+        self.user_id == ANONYMOUS_USER_ID
+    }
+}
+
+/// Keys bearer tokens may be signed with
+#[derive(Clone)]
+enum JwtKeys {
+    /// A single public key, used regardless of the token's `kid`
+    Single(DecodingKey),
+    /// A JWKS document, the key is selected by the token's `kid`
+    Set(JwkSet),
+}
+
+/// How bearer tokens are validated
+#[derive(Clone)]
+pub struct JwtConfig {
+    keys: JwtKeys,
+    validation: Validation,
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+}
+
+impl JwtConfig {
+    /// Read `JWT_PUBLIC_KEY_FILE` (PEM) or `JWT_JWKS_FILE`, `JWT_ALGORITHM`
+    /// (default RS256), `JWT_ISSUER` and the optional `JWT_AUDIENCE`
+    ///
+    /// Returns `None` when neither key source is configured.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let algorithm = match env::var("JWT_ALGORITHM") {
+            Ok(value) => Algorithm::from_str(&value)
+                .map_err(|_| format!("Invalid JWT_ALGORITHM '{}'", value))?,
+            Err(_) => Algorithm::RS256,
+        };
+
+        let keys = match (env::var("JWT_PUBLIC_KEY_FILE"), env::var("JWT_JWKS_FILE")) {
+            (Ok(_), Ok(_)) => {
+                return Err("Set only one of JWT_PUBLIC_KEY_FILE and JWT_JWKS_FILE".to_string());
+            }
+            (Ok(path), Err(_)) => {
+                let pem = fs::read(&path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+                let key = match algorithm {
+                    Algorithm::RS256
+                    | Algorithm::RS384
+                    | Algorithm::RS512
+                    | Algorithm::PS256
+                    | Algorithm::PS384
+                    | Algorithm::PS512 => DecodingKey::from_rsa_pem(&pem),
+                    Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(&pem),
+                    Algorithm::EdDSA => DecodingKey::from_ed_pem(&pem),
+                    _ => return Err(format!("JWT_ALGORITHM {:?} needs a public key", algorithm)),
+                }
+                .map_err(|e| format!("Invalid public key in {}: {}", path, e))?;
+                JwtKeys::Single(key)
+            }
+            (Err(_), Ok(path)) => {
+                let jwks = fs::read_to_string(&path)
+                    .map_err(|e| format!("Cannot read {}: {}", path, e))?;
+                let jwks: JwkSet = serde_json::from_str(&jwks)
+                    .map_err(|e| format!("Invalid JWKS in {}: {}", path, e))?;
+                JwtKeys::Set(jwks)
+            }
+            (Err(_), Err(_)) => return Ok(None),
+        };
+
+        let issuer = env::var("JWT_ISSUER")
+            .map_err(|_| "JWT_ISSUER is required when JWT validation is enabled".to_string())?;
+
+        let mut validation = Validation::new(algorithm);
+        validation.set_issuer(&[issuer]);
+        match env::var("JWT_AUDIENCE") {
+            Ok(audience) => validation.set_audience(&[audience]),
+            Err(_) => validation.validate_aud = false,
+        }
+
+        Ok(Some(Self { keys, validation }))
+    }
+
+    /// Validate a bearer token and return its `sub` claim
+    fn verify(&self, token: &str) -> Result<String, ApiError> {
+        let invalid = |message: &str| ApiError::new(ErrorCode::InvalidToken, message);
+
+        let key = match &self.keys {
+            JwtKeys::Single(key) => key.clone(),
+            JwtKeys::Set(jwks) => {
+                let header = jsonwebtoken::decode_header(token)
+                    .map_err(|_| invalid("Bearer token is malformed"))?;
+                let kid = header
+                    .kid
+                    .ok_or_else(|| invalid("Bearer token has no key ID"))?;
+                let jwk = jwks
+                    .find(&kid)
+                    .ok_or_else(|| invalid("Bearer token is signed with an unknown key"))?;
+                DecodingKey::from_jwk(jwk)
+                    .map_err(|_| invalid("Bearer token is signed with an unusable key"))?
+            }
+        };
+
+        let claims = jsonwebtoken::decode::<Claims>(token, &key, &self.validation)
+            .map_err(|e| match e.kind() {
+                ErrorKind::ExpiredSignature => invalid("Bearer token has expired"),
+                ErrorKind::ImmatureSignature => invalid("Bearer token is not valid yet"),
+                ErrorKind::InvalidSignature => invalid("Bearer token signature is invalid"),
+                ErrorKind::InvalidIssuer => invalid("Bearer token issuer is not trusted"),
+                ErrorKind::InvalidAudience => invalid("Bearer token audience is not accepted"),
+                ErrorKind::InvalidAlgorithm => invalid("Bearer token algorithm is not accepted"),
+                ErrorKind::MissingRequiredClaim(claim) => {
+                    invalid(&format!("Bearer token is missing the {} claim", claim))
+                }
+                _ => invalid("Bearer token is malformed"),
+            })?
+            .claims;
+
+        Ok(claims.sub)
+    }
+}
+
+/// Read the user ID from the `X-User-Id` header
+fn user_id_from_header(headers: &HeaderMap) -> Result<String, ApiError> {
+    match headers.get("x-user-id") {
         Some(header_value) => match header_value.to_str() {
             Ok(user_id) => {
                 if user_id.trim().is_empty() {
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        Json(json!({
-                            "error": "Invalid user ID",
-                            "message": "X-User-Id header cannot be empty"
-                        })),
+                    return Err(ApiError::new(
+                        ErrorCode::InvalidIdentity,
+                        "X-User-Id header cannot be empty",
                     ));
                 }
-                user_id.to_string()
-            }
-            Err(_) => {
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    Json(json!({
-                        "error": "Invalid header format",
-                        "message": "X-User-Id header must be valid UTF-8"
-                    })),
-                ));
+                Ok(user_id.to_string())
             }
+            Err(_) => Err(ApiError::new(
+                ErrorCode::InvalidIdentity,
+                "X-User-Id header must be valid UTF-8",
+            )),
         },
-        None => {
-            return Err((
-                StatusCode::UNAUTHORIZED,
-                Json(json!({
-                    "error": "Missing authentication",
-                    "message": "X-User-Id header is required"
-                })),
+        None => Err(ApiError::new(
+            ErrorCode::Unauthenticated,
+            "X-User-Id header is required",
+        )),
+    }
+}
+
+/// Extract and validate the user ID from a bearer token, or from the
+/// `X-User-Id` header when that fallback is allowed
+fn authenticate(ctx: &Ctx, headers: &HeaderMap) -> Result<String, ApiError> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let user_id = match (bearer, &ctx.jwt) {
+        (Some(token), Some(jwt)) => jwt.verify(token.trim()).inspect_err(|e| {
+            tracing::warn!("Rejecting bearer token: {}", e.message);
+        })?,
+        (Some(_), None) if !ctx.allow_user_id_header => {
+            return Err(ApiError::new(
+                ErrorCode::Unauthenticated,
+                "Bearer tokens are not configured",
+            ));
+        }
+        _ if ctx.allow_user_id_header => user_id_from_header(headers)?,
+        _ => {
+            return Err(ApiError::new(
+                ErrorCode::Unauthenticated,
+                "Authorization: Bearer token is required",
             ));
         }
     };
 
+    // The handlers add the `user:` type themselves, an ID that already carries
+    // it would become `user:user:...` and never match a tuple
+    if user_id.starts_with("user:") {
+        tracing::warn!("Rejecting user ID with type prefix: {:?}", user_id);
+        return Err(ApiError::new(
+            ErrorCode::InvalidUserId,
+            "User ID must not include the 'user:' prefix",
+        ));
+    }
+
+    if user_id == ANONYMOUS_USER_ID {
+        return Err(ApiError::new(
+            ErrorCode::InvalidUserId,
+            "User ID is reserved for anonymous callers",
+        ));
+    }
+
+    // Reject IDs that would corrupt FGA tuple grammar (e.g. containing ':' or '#')
+    if !ctx.user_id_pattern.is_match(&user_id) {
+        tracing::warn!("Rejecting malformed user ID: {:?}", user_id);
+        return Err(ApiError::new(
+            ErrorCode::InvalidUserId,
+            format!(
+                "User ID must match the pattern {}",
+                ctx.user_id_pattern.as_str()
+            ),
+        ));
+    }
+
+    Ok(user_id)
+}
+
+/// Authentication middleware that rejects requests without a valid identity
+pub async fn auth_middleware(
+    State(ctx): State<Arc<Ctx>>,
+    headers: HeaderMap,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let user_id = authenticate(&ctx, &headers)?;
     tracing::info!("Authenticated user: {}", user_id);
 
     // Create AuthUser and insert it into request extensions
@@ -68,3 +249,27 @@ pub async fn auth_middleware(
     // Continue to the next handler
     Ok(next.run(request).await)
 }
+
+/// Authentication middleware for public reads: requests without any identity
+/// continue as `ANONYMOUS_USER_ID`, checked as `ANONYMOUS_USER` in OpenFGA.
+/// A supplied identity is validated exactly as in `auth_middleware`.
+pub async fn optional_auth_middleware(
+    State(ctx): State<Arc<Ctx>>,
+    headers: HeaderMap,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let has_identity =
+        headers.contains_key(header::AUTHORIZATION) || headers.contains_key("x-user-id");
+    let user_id = if has_identity {
+        let user_id = authenticate(&ctx, &headers)?;
+        tracing::info!("Authenticated user: {}", user_id);
+        user_id
+    } else {
+        tracing::info!("Serving anonymous request");
+        ANONYMOUS_USER_ID.to_string()
+    };
+
+    request.extensions_mut().insert(AuthUser { user_id });
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,118 @@
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::{Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::context::Ctx;
+use crate::error::{ApiError, ErrorCode};
+
+/// Placeholder written in place of redacted values
+const REDACTED: &str = "[REDACTED]";
+
+/// Settings for debug logging of request and response bodies
+#[derive(Clone, Debug)]
+pub struct BodyLogConfig {
+    /// JSON keys whose values are replaced before logging, at any depth
+    pub redact_keys: HashSet<String>,
+    /// Bodies larger than this are passed through without being logged
+    pub max_bytes: usize,
+}
+
+/// Log JSON request and response bodies at debug level, with redaction
+///
+/// Only bodies with a known size up to `max_bytes` are buffered; anything else
+/// streams through untouched so large uploads are never held in memory.
+pub async fn body_log_middleware(
+    State(ctx): State<Arc<Ctx>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(config) = &ctx.body_logging else {
+        return next.run(request).await;
+    };
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    let (parts, body) = request.into_parts();
+    let label = format!("request {} {}", method, path);
+    let body = match log_body(config, &label, &parts.headers, body).await {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::warn!("Failed to buffer {} body: {}", label, e);
+            return ApiError::validation("Failed to read request body").into_response();
+        }
+    };
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    let (parts, body) = response.into_parts();
+    let label = format!("response {} {} {}", parts.status.as_u16(), method, path);
+    match log_body(config, &label, &parts.headers, body).await {
+        Ok(body) => Response::from_parts(parts, body),
+        Err(e) => {
+            tracing::error!("Failed to buffer {} body: {}", label, e);
+            ApiError::new(ErrorCode::Internal, "Failed to read response").into_response()
+        }
+    }
+}
+
+/// Buffer and log a body if it is small enough, returning an equivalent body
+///
+/// Fails when the body cannot be read, since its content is lost by then.
+async fn log_body(
+    config: &BodyLogConfig,
+    label: &str,
+    headers: &HeaderMap,
+    body: Body,
+) -> Result<Body, axum::Error> {
+    let is_json = headers
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+
+    match body.size_hint().exact() {
+        Some(size) if is_json && size as usize <= config.max_bytes => {}
+        _ => {
+            tracing::debug!("{} body not logged (not JSON or over size limit)", label);
+            return Ok(body);
+        }
+    }
+
+    let bytes = axum::body::to_bytes(body, config.max_bytes).await?;
+    tracing::debug!("{} body: {}", label, redacted(config, &bytes));
+    Ok(Body::from(bytes))
+}
+
+/// Render a JSON body with the configured keys redacted
+fn redacted(config: &BodyLogConfig, bytes: &Bytes) -> String {
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(mut value) => {
+            redact(&mut value, &config.redact_keys);
+            value.to_string()
+        }
+        Err(_) => format!("<invalid JSON, {} bytes>", bytes.len()),
+    }
+}
+
+/// Replace the values of redacted keys anywhere in the document
+fn redact(value: &mut Value, keys: &HashSet<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if keys.contains(key) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact(field, keys);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact(item, keys)),
+        _ => {}
+    }
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+/// Shares a single in-flight call between concurrent callers using the same key
+///
+/// This is not a cache: once the call completes its entry is dropped, so the
+/// next caller with the same key triggers a fresh upstream call.
+pub struct SingleFlight<K, V> {
+    in_flight: Mutex<HashMap<K, Arc<OnceCell<V>>>>,
+}
+
+impl<K, V> Default for SingleFlight<K, V> {
+    fn default() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K, V> SingleFlight<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    /// Create an empty single-flight group
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `f` for `key`, or wait for the call already in flight for it
+    pub async fn run<F, Fut>(&self, key: K, f: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let cell = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+
+        // If the caller driving the call is dropped, a waiting caller takes over
+        let value = cell.get_or_init(f).await.clone();
+
+        // Whoever finishes first clears the entry, unless a new flight replaced it
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &cell))
+        {
+            in_flight.remove(&key);
+        }
+
+        value
+    }
+}
@@ -0,0 +1,60 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+use crate::context::Ctx;
+use crate::error::{ApiError, ErrorCode};
+
+/// Maximum in-flight requests per route path, keyed by the route pattern
+/// (e.g. `/api/shared-resources`)
+pub type RouteLimits = HashMap<String, Arc<Semaphore>>;
+
+/// Parse `ROUTE_CONCURRENCY_LIMITS` entries of the form `path=max`
+pub fn parse_route_limits(entries: &[String]) -> Result<RouteLimits, String> {
+    let mut limits = RouteLimits::new();
+    for entry in entries {
+        let Some((path, max)) = entry.rsplit_once('=') else {
+            return Err(format!("expected path=max, got '{}'", entry));
+        };
+        let max: usize = match max.trim().parse() {
+            Ok(max) if max > 0 => max,
+            _ => return Err(format!("limit for '{}' must be a positive number", path)),
+        };
+        limits.insert(path.trim().to_string(), Arc::new(Semaphore::new(max)));
+    }
+    Ok(limits)
+}
+
+/// Bound in-flight requests for routes with a configured limit, rejecting
+/// with 503 once the limit is reached so other routes keep serving
+pub async fn route_concurrency_middleware(
+    State(ctx): State<Arc<Ctx>>,
+    matched_path: Option<MatchedPath>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let Some(semaphore) =
+        matched_path.and_then(|path| ctx.route_limits.get(path.as_str()).cloned())
+    else {
+        return Ok(next.run(request).await);
+    };
+
+    let Ok(_permit) = semaphore.try_acquire_owned() else {
+        tracing::warn!(
+            "Concurrency limit reached for {} {}",
+            request.method(),
+            request.uri().path()
+        );
+        return Err(ApiError::new(
+            ErrorCode::Overloaded,
+            "Too many concurrent requests for this endpoint",
+        ));
+    };
+
+    Ok(next.run(request).await)
+}
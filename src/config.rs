@@ -0,0 +1,141 @@
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Directory holding one config file per profile
+const CONFIG_DIR: &str = "config";
+
+/// Contents of `config/{profile}.toml`, every setting optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    database: DatabaseFile,
+    openfga: OpenFgaFile,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct DatabaseFile {
+    url: Option<String>,
+    max_connections: Option<u32>,
+    acquire_timeout_secs: Option<u64>,
+    idle_timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct OpenFgaFile {
+    url: Option<String>,
+    store_id: Option<String>,
+    authorization_model_id: Option<String>,
+}
+
+/// Postgres connection and pool settings
+#[derive(Clone, Debug)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub acquire_timeout: Duration,
+    pub idle_timeout: Duration,
+}
+
+/// Where OpenFGA is reached and which store and model are used
+#[derive(Clone, Debug)]
+pub struct OpenFgaConnection {
+    pub url: String,
+    /// Empty when no store is configured
+    pub store_id: String,
+    pub authorization_model_id: Option<String>,
+}
+
+/// Connection settings from the profile's config file, overridden by env vars
+#[derive(Clone, Debug)]
+pub struct AppConfig {
+    pub database: DatabaseConfig,
+    pub openfga: OpenFgaConnection,
+}
+
+impl AppConfig {
+    /// Load `config/{profile}.toml` and apply `DATABASE_URL`, `DB_MAX_CONNECTIONS`,
+    /// `DB_ACQUIRE_TIMEOUT_SECS`, `DB_IDLE_TIMEOUT_SECS`, `OPENFGA_CLIENT_URL`,
+    /// `OPENFGA_STORE_ID` and `OPENFGA_AUTH_MODEL_ID` on top of it
+    ///
+    /// Without the file the settings come from the environment alone.
+    pub fn load(profile: &str) -> Result<Self, String> {
+        let path = PathBuf::from(CONFIG_DIR).join(format!("{}.toml", profile));
+        let file = match fs::read_to_string(&path) {
+            Ok(contents) => {
+                tracing::info!("Loading configuration from {}", path.display());
+                toml::from_str(&contents)
+                    .map_err(|e| format!("Invalid {}: {}", path.display(), e))?
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => FileConfig::default(),
+            Err(e) => return Err(format!("Cannot read {}: {}", path.display(), e)),
+        };
+        Self::merge(file)
+    }
+
+    fn merge(file: FileConfig) -> Result<Self, String> {
+        let FileConfig { database, openfga } = file;
+
+        let url = env::var("DATABASE_URL")
+            .ok()
+            .or(database.url)
+            .ok_or("DATABASE_URL must be set, or database.url in the config file")?;
+        let max_connections = positive(
+            "DB_MAX_CONNECTIONS",
+            database.max_connections.map(u64::from),
+            5,
+        )?;
+        let database = DatabaseConfig {
+            url,
+            max_connections: u32::try_from(max_connections)
+                .map_err(|_| format!("DB_MAX_CONNECTIONS {} is too large", max_connections))?,
+            acquire_timeout: Duration::from_secs(positive(
+                "DB_ACQUIRE_TIMEOUT_SECS",
+                database.acquire_timeout_secs,
+                3,
+            )?),
+            idle_timeout: Duration::from_secs(positive(
+                "DB_IDLE_TIMEOUT_SECS",
+                database.idle_timeout_secs,
+                600,
+            )?),
+        };
+
+        let openfga = OpenFgaConnection {
+            url: env::var("OPENFGA_CLIENT_URL")
+                .ok()
+                .or(openfga.url)
+                .unwrap_or_else(|| "http://localhost:8081".to_string()),
+            store_id: env::var("OPENFGA_STORE_ID")
+                .ok()
+                .or(openfga.store_id)
+                .unwrap_or_default(),
+            authorization_model_id: env::var("OPENFGA_AUTH_MODEL_ID")
+                .ok()
+                .or(openfga.authorization_model_id),
+        };
+
+        Ok(Self { database, openfga })
+    }
+}
+
+/// Read a positive number from the environment, falling back to the config
+/// file value and then to `default`
+fn positive(name: &str, file: Option<u64>, default: u64) -> Result<u64, String> {
+    let value = match env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid {} '{}': expected a positive number", name, value))?,
+        Err(_) => file.unwrap_or(default),
+    };
+    if value == 0 {
+        return Err(format!("{} must be a positive number", name));
+    }
+    Ok(value)
+}
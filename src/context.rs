@@ -1,10 +1,36 @@
+use crate::audit::{MemoryTupleChangeLog, PgTupleChangeLog, TupleChangeLog};
+use crate::auth::JwtConfig;
+use crate::body_log::BodyLogConfig;
+use crate::cache::TtlCache;
+use crate::coalesce::SingleFlight;
+use crate::concurrency::{self, RouteLimits};
+use crate::config::{AppConfig, DatabaseConfig, OpenFgaConnection};
+use crate::fga::{
+    self, ApiToken, FgaApi, FgaClient, FgaHealth, FgaRetry, FgaTimeouts, ModelInfo,
+    ReconnectingClient,
+};
+use crate::maintenance::{self, ReadOnlyWindow};
+use crate::outbox;
+use crate::prometheus;
+use crate::rate_limit::RateLimits;
+use crate::readiness;
+use crate::schema::ResourceSchemas;
+use crate::stats::Stats;
+use crate::store::{MemoryResourceStore, PgResourceStore, ResourceStore};
+use axum::http::HeaderValue;
+use metrics_exporter_prometheus::PrometheusHandle;
+use openfga_client::client::ConsistencyPreference;
 use openfga_client::client::OpenFgaServiceClient;
+use regex::Regex;
 use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
+use std::collections::BTreeMap;
 use std::env;
+use std::fs;
 use std::sync::Arc;
 use std::time::Duration;
-use tonic::transport::Channel;
+use tokio::time::MissedTickBehavior;
+use tonic::transport::{Certificate, ClientTlsConfig, Endpoint};
 
 /// OpenFGA configuration parameters
 #[derive(Clone, Debug)]
@@ -13,19 +39,124 @@ pub struct OpenFgaConfig {
     pub store_id: String,
     /// OpenFGA authorization model ID
     pub authorization_model_id: Option<String>,
+    /// Checkable relations per object type, as defined by the authorization model
+    pub relations_by_type: BTreeMap<String, Vec<String>>,
+    /// Action names mapped to the relation required to perform them
+    pub actions: BTreeMap<String, String>,
+    /// Timeouts for outgoing calls, per operation type
+    pub timeouts: FgaTimeouts,
+    /// Consistency requested for checks, reads, expansions and lists
+    pub consistency: ConsistencyPreference,
+    /// Retry policy for checks, lists and writes that fail transiently
+    pub retry: FgaRetry,
+    /// Object types listed by the shared-resources endpoint
+    pub shared_object_types: Vec<String>,
+    /// Relations listed per type by the shared-resources endpoint
+    pub shared_relations: Vec<String>,
 }
 
+/// Who may call the admin endpoints
+#[derive(Clone, Debug, Default)]
+pub struct AdminConfig {
+    /// User IDs that are always treated as admins
+    pub user_ids: Vec<String>,
+    /// Object whose relation grants admin access (e.g. "organisation:root")
+    pub object: Option<String>,
+    /// Relation on `object` that grants admin access
+    pub relation: String,
+}
+
+/// Identifies a ListObjects query for coalescing concurrent identical calls
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ListObjectsKey {
+    pub store_id: String,
+    pub authorization_model_id: String,
+    pub consistency: i32,
+    pub object_type: String,
+    pub relation: String,
+    pub user: String,
+}
+
+/// In-flight ListObjects calls shared between concurrent identical requests
+pub type ListObjectsFlights = SingleFlight<ListObjectsKey, Result<Vec<String>, tonic::Status>>;
+
+/// Default user ID format: no FGA-reserved characters (`:`, `#`, `*`) or whitespace
+pub const DEFAULT_USER_ID_PATTERN: &str = r"^[A-Za-z0-9][A-Za-z0-9_.@|-]{0,127}$";
+
 /// Application context that holds shared resources
 #[derive(Clone)]
 pub struct Ctx {
     /// PostgreSQL connection pool
     pub db: PgPool,
+    /// Resource metadata persistence
+    pub resources: Arc<dyn ResourceStore>,
+    /// Tuple changes made through the API, queryable by operation ID
+    pub tuple_changes: Arc<dyn TupleChangeLog>,
+    /// JSON Schemas resource properties must conform to, per service type
+    pub resource_schemas: Arc<ResourceSchemas>,
     /// Application profile name (e.g., "dev", "prod")
     pub profile: String,
-    /// OpenFGA client
-    pub fga_client: OpenFgaServiceClient<Channel>,
+    /// OpenFGA calls, through a client the health check replaces when the
+    /// connection breaks; read it through `Ctx::fga_client`
+    fga_client: Arc<dyn FgaApi>,
     /// OpenFGA configuration
     pub fga_config: OpenFgaConfig,
+    /// Coalesces concurrent identical ListObjects calls
+    pub list_objects_flights: Arc<ListObjectsFlights>,
+    /// Whether type discovery endpoints are served without authentication
+    pub public_type_relations: bool,
+    /// Whether the capability manifest is served without authentication
+    pub public_capabilities: bool,
+    /// Admin endpoint access configuration
+    pub admin: AdminConfig,
+    /// Organisation relations reported as roles, highest first
+    pub org_roles: Vec<String>,
+    /// Respond 404 instead of role "none" for organisations the user has no role in
+    pub hide_unknown_orgs: bool,
+    /// Require organisation membership in addition to resource relations
+    pub enforce_org_membership: bool,
+    /// FGA principal used for unauthenticated callers (e.g. "user:*")
+    pub anonymous_user: String,
+    /// Scheduled windows during which mutating requests are rejected
+    pub read_only_windows: Vec<ReadOnlyWindow>,
+    /// Debug body logging settings, `None` when disabled
+    pub body_logging: Option<BodyLogConfig>,
+    /// Base backoff in seconds sent as `Retry-After` on 503 responses
+    pub retry_after_secs: u64,
+    /// Reject unknown names in the `fields` query parameter instead of ignoring them
+    pub strict_fields: bool,
+    /// Format authenticated user IDs must match
+    pub user_id_pattern: Regex,
+    /// Bearer token validation, `None` when JWTs are not configured
+    pub jwt: Option<JwtConfig>,
+    /// Accept the `X-User-Id` header as identity, for local development
+    pub allow_user_id_header: bool,
+    /// Shared-resources responses per user ID
+    pub shared_resources_cache: Arc<TtlCache<String, serde_json::Value>>,
+    /// Renders the Prometheus metrics served at `/metrics`
+    pub prometheus: PrometheusHandle,
+    /// Check results per (user ID, relation, object)
+    pub check_cache: Arc<TtlCache<(String, String, String), bool>>,
+    /// Identity of the pinned authorization model, read on first use
+    pub model_info: Arc<tokio::sync::OnceCell<ModelInfo>>,
+    /// Reject mutating requests that do not state an `X-Action-Reason`
+    pub require_action_reason: bool,
+    /// Byte limit for list items in buffered responses, `None` for no limit
+    pub max_response_bytes: Option<usize>,
+    /// Rolling latency, status and cache statistics for the admin stats endpoint
+    pub stats: Arc<Stats>,
+    /// Dependencies whose failure only degrades readiness instead of failing it
+    pub readiness_optional: Vec<String>,
+    /// Per-route in-flight request limits, routes not listed are unlimited
+    pub route_limits: RouteLimits,
+    /// Request rate limits per user and per anonymous client IP, `None` when disabled
+    pub rate_limits: Option<Arc<RateLimits>>,
+    /// Origins browsers may call the API from (`*` for any), `None` disables CORS
+    pub cors_allowed_origins: Option<Vec<String>>,
+    /// Requests taking longer are answered with 408
+    pub request_timeout: Duration,
+    /// Requests with larger bodies are answered with 413
+    pub request_body_limit: usize,
 }
 
 impl Ctx {
@@ -38,14 +169,265 @@ impl Ctx {
         let profile = env::var("PROFILE").unwrap_or_else(|_| "dev".to_string());
         tracing::info!("Starting application with profile: {}", profile);
 
+        // Get connection settings from config/{profile}.toml, overridden by env vars
+        let config = AppConfig::load(&profile)?;
+
+        // Get the anonymous principal, validated so a typo fails fast
+        let anonymous_user = env::var("ANONYMOUS_USER").unwrap_or_else(|_| "user:*".to_string());
+        if !is_valid_fga_user(&anonymous_user) {
+            return Err(format!(
+                "Invalid ANONYMOUS_USER '{}', expected type:id, type:* or type:id#relation",
+                anonymous_user
+            )
+            .into());
+        }
+
+        // Get the scheduled read-only windows, if any
+        let read_only_windows = match env::var("READ_ONLY_WINDOWS") {
+            Ok(value) => maintenance::parse_read_only_windows(&value)
+                .map_err(|e| format!("Invalid READ_ONLY_WINDOWS: {}", e))?,
+            Err(_) => Vec::new(),
+        };
+        for window in &read_only_windows {
+            tracing::info!(
+                "Read-only window scheduled from {} to {}",
+                window.start,
+                window.end
+            );
+        }
+
+        // Get body logging settings, only honoured in the dev profile
+        let body_logging = get_body_log_config(&profile)?;
+
+        // Get the base backoff advertised to clients on 503 responses
+        let retry_after_secs = match env::var("RETRY_AFTER_SECS") {
+            Ok(value) => value
+                .parse()
+                .map_err(|_| format!("Invalid RETRY_AFTER_SECS '{}'", value))?,
+            Err(_) => 5,
+        };
+
+        // Get the accepted user ID format
+        let user_id_pattern =
+            env::var("USER_ID_PATTERN").unwrap_or_else(|_| DEFAULT_USER_ID_PATTERN.to_string());
+        let user_id_pattern = Regex::new(&user_id_pattern)
+            .map_err(|e| format!("Invalid USER_ID_PATTERN '{}': {}", user_id_pattern, e))?;
+
+        // Get bearer token validation; the X-User-Id header stays accepted by
+        // default only while no JWT keys are configured
+        let jwt = JwtConfig::from_env()?;
+        let allow_user_id_header = match env::var("ALLOW_USER_ID_HEADER") {
+            Ok(value) => value == "1" || value.eq_ignore_ascii_case("true"),
+            Err(_) => jwt.is_none(),
+        };
+        if jwt.is_some() && allow_user_id_header {
+            tracing::warn!("X-User-Id header is accepted alongside bearer tokens");
+        }
+
+        // Get the shared-resources cache TTL, 0 disables caching
+        let shared_cache_ttl = match env::var("SHARED_CACHE_TTL_SECS") {
+            Ok(value) => value
+                .parse()
+                .map_err(|_| format!("Invalid SHARED_CACHE_TTL_SECS '{}'", value))?,
+            Err(_) => 30,
+        };
+
+        // Get the check result cache TTL, 0 disables caching
+        let check_cache_ttl = match env::var("FGA_CHECK_CACHE_TTL") {
+            Ok(value) => value
+                .parse()
+                .map_err(|_| format!("Invalid FGA_CHECK_CACHE_TTL '{}'", value))?,
+            Err(_) => 5,
+        };
+
+        // Get the response size limit, unset means unlimited
+        let max_response_bytes = match env::var("MAX_RESPONSE_BYTES") {
+            Ok(value) => Some(
+                value
+                    .parse()
+                    .map_err(|_| format!("Invalid MAX_RESPONSE_BYTES '{}'", value))?,
+            ),
+            Err(_) => None,
+        };
+
+        // Get the window the admin stats are computed over
+        let stats_window = match env::var("STATS_WINDOW_SECS") {
+            Ok(value) => match value.parse() {
+                Ok(secs) if secs > 0 => secs,
+                _ => return Err(format!("Invalid STATS_WINDOW_SECS '{}'", value).into()),
+            },
+            Err(_) => 300,
+        };
+
+        // Get the dependencies that are optional for readiness
+        let readiness_optional = env_list("READINESS_OPTIONAL").unwrap_or_default();
+        if let Some(unknown) = readiness_optional
+            .iter()
+            .find(|name| !readiness::DEPENDENCIES.contains(&name.as_str()))
+        {
+            return Err(format!(
+                "Invalid READINESS_OPTIONAL '{}', expected one of {:?}",
+                unknown,
+                readiness::DEPENDENCIES
+            )
+            .into());
+        }
+
+        // Get the per-route concurrency limits
+        let route_limits = concurrency::parse_route_limits(
+            &env_list("ROUTE_CONCURRENCY_LIMITS").unwrap_or_default(),
+        )
+        .map_err(|e| format!("Invalid ROUTE_CONCURRENCY_LIMITS: {}", e))?;
+        for (path, semaphore) in &route_limits {
+            tracing::info!(
+                "Limiting {} to {} concurrent requests",
+                path,
+                semaphore.available_permits()
+            );
+        }
+
+        // Get the request rate limits
+        let rate_limits = RateLimits::from_env()?.map(Arc::new);
+        if let Some(limits) = &rate_limits {
+            tracing::info!(
+                "Rate limiting users to {}/s and anonymous clients to {}/s",
+                limits.users.per_second(),
+                limits.anonymous.per_second()
+            );
+        }
+
+        // Get the request time and body size limits
+        let request_timeout = match env::var("REQUEST_TIMEOUT_SECS") {
+            Ok(value) => match value.parse() {
+                Ok(secs) if secs > 0 => Duration::from_secs(secs),
+                _ => return Err(format!("Invalid REQUEST_TIMEOUT_SECS '{}'", value).into()),
+            },
+            Err(_) => Duration::from_secs(30),
+        };
+        let request_body_limit = match env::var("REQUEST_BODY_LIMIT_BYTES") {
+            Ok(value) => match value.parse() {
+                Ok(bytes) if bytes > 0 => bytes,
+                _ => return Err(format!("Invalid REQUEST_BODY_LIMIT_BYTES '{}'", value).into()),
+            },
+            Err(_) => 1024 * 1024,
+        };
+
+        // Get the origins allowed for cross-origin browser calls, any origin
+        // in the dev profile unless restricted
+        let cors_allowed_origins = match env_list("CORS_ALLOWED_ORIGINS") {
+            Some(origins) => Some(origins),
+            None if profile == "dev" => Some(vec!["*".to_string()]),
+            None => None,
+        };
+        if let Some(origins) = &cors_allowed_origins {
+            if origins.len() > 1 && origins.iter().any(|origin| origin == "*") {
+                return Err(
+                    "Invalid CORS_ALLOWED_ORIGINS: '*' cannot be combined with origins".into(),
+                );
+            }
+            if let Some(invalid) = origins
+                .iter()
+                .find(|origin| HeaderValue::from_str(origin).is_err())
+            {
+                return Err(
+                    format!("Invalid CORS_ALLOWED_ORIGINS: bad origin '{}'", invalid).into(),
+                );
+            }
+            tracing::info!("Allowing cross-origin requests from {}", origins.join(", "));
+        }
+
+        // Install the metrics recorder before anything records
+        let prometheus = prometheus::install()?;
+
         // Create database connection pool
-        let db = pg_pool().await?;
+        let db = pg_pool(&config.database).await?;
+
+        // Get the resource store backend, Postgres unless explicitly in memory;
+        // the tuple change log is kept alongside the resources
+        let (resources, tuple_changes): (Arc<dyn ResourceStore>, Arc<dyn TupleChangeLog>) =
+            match env::var("RESOURCE_STORE").as_deref().unwrap_or("postgres") {
+                "postgres" => (
+                    Arc::new(PgResourceStore::new(db.clone())),
+                    Arc::new(PgTupleChangeLog::new(db.clone())),
+                ),
+                "memory" => {
+                    tracing::warn!("Resources are stored in memory and lost on restart");
+                    (
+                        Arc::new(MemoryResourceStore::new()),
+                        Arc::new(MemoryTupleChangeLog::new()),
+                    )
+                }
+                other => return Err(format!("Invalid RESOURCE_STORE '{}'", other).into()),
+            };
+
+        // Forward resource events written to the outbox, 0 disables the relay
+        let outbox_relay_interval = match env::var("OUTBOX_RELAY_INTERVAL_SECS") {
+            Ok(value) => Duration::from_secs(
+                value
+                    .parse()
+                    .map_err(|_| format!("Invalid OUTBOX_RELAY_INTERVAL_SECS '{}'", value))?,
+            ),
+            Err(_) => Duration::from_secs(5),
+        };
+        outbox::spawn_relay(resources.clone(), outbox_relay_interval);
+
+        // Load the property schemas, service types without one accept anything
+        let resource_schemas = ResourceSchemas::from_env()?;
+        let service_types = resource_schemas.service_types();
+        if !service_types.is_empty() {
+            tracing::info!(
+                "Validating resource properties for service types {}",
+                service_types.join(", ")
+            );
+        }
 
         // Initialize OpenFGA client
-        let fga_client = init_fga_client().await?;
+        let fga_client = init_fga_client(&config.openfga.url).await?;
 
         // Get OpenFGA configuration
-        let fga_config = get_fga_config();
+        let mut fga_config = get_fga_config(&config.openfga)?;
+
+        // Create a store on first run when asked to, never by default so a
+        // misconfigured production deployment does not get a fresh empty store
+        if fga_config.store_id.is_empty() && env_flag("OPENFGA_AUTO_CREATE_STORE") {
+            let name =
+                env::var("OPENFGA_STORE_NAME").unwrap_or_else(|_| "openfga-demo".to_string());
+            fga_config.store_id = fga::create_store(&fga_client, &name, fga_config.timeouts.write)
+                .await
+                .map_err(|e| {
+                    format!("Failed to create OpenFGA store '{}': {}", name, e.message())
+                })?;
+            tracing::info!(
+                "Created OpenFGA store '{}' with ID {}, set OPENFGA_STORE_ID to reuse it",
+                name,
+                fga_config.store_id
+            );
+        }
+
+        // Apply the model from a file and use it instead of OPENFGA_AUTH_MODEL_ID
+        if let Ok(path) = env::var("OPENFGA_MODEL_FILE") {
+            if fga_config.store_id.is_empty() {
+                return Err("OPENFGA_MODEL_FILE requires an OpenFGA store ID".into());
+            }
+            let model_id = fga::write_model_file(
+                &fga_client,
+                &fga_config.store_id,
+                &path,
+                fga_config.timeouts.write,
+            )
+            .await?;
+            tracing::info!("Wrote authorization model from {} as {}", path, model_id);
+            fga_config.authorization_model_id = Some(model_id);
+        }
+
+        // Keep checking the connection and reconnect when OpenFGA restarts
+        let fga_client = Arc::new(ReconnectingClient::new(fga_client));
+        spawn_fga_health_check(
+            fga_client.clone(),
+            config.openfga.url.clone(),
+            FgaHealth::from_env()?,
+            fga_config.timeouts.check,
+        );
 
         // Log OpenFGA configuration
         if !fga_config.store_id.is_empty() {
@@ -58,67 +440,532 @@ impl Ctx {
 
         Ok(Arc::new(Self {
             db,
+            resources,
+            tuple_changes,
+            resource_schemas: Arc::new(resource_schemas),
             profile,
             fga_client,
             fga_config,
+            list_objects_flights: Arc::new(ListObjectsFlights::new()),
+            public_type_relations: env_flag("PUBLIC_TYPE_RELATIONS"),
+            public_capabilities: env_flag("PUBLIC_CAPABILITIES"),
+            admin: get_admin_config(),
+            org_roles: env_list("ORG_ROLES")
+                .unwrap_or_else(|| vec!["admin".into(), "member".into()]),
+            hide_unknown_orgs: env_flag("HIDE_UNKNOWN_ORGS"),
+            enforce_org_membership: env_flag("ENFORCE_ORG_MEMBERSHIP"),
+            anonymous_user,
+            read_only_windows,
+            body_logging,
+            retry_after_secs,
+            strict_fields: env_flag("STRICT_FIELDS"),
+            user_id_pattern,
+            jwt,
+            allow_user_id_header,
+            shared_resources_cache: Arc::new(TtlCache::new(Duration::from_secs(shared_cache_ttl))),
+            check_cache: Arc::new(TtlCache::new(Duration::from_secs(check_cache_ttl))),
+            prometheus,
+            model_info: Arc::new(tokio::sync::OnceCell::new()),
+            require_action_reason: env_flag("REQUIRE_ACTION_REASON"),
+            max_response_bytes,
+            stats: Arc::new(Stats::new(Duration::from_secs(stats_window))),
+            readiness_optional,
+            route_limits,
+            rate_limits,
+            cors_allowed_origins,
+            request_timeout,
+            request_body_limit,
         }))
     }
+
+    /// OpenFGA client, cheap to clone
+    pub fn fga_client(&self) -> Arc<dyn FgaApi> {
+        self.fga_client.clone()
+    }
+
+    /// Send OpenFGA calls to the given implementation, e.g. a test double
+    #[cfg(feature = "test-util")]
+    pub fn with_fga_client(mut self, client: Arc<dyn FgaApi>) -> Self {
+        self.fga_client = client;
+        self
+    }
+
+    /// Context for integration tests that never reaches Postgres or OpenFGA
+    ///
+    /// Resources are kept in memory, the database pool and the OpenFGA channel
+    /// connect lazily and point nowhere, and the environment is not read.
+    /// Seed `check_cache` to decide permission checks. Must be called within a
+    /// Tokio runtime.
+    #[cfg(feature = "test-util")]
+    pub fn for_testing() -> Self {
+        let db = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/openfga_demo_test")
+            .expect("static database URL is valid");
+        let channel = Endpoint::from_static("http://127.0.0.1:1").connect_lazy();
+        let fga_client = OpenFgaServiceClient::with_interceptor(channel, ApiToken::default());
+        let relations_by_type = BTreeMap::from([(
+            "resource".to_string(),
+            ["owner", "admin", "editor", "viewer"]
+                .map(String::from)
+                .to_vec(),
+        )]);
+
+        Self {
+            db,
+            resources: Arc::new(MemoryResourceStore::new()),
+            tuple_changes: Arc::new(MemoryTupleChangeLog::new()),
+            resource_schemas: Arc::new(ResourceSchemas::default()),
+            profile: "test".to_string(),
+            fga_client: Arc::new(ReconnectingClient::new(fga_client)),
+            fga_config: OpenFgaConfig {
+                store_id: "test-store".to_string(),
+                authorization_model_id: None,
+                relations_by_type,
+                actions: BTreeMap::new(),
+                timeouts: FgaTimeouts::default(),
+                consistency: fga::DEFAULT_CONSISTENCY,
+                retry: FgaRetry {
+                    max_attempts: 1,
+                    ..FgaRetry::default()
+                },
+                shared_object_types: vec!["resource".to_string()],
+                shared_relations: vec!["viewer".to_string()],
+            },
+            list_objects_flights: Arc::new(SingleFlight::new()),
+            public_type_relations: false,
+            public_capabilities: false,
+            admin: AdminConfig::default(),
+            org_roles: vec!["admin".to_string(), "member".to_string()],
+            hide_unknown_orgs: false,
+            enforce_org_membership: false,
+            anonymous_user: "user:*".to_string(),
+            read_only_windows: Vec::new(),
+            body_logging: None,
+            retry_after_secs: 5,
+            strict_fields: false,
+            user_id_pattern: Regex::new(DEFAULT_USER_ID_PATTERN)
+                .expect("default user ID pattern is valid"),
+            jwt: None,
+            allow_user_id_header: true,
+            shared_resources_cache: Arc::new(TtlCache::new(Duration::ZERO)),
+            prometheus: metrics_exporter_prometheus::PrometheusBuilder::new()
+                .build_recorder()
+                .handle(),
+            check_cache: Arc::new(TtlCache::new(Duration::from_secs(3600))),
+            model_info: Arc::new(tokio::sync::OnceCell::new()),
+            require_action_reason: false,
+            max_response_bytes: None,
+            stats: Arc::new(Stats::new(Duration::from_secs(60))),
+            readiness_optional: Vec::new(),
+            route_limits: RouteLimits::new(),
+            rate_limits: None,
+            cors_allowed_origins: None,
+            request_timeout: Duration::from_secs(30),
+            request_body_limit: 1024 * 1024,
+        }
+    }
 }
 
-async fn pg_pool() -> Result<PgPool, Box<dyn std::error::Error>> {
-    // Get database URL from environment
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    tracing::info!("Connecting to database");
+async fn pg_pool(config: &DatabaseConfig) -> Result<PgPool, Box<dyn std::error::Error>> {
+    tracing::info!(
+        "Connecting to database (max {} connections, acquire timeout {:?}, idle timeout {:?})",
+        config.max_connections,
+        config.acquire_timeout,
+        config.idle_timeout
+    );
 
     let db = PgPoolOptions::new()
-        .max_connections(5)
-        .acquire_timeout(Duration::from_secs(3))
-        .connect(&database_url)
+        .max_connections(config.max_connections)
+        .acquire_timeout(config.acquire_timeout)
+        .idle_timeout(config.idle_timeout)
+        .connect(&config.url)
         .await?;
 
     // Test database connection
     sqlx::query("SELECT 1").execute(&db).await?;
     tracing::info!("Database connection established successfully");
 
+    // Deployments that manage the schema externally set RUN_MIGRATIONS=false
+    let run_migrations = env::var("RUN_MIGRATIONS").map_or(true, |value| {
+        value != "0" && !value.eq_ignore_ascii_case("false")
+    });
+    if run_migrations {
+        sqlx::migrate!("./migrations")
+            .run(&db)
+            .await
+            .map_err(|e| format!("Failed to apply database migrations: {}", e))?;
+        tracing::info!("Database migrations applied");
+    } else {
+        tracing::info!("Skipping database migrations (RUN_MIGRATIONS=false)");
+    }
+
     Ok(db)
 }
 
 /// Initialize the OpenFGA client
-async fn init_fga_client() -> Result<OpenFgaServiceClient<Channel>, Box<dyn std::error::Error>> {
-    // Get OpenFGA client URL from environment, default to localhost
-    let fga_url =
-        env::var("OPENFGA_CLIENT_URL").unwrap_or_else(|_| "http://localhost:8081".to_string());
-    tracing::info!("Connecting to OpenFGA at {}", fga_url);
-
-    // Create OpenFGA client without authentication
-    let client = OpenFgaServiceClient::connect(fga_url).await?;
+async fn init_fga_client(fga_url: &str) -> Result<FgaClient, Box<dyn std::error::Error>> {
+    let fga_url = fga_url.to_string();
+
+    // TLS is used for https:// URLs, or for any URL when OPENFGA_TLS is set
+    let tls = fga_url.starts_with("https://") || env_flag("OPENFGA_TLS");
+    let fga_url = match fga_url.strip_prefix("http://") {
+        Some(rest) if tls => format!("https://{}", rest),
+        _ => fga_url,
+    };
+    tracing::info!(
+        "Connecting to OpenFGA at {} ({})",
+        fga_url,
+        if tls { "TLS" } else { "plaintext" }
+    );
+
+    let mut endpoint = Endpoint::from_shared(fga_url.clone())
+        .map_err(|e| format!("Invalid OPENFGA_CLIENT_URL '{}': {}", fga_url, e))?;
+    if tls {
+        let mut tls_config = ClientTlsConfig::new().with_native_roots();
+        if let Ok(path) = env::var("OPENFGA_CA_CERT") {
+            let pem = fs::read(&path)
+                .map_err(|e| format!("Cannot read OPENFGA_CA_CERT {}: {}", path, e))?;
+            tls_config = tls_config.ca_certificate(Certificate::from_pem(pem));
+        }
+        endpoint = endpoint
+            .tls_config(tls_config)
+            .map_err(|e| format!("Invalid OpenFGA TLS configuration: {}", error_chain(&e)))?;
+    }
+
+    // tonic's transport error only says "transport error", the cause (e.g. a
+    // failed TLS handshake) is in its source chain
+    let channel = endpoint.connect().await.map_err(|e| {
+        format!(
+            "Failed to connect to OpenFGA at {}: {}",
+            fga_url,
+            error_chain(&e)
+        )
+    })?;
+
+    let api_token = ApiToken::from_env()?;
+    if api_token.is_set() && !tls {
+        tracing::warn!("Sending OPENFGA_API_TOKEN over a plaintext connection");
+    }
+    let client = OpenFgaServiceClient::with_interceptor(channel, api_token);
     tracing::info!("OpenFGA client initialized successfully");
 
     Ok(client)
 }
 
-/// Get OpenFGA configuration from environment variables
-fn get_fga_config() -> OpenFgaConfig {
-    // Get OpenFGA store ID from environment, default to empty string which will need to be set later
-    let store_id = env::var("OPENFGA_STORE_ID").unwrap_or_else(|_| {
-        tracing::warn!("OPENFGA_STORE_ID not set, using empty string");
-        String::new()
+/// Ping OpenFGA periodically and rebuild the client after repeated connection
+/// failures, so a restarted server does not leave the process with a broken channel
+fn spawn_fga_health_check(
+    client: Arc<ReconnectingClient>,
+    fga_url: String,
+    health: FgaHealth,
+    timeout: Duration,
+) {
+    if health.interval.is_zero() {
+        tracing::info!("OpenFGA connection health check disabled");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(health.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick completes immediately, the connection was just made
+        ticker.tick().await;
+
+        let mut failures = 0;
+        loop {
+            ticker.tick().await;
+
+            match fga::ping(&client.current(), timeout).await {
+                // Any answer, even a rejection, means the connection works
+                Err(e) if fga::is_transient(&e) => {
+                    failures += 1;
+                    tracing::warn!(
+                        "OpenFGA ping failed ({}/{}): {}",
+                        failures,
+                        health.failure_threshold,
+                        e.message()
+                    );
+                }
+                _ => {
+                    if failures > 0 {
+                        tracing::info!("OpenFGA connection recovered");
+                    }
+                    failures = 0;
+                    continue;
+                }
+            }
+            if failures < health.failure_threshold {
+                continue;
+            }
+
+            match init_fga_client(&fga_url).await {
+                Ok(fresh) => {
+                    client.replace(fresh);
+                    failures = 0;
+                    tracing::info!("Reconnected to OpenFGA");
+                }
+                Err(e) => tracing::warn!("Failed to reconnect to OpenFGA: {}", e),
+            }
+        }
     });
+}
+
+/// Render an error together with its sources
+fn error_chain(e: &dyn std::error::Error) -> String {
+    let mut message = e.to_string();
+    let mut source = e.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+/// Get admin access configuration from environment variables
+fn get_admin_config() -> AdminConfig {
+    let user_ids = env_list("ADMIN_USERS").unwrap_or_default();
+
+    let object = env::var("ADMIN_OBJECT").ok();
+    let relation = env::var("ADMIN_RELATION").unwrap_or_else(|_| "admin".to_string());
+
+    if object.is_none() && user_ids.is_empty() {
+        tracing::warn!("Neither ADMIN_USERS nor ADMIN_OBJECT set, admin endpoints are disabled");
+    }
+
+    AdminConfig {
+        user_ids,
+        object,
+        relation,
+    }
+}
 
-    // Get OpenFGA authorization model ID from environment, optional
-    let authorization_model_id = match env::var("OPENFGA_AUTH_MODEL_ID") {
-        Ok(id) => {
+/// Get body logging settings when `LOG_BODIES` is enabled in the dev profile
+fn get_body_log_config(profile: &str) -> Result<Option<BodyLogConfig>, Box<dyn std::error::Error>> {
+    if !env_flag("LOG_BODIES") {
+        return Ok(None);
+    }
+    if profile != "dev" {
+        tracing::warn!("LOG_BODIES is only honoured in the dev profile, ignoring it");
+        return Ok(None);
+    }
+
+    let redact_keys = env::var("LOG_BODIES_REDACT")
+        .unwrap_or_else(|_| "properties".to_string())
+        .split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(String::from)
+        .collect();
+
+    let max_bytes = match env::var("LOG_BODIES_MAX_BYTES") {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| format!("Invalid LOG_BODIES_MAX_BYTES '{}'", value))?,
+        Err(_) => 4096,
+    };
+
+    tracing::info!(
+        "Logging request and response bodies up to {} bytes",
+        max_bytes
+    );
+    Ok(Some(BodyLogConfig {
+        redact_keys,
+        max_bytes,
+    }))
+}
+
+/// Check that a value is an FGA user: `type:id`, `type:*` or `type:id#relation`
+pub(crate) fn is_valid_fga_user(value: &str) -> bool {
+    let is_name = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '@' | '|'))
+    };
+
+    let Some((object_type, rest)) = value.split_once(':') else {
+        return false;
+    };
+    if !is_name(object_type) {
+        return false;
+    }
+
+    match rest.split_once('#') {
+        Some((id, relation)) => is_name(id) && is_name(relation),
+        None => rest == "*" || is_name(rest),
+    }
+}
+
+/// Read a comma-separated list from the environment, `None` when unset or empty
+fn env_list(name: &str) -> Option<Vec<String>> {
+    let values: Vec<String> = env::var(name)
+        .ok()?
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(String::from)
+        .collect();
+    (!values.is_empty()).then_some(values)
+}
+
+/// Read a boolean flag from the environment ("1" or "true"), defaulting to false
+fn env_flag(name: &str) -> bool {
+    env::var(name)
+        .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Relations per type from `etc/auth_model.fga`, excluding structural relations
+fn default_relations_by_type() -> BTreeMap<String, Vec<String>> {
+    [
+        ("group", vec!["member"]),
+        ("organisation", vec!["member", "descendant_member"]),
+        ("service", vec!["admin", "editor", "viewer"]),
+        ("service_type", vec!["admin", "editor", "viewer"]),
+        ("resource", vec!["owner", "admin", "editor", "viewer"]),
+    ]
+    .into_iter()
+    .map(|(object_type, relations)| {
+        (
+            object_type.to_string(),
+            relations.into_iter().map(String::from).collect(),
+        )
+    })
+    .collect()
+}
+
+/// Actions exposed by the resource handlers and the relation each requires
+fn default_actions() -> BTreeMap<String, String> {
+    [
+        ("view", "viewer"),
+        ("update", "editor"),
+        ("delete", "owner"),
+    ]
+    .into_iter()
+    .map(|(action, relation)| (action.to_string(), relation.to_string()))
+    .collect()
+}
+
+/// Parse `type=rel1,rel2;type2=rel3` into a relations-by-type map
+fn parse_relations_by_type(value: &str) -> Result<BTreeMap<String, Vec<String>>, String> {
+    let mut relations_by_type = BTreeMap::new();
+    for entry in value.split(';').filter(|entry| !entry.trim().is_empty()) {
+        let (object_type, relations) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("invalid type relations entry '{}'", entry))?;
+        let relations: Vec<String> = relations
+            .split(',')
+            .map(str::trim)
+            .filter(|relation| !relation.is_empty())
+            .map(String::from)
+            .collect();
+        if relations.is_empty() {
+            return Err(format!("no relations given for type '{}'", object_type));
+        }
+        relations_by_type.insert(object_type.trim().to_string(), relations);
+    }
+    Ok(relations_by_type)
+}
+
+/// Parse `action=relation,action2=relation2` into an action map
+fn parse_actions(value: &str) -> Result<BTreeMap<String, String>, String> {
+    value
+        .split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            entry
+                .split_once('=')
+                .map(|(action, relation)| (action.trim().to_string(), relation.trim().to_string()))
+                .ok_or_else(|| format!("invalid action mapping '{}'", entry))
+        })
+        .collect()
+}
+
+/// Get OpenFGA configuration from the connection settings and environment variables
+fn get_fga_config(
+    connection: &OpenFgaConnection,
+) -> Result<OpenFgaConfig, Box<dyn std::error::Error>> {
+    // The store ID may be empty, it will need to be set later
+    let store_id = connection.store_id.clone();
+    if store_id.is_empty() {
+        tracing::warn!("OPENFGA_STORE_ID not set, using empty string");
+    }
+
+    // The authorization model ID is optional
+    let authorization_model_id = match connection.authorization_model_id.clone() {
+        Some(id) => {
             tracing::info!("Using OpenFGA authorization model ID: {}", id);
             Some(id)
         }
-        Err(_) => {
+        None => {
             tracing::info!("OPENFGA_AUTH_MODEL_ID not set, will need to be set later");
             None
         }
     };
 
-    OpenFgaConfig {
+    // Get the relations per type, defaulting to the bundled model
+    let relations_by_type = match env::var("FGA_TYPE_RELATIONS") {
+        Ok(value) => parse_relations_by_type(&value)
+            .map_err(|e| format!("Invalid FGA_TYPE_RELATIONS: {}", e))?,
+        Err(_) => default_relations_by_type(),
+    };
+
+    // Get the action to relation mappings, defaulting to the resource handlers
+    let actions = match env::var("FGA_ACTIONS") {
+        Ok(value) => parse_actions(&value).map_err(|e| format!("Invalid FGA_ACTIONS: {}", e))?,
+        Err(_) => default_actions(),
+    };
+
+    // Get the per-operation timeouts
+    let timeouts = FgaTimeouts::from_env()?;
+    tracing::info!(
+        "OpenFGA timeouts: check {:?}, list {:?}, write {:?}",
+        timeouts.check,
+        timeouts.list,
+        timeouts.write
+    );
+
+    // Get the consistency preference, rejecting typos at startup
+    let consistency = match env::var("OPENFGA_CONSISTENCY") {
+        Ok(value) => fga::parse_consistency(&value)
+            .map_err(|e| format!("Invalid OPENFGA_CONSISTENCY: {}", e))?,
+        Err(_) => fga::DEFAULT_CONSISTENCY,
+    };
+    tracing::info!("OpenFGA consistency: {}", consistency.as_str_name());
+
+    // Get the retry policy for transient failures
+    let retry = FgaRetry::from_env()?;
+    tracing::info!(
+        "OpenFGA retries: {} attempts, base delay {:?}",
+        retry.max_attempts,
+        retry.base_delay
+    );
+
+    // Get what the shared-resources endpoint lists, defaulting to the bundled model
+    let shared_object_types = env_list("SHARED_OBJECT_TYPES").unwrap_or_else(|| {
+        ["service", "service_type", "resource"]
+            .map(String::from)
+            .to_vec()
+    });
+    if let Some(unknown) = shared_object_types
+        .iter()
+        .find(|object_type| !relations_by_type.contains_key(*object_type))
+    {
+        return Err(format!("Invalid SHARED_OBJECT_TYPES: unknown type '{}'", unknown).into());
+    }
+    let shared_relations = env_list("SHARED_RELATIONS")
+        .unwrap_or_else(|| ["viewer", "editor", "admin"].map(String::from).to_vec());
+
+    Ok(OpenFgaConfig {
         store_id,
         authorization_model_id,
-    }
+        relations_by_type,
+        actions,
+        timeouts,
+        consistency,
+        retry,
+        shared_object_types,
+        shared_relations,
+    })
 }
@@ -1,27 +1,91 @@
-use crate::auth::AuthUser;
-use crate::context::Ctx;
+use crate::audit::TupleChange;
+use crate::auth::{ANONYMOUS_USER_ID, AuthUser};
+use crate::context::{Ctx, ListObjectsKey, is_valid_fga_user};
+use crate::cursor::Cursor;
+use crate::error::{ApiError, ErrorCode};
+use crate::extract::ApiJson;
+use crate::fga::{self, FgaError};
+use crate::prometheus;
+use crate::saga::Saga;
+use crate::store::{ORGANISATION_REPARENTED, Resource, ResourceKey, StoreError};
 use axum::{
     Extension,
     extract::{Json, Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header},
 };
 use openfga_client::client::{
-    CheckRequest, CheckRequestTupleKey, ListObjectsRequest, TupleKeyWithoutCondition,
+    Assertion, AssertionTupleKey, BatchCheckItem, BatchCheckRequest, CheckRequest,
+    CheckRequestTupleKey, ContextualTupleKeys, ExpandRequest, ExpandRequestTupleKey,
+    ListObjectsRequest, ListUsersRequest, Object, ReadAssertionsRequest, ReadChangesRequest,
+    ReadRequest, ReadRequestTupleKey, TupleKey, TupleKeyWithoutCondition, TupleOperation,
+    UserTypeFilter, WriteAssertionsRequest, WriteRequest, WriteRequestDeletes, WriteRequestWrites,
+    WriteResponse, batch_check_single_result, user,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
-use tonic::Request;
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Resource {
-    pub name: String,
-    pub service_name: String,
-    pub service_type: String,
-    pub org_id: String,
-    pub properties: Value,
-}
+/// Page size used when a cursor is sent without `page_size`, and by
+/// `/api/list-objects` when no paging parameters are sent
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Largest page a client may request
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// Maximum number of listed objects whose access source is classified
+const MAX_CLASSIFIED_OBJECTS: usize = 100;
+
+/// Maximum number of concurrent OpenFGA calls fanned out by a single request
+const FGA_FANOUT_CONCURRENCY: usize = 8;
+
+/// Maximum count reported per relation by the summary endpoint
+const MAX_SUMMARY_COUNT: usize = 1000;
+
+/// Maximum number of objects returned per bucket when comparing access
+const MAX_COMPARE_RESULTS: usize = 1000;
+
+/// Maximum number of users a resource can be shared with in one request
+const MAX_SHARE_USERS: usize = 100;
+
+/// Maximum number of resources created by one batch request
+const MAX_BATCH_RESOURCES: usize = 500;
+
+/// Tuples per OpenFGA `Write` call, the server's default limit
+const MAX_TUPLES_PER_WRITE: usize = 100;
+
+/// Maximum number of relations combined in one list-objects request
+const MAX_LIST_RELATIONS: usize = 10;
+
+/// Maximum number of checks in one batch-check request
+const MAX_BATCH_CHECK_ITEMS: usize = 100;
+
+/// Maximum resource IDs fetched by one batch-get, each costing one check
+const MAX_BATCH_GET_ITEMS: usize = 100;
+
+/// Maximum resource IDs deleted by one bulk delete
+const MAX_BULK_DELETE_ITEMS: usize = 100;
+
+/// Longest accepted client-supplied operation ID
+const MAX_OPERATION_ID_LEN: usize = 128;
+
+/// Maximum number of assertions OpenFGA stores per authorization model
+const MAX_ASSERTIONS: usize = 100;
+
+/// Maximum number of tuples read when listing the tuples on an object
+const MAX_OBJECT_TUPLES: usize = 1000;
+
+/// Maximum number of objects returned across all types by the access endpoint
+const MAX_ACCESS_ALL_OBJECTS: usize = 5000;
+
+/// Time budget for enumerating access across all types before returning partial results
+const ACCESS_ALL_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Debug, Deserialize)]
 pub struct ResourceParams {
@@ -31,562 +95,4521 @@ pub struct ResourceParams {
     pub name: String,
 }
 
+impl ResourceParams {
+    fn key(&self) -> ResourceKey {
+        ResourceKey {
+            service_name: self.service_name.clone(),
+            service_type: self.service_type.clone(),
+            org_id: self.org_id.clone(),
+            name: self.name.clone(),
+        }
+    }
+}
+
+/// Properties stored for a resource, taken from the request body
+fn resource_properties(payload: &Value) -> Value {
+    payload
+        .get("properties")
+        .cloned()
+        .unwrap_or_else(|| json!({}))
+}
+
+/// Reject properties that do not conform to the service type's schema with 422,
+/// listing the violations in `details`
+fn validate_properties(ctx: &Ctx, service_type: &str, properties: &Value) -> Result<(), ApiError> {
+    ctx.resource_schemas
+        .validate(service_type, properties)
+        .map_err(|violations| {
+            ApiError::new(
+                ErrorCode::InvalidProperties,
+                format!(
+                    "Properties do not match the {} schema: {}",
+                    service_type, violations[0].message
+                ),
+            )
+            .with_details(json!({ "errors": violations }))
+        })
+}
+
+/// Strong ETag of a resource version
+fn resource_etag(version: i64) -> String {
+    format!("\"{}\"", version)
+}
+
+/// Version an update must apply to, from its `If-Match` header
+///
+/// `*` matches any existing version and yields `None`. A tag that can never
+/// be one of ours, e.g. a weak `W/` tag, fails the precondition.
+fn if_match_version(headers: &HeaderMap) -> Result<Option<i64>, ApiError> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Err(ApiError::new(
+            ErrorCode::PreconditionRequired,
+            "If-Match header with the resource's ETag is required",
+        ));
+    };
+    let value = value
+        .to_str()
+        .map_err(|_| ApiError::validation("If-Match header must be valid ASCII"))?
+        .trim();
+    if value == "*" {
+        return Ok(None);
+    }
+    if value.contains(',') {
+        return Err(ApiError::validation("If-Match must name a single ETag"));
+    }
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .and_then(|version| version.parse().ok())
+        .map(Some)
+        .ok_or_else(|| {
+            ApiError::new(
+                ErrorCode::PreconditionFailed,
+                format!("If-Match {} does not match the resource", value),
+            )
+        })
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ListQueryParams {
     pub relation: Option<String>,
     pub object_type: Option<String>,
+    pub page_size: Option<usize>,
+    #[serde(alias = "continuation_token")]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DryRunQueryParams {
+    /// Report what would change without changing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminListQueryParams {
+    #[serde(rename = "type")]
+    pub object_type: Option<String>,
+    pub relation: Option<String>,
+    pub page_size: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrgResourcesQueryParams {
+    pub org_id: String,
+    pub page_size: Option<usize>,
+    #[serde(alias = "continuation_token")]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangesQueryParams {
+    #[serde(rename = "type")]
+    pub object_type: Option<String>,
+    pub page_size: Option<usize>,
+    #[serde(alias = "continuation_token")]
+    pub cursor: Option<String>,
 }
 
+/// One tuple write or delete from the OpenFGA changelog
 #[derive(Debug, Serialize)]
-pub struct ListResponse {
-    pub objects: Vec<String>,
-    pub total_count: usize,
-    pub object_type: String,
+pub struct TupleChangeView {
+    pub operation: &'static str,
+    pub user: String,
     pub relation: String,
+    pub object: String,
+    /// RFC 3339 time of the change
+    pub timestamp: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct SharedResourcesResponse {
-    pub services: Vec<SharedService>,
-    pub service_types: Vec<SharedServiceType>,
-    pub resources: Vec<SharedResource>,
+#[derive(Debug, Deserialize)]
+pub struct DiagnoseQueryParams {
+    pub user: String,
+    /// Object ID, e.g. `resource:svc/type/org/name`
+    pub resource: String,
 }
 
-#[derive(Debug, Serialize)]
-pub struct SharedService {
-    pub id: String,
-    pub name: String,
-    pub shared_via: String,
-    pub permissions: Vec<String>,
+#[derive(Debug, Deserialize)]
+pub struct WalkthroughQueryParams {
+    pub user: String,
+    /// Object ID, e.g. `resource:svc/type/org/name`
+    pub resource: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExpandQueryParams {
+    pub object: String,
+    pub relation: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListUsersQueryParams {
+    pub relation: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
-pub struct SharedServiceType {
-    pub id: String,
-    pub service_name: String,
-    pub service_type: String,
-    pub shared_via: String,
-    pub permissions: Vec<String>,
+pub struct ListUsersResponse {
+    #[serde(flatten)]
+    pub list: ListEnvelope<String>,
+    pub object: String,
+    pub relation: String,
+    /// Whether the relation is granted to every user (`user:*`)
+    pub public: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OutgoingSharesQueryParams {
+    pub page_size: Option<usize>,
+    #[serde(alias = "continuation_token")]
+    pub cursor: Option<String>,
 }
 
+/// A resource the caller owns and the other users holding relations on it
 #[derive(Debug, Serialize)]
-pub struct SharedResource {
+pub struct OutgoingShare {
     pub id: String,
     pub service_name: String,
     pub service_type: String,
+    pub org_id: String,
     pub resource_name: String,
-    pub shared_via: String,
-    pub permissions: Vec<String>,
+    /// User IDs per relation, `*` when the relation is public
+    pub shared_with: BTreeMap<String, Vec<String>>,
 }
 
-/// Check if a user has the required permission for a resource
-async fn check_permission(
-    ctx: &Arc<Ctx>,
-    user_id: &str,
-    relation: &str,
-    object_id: &str,
-) -> Result<bool, Box<dyn std::error::Error>> {
-    tracing::info!(
-        "Checking if user {} has {} permission on resource {}",
-        user_id,
-        relation,
-        object_id
-    );
-
-    // Get store ID from context
-    let store_id = &ctx.fga_config.store_id;
-    if store_id.is_empty() {
-        return Err("OpenFGA store ID not configured".into());
-    }
+#[derive(Debug, Deserialize)]
+pub struct TypeQueryParams {
+    #[serde(rename = "type")]
+    pub object_type: Option<String>,
+}
 
-    // Get authorization model ID from context
-    let authorization_model_id = match &ctx.fga_config.authorization_model_id {
-        Some(id) => id,
-        None => return Err("OpenFGA authorization model ID not configured".into()),
-    };
+/// Page metadata shared by every list response
+#[derive(Debug, Serialize)]
+pub struct PageInfo {
+    /// Number of items in this page
+    pub size: usize,
+    /// Token for the next page, `null` on the last page
+    pub next_token: Option<String>,
+    /// Items were dropped to keep the response under `MAX_RESPONSE_BYTES`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Number of items dropped, present only when truncated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub omitted: Option<usize>,
+}
 
-    // Get the OpenFGA client
-    let mut service_client = ctx.fga_client.clone();
+/// Standard envelope for list responses: `{ items: [...], page: {...} }`
+#[derive(Debug, Serialize)]
+pub struct ListEnvelope<T> {
+    pub items: Vec<T>,
+    pub page: PageInfo,
+}
 
-    // Create the tuple key for checking
-    let tuple_key = TupleKeyWithoutCondition {
-        user: format!("user:{}", user_id),
-        relation: relation.to_string(),
-        object: object_id.to_string(),
-    };
+impl<T> ListEnvelope<T> {
+    /// Wrap a complete (single page) list
+    pub fn new(items: Vec<T>) -> Self {
+        Self {
+            page: PageInfo {
+                size: items.len(),
+                next_token: None,
+                truncated: false,
+                omitted: None,
+            },
+            items,
+        }
+    }
 
-    // Create a check request using tonic::Request
-    let check_request = Request::new(CheckRequest {
-        store_id: store_id.clone(),
-        tuple_key: Some(CheckRequestTupleKey {
-            user: tuple_key.user,
-            relation: tuple_key.relation,
-            object: tuple_key.object,
-        }),
-        authorization_model_id: authorization_model_id.clone(),
-        ..Default::default()
-    });
+    /// Cut one page out of a complete list. Without `page_size` or `cursor`
+    /// the whole list is returned as a single page.
+    ///
+    /// `endpoint` and `query` are bound into the emitted cursor so it is only
+    /// accepted for the same listing.
+    pub fn paginate(
+        mut self,
+        endpoint: &str,
+        query: &str,
+        page_size: Option<usize>,
+        cursor: Option<&str>,
+    ) -> Result<Self, ApiError> {
+        if page_size.is_none() && cursor.is_none() {
+            return Ok(self);
+        }
 
-    // Perform the check
-    match service_client.check(check_request).await {
-        Ok(response) => {
-            let allowed = response.into_inner().allowed;
-            tracing::info!(
-                "Permission check result for user {} on resource {}: {}",
-                user_id,
-                object_id,
-                allowed
-            );
-            Ok(allowed)
+        let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+        if page_size == 0 || page_size > MAX_PAGE_SIZE {
+            return Err(ApiError::validation(format!(
+                "page_size must be between 1 and {}",
+                MAX_PAGE_SIZE
+            )));
         }
-        Err(e) => {
-            tracing::error!("Error checking permission with OpenFGA: {}", e);
 
-            // Check if it's a transport error (connection issue)
-            let error_msg = e.to_string();
-            if error_msg.contains("transport error") || error_msg.contains("Connection refused") {
-                tracing::error!("OpenFGA server appears to be unavailable. Please check:");
-                tracing::error!("1. OpenFGA server is running");
-                tracing::error!(
-                    "2. OPENFGA_CLIENT_URL is correct (default: http://localhost:8081)"
-                );
-                tracing::error!("3. Network connectivity to OpenFGA server");
-                return Err("OpenFGA server is not available. Please check server status and configuration.".into());
+        let offset = match cursor {
+            Some(cursor) => Cursor::decode(cursor, endpoint, query)?
+                .parse::<usize>()
+                .map_err(|_| ApiError::validation("Malformed cursor"))?,
+            None => 0,
+        };
+
+        let end = offset.saturating_add(page_size).min(self.items.len());
+        let next_token = (end < self.items.len())
+            .then(|| Cursor::new(endpoint, query, end.to_string()).encode());
+        self.items = self.items.drain(offset.min(end)..end).collect();
+        self.page.size = self.items.len();
+        self.page.next_token = next_token;
+        Ok(self)
+    }
+
+    /// Keep as many leading items as fit in `budget` serialized bytes,
+    /// deducting what they use so several lists can share one budget
+    pub fn bounded(mut self, budget: &mut usize) -> Self
+    where
+        T: Serialize,
+    {
+        let mut used = 0usize;
+        let mut kept = 0;
+        for item in &self.items {
+            // Account for the separating comma as well as the item itself
+            let size = serde_json::to_vec(item).map_or(0, |bytes| bytes.len()) + 1;
+            if used.saturating_add(size) > *budget {
+                break;
             }
+            used += size;
+            kept += 1;
+        }
+        *budget -= used;
 
-            Err(format!("OpenFGA permission check failed: {}", e).into())
+        let omitted = self.items.len() - kept;
+        if omitted > 0 {
+            self.items.truncate(kept);
+            self.page.size = kept;
+            self.page.truncated = true;
+            self.page.omitted = Some(omitted);
         }
+        self
     }
 }
 
-// Create a new resource
-pub async fn create_resource(
-    State(ctx): State<Arc<Ctx>>,
-    Extension(auth_user): Extension<AuthUser>,
-    Path(params): Path<ResourceParams>,
-    Json(_payload): Json<Value>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    tracing::info!(
-        "Creating resource: {}/{}/{}/{}",
-        params.service_name,
-        params.service_type,
-        params.org_id,
-        params.name
-    );
+/// Byte budget for the collections in a buffered response
+fn response_budget(ctx: &Ctx) -> usize {
+    ctx.max_response_bytes.unwrap_or(usize::MAX)
+}
 
-    // let resource_key = format!(
-    //     "{}/{}/{}/{}",
-    //     params.service_name, params.service_type, params.org_id, params.name
-    // );
+#[derive(Debug, Serialize)]
+pub struct ListResponse {
+    #[serde(flatten)]
+    pub list: ListEnvelope<String>,
+    pub object_type: String,
+    pub relation: String,
+}
 
-    let org_key = format!("organisation:{}", params.org_id);
+/// Objects matching any of several relations, see `list_objects`
+#[derive(Debug, Serialize)]
+pub struct MultiRelationListResponse {
+    #[serde(flatten)]
+    pub list: ListEnvelope<String>,
+    pub object_type: String,
+    pub relations: Vec<String>,
+    /// Relations each object on this page matched
+    pub matched: BTreeMap<String, Vec<String>>,
+}
 
-    // Get user ID from authentication middleware
-    let user_id = &auth_user.user_id;
+/// How a user came to hold a relation on an object
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessSource {
+    /// A tuple grants the relation to the user directly
+    Direct,
+    /// The relation is derived from groups, organisations or parent objects
+    Inherited,
+}
 
-    // To create a resource, user needs to be an admin of the organization
-    // In a real app, we would check if the user is an admin of the organization
-    // For this example, we'll check if the user has admin permission on the resource
-    match check_permission(&ctx, user_id, "admin", &org_key).await {
-        Ok(allowed) => {
-            if !allowed {
-                tracing::warn!(
-                    "User {} does not have admin permission for resource {}",
-                    user_id,
-                    org_key
-                );
-                return Err((
-                    StatusCode::FORBIDDEN,
-                    Json(json!({
-                        "error": "Permission denied",
-                        "message": "You do not have permission to create this resource"
-                    })),
-                ));
-            }
+#[derive(Debug, Serialize)]
+pub struct ObjectWithSource {
+    pub object: String,
+    /// `None` when the object was not classified
+    pub source: Option<AccessSource>,
+}
 
-            tracing::info!(
-                "User {} has admin permission for organisation {}",
-                user_id,
-                org_key
-            );
+#[derive(Debug, Serialize)]
+pub struct ListWithSourceResponse {
+    #[serde(flatten)]
+    pub list: ListEnvelope<ObjectWithSource>,
+    pub classified_count: usize,
+    /// Set when only the first objects were classified
+    pub classification_truncated: bool,
+    pub object_type: String,
+    pub relation: String,
+}
 
-            // In a real app, we would create the resource in the database
+#[derive(Debug, Deserialize)]
+pub struct CompareAccessRequest {
+    pub user_a: String,
+    pub user_b: String,
+    #[serde(rename = "type")]
+    pub object_type: String,
+    pub relation: String,
+}
 
-            Ok((
-                StatusCode::CREATED,
-                Json(json!({
-                    "message": "Resource created successfully",
-                    "organisation": params.org_id
-                })),
-            ))
-        }
-        Err(e) => {
-            tracing::error!("Error checking permission: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to check permission",
-                    "message": e.to_string()
-                })),
-            ))
+#[derive(Debug, Serialize)]
+pub struct CompareAccessResponse {
+    pub only_a: Vec<String>,
+    pub only_b: Vec<String>,
+    pub shared: Vec<String>,
+    /// Set when any bucket was cut at `MAX_COMPARE_RESULTS`
+    pub truncated: bool,
+}
+
+/// A relationship tuple as returned in responses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TupleView {
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+}
+
+/// An authorization model assertion: whether `user` is expected to hold
+/// `relation` on `object`, given the optional contextual tuples
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssertionView {
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+    pub expectation: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contextual_tuples: Vec<TupleView>,
+}
+
+impl From<Assertion> for AssertionView {
+    fn from(assertion: Assertion) -> Self {
+        let key = assertion.tuple_key.unwrap_or_default();
+        Self {
+            user: key.user,
+            relation: key.relation,
+            object: key.object,
+            expectation: assertion.expectation,
+            contextual_tuples: assertion
+                .contextual_tuples
+                .into_iter()
+                .map(|tuple| TupleView {
+                    user: tuple.user,
+                    relation: tuple.relation,
+                    object: tuple.object,
+                })
+                .collect(),
         }
     }
 }
 
-// Update an existing resource
-pub async fn update_resource(
-    State(ctx): State<Arc<Ctx>>,
-    Extension(auth_user): Extension<AuthUser>,
-    Path(params): Path<ResourceParams>,
-    Json(_payload): Json<Value>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    tracing::info!(
-        "Updating resource: {}/{}/{}/{}",
-        params.service_name,
-        params.service_type,
-        params.org_id,
-        params.name
-    );
+impl AssertionView {
+    fn contextual_tuple_keys(&self) -> Vec<TupleKey> {
+        self.contextual_tuples
+            .iter()
+            .map(|tuple| TupleKey {
+                user: tuple.user.clone(),
+                relation: tuple.relation.clone(),
+                object: tuple.object.clone(),
+                ..Default::default()
+            })
+            .collect()
+    }
+}
 
-    let resource_key = format!(
-        "{}/{}/{}/{}",
-        params.service_name, params.service_type, params.org_id, params.name
-    );
+#[derive(Debug, Deserialize)]
+pub struct WriteAssertionsBody {
+    pub assertions: Vec<AssertionView>,
+}
 
-    // Get user ID from authentication middleware
-    let user_id = &auth_user.user_id;
+/// Outcome of running one stored assertion
+#[derive(Debug, Serialize)]
+pub struct AssertionResult {
+    #[serde(flatten)]
+    pub assertion: AssertionView,
+    /// Check result, absent when the check failed
+    pub allowed: Option<bool>,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
 
-    // To update a resource, user needs to be an editor of the resource
-    match check_permission(&ctx, user_id, "editor", &resource_key).await {
-        Ok(allowed) => {
-            if !allowed {
-                tracing::warn!(
-                    "User {} does not have editor permission for resource {}",
-                    user_id,
-                    resource_key
-                );
-                return Err((
-                    StatusCode::FORBIDDEN,
-                    Json(json!({
-                        "error": "Permission denied",
-                        "message": "You do not have permission to update this resource"
-                    })),
-                ));
+/// Body of grant and revoke requests
+#[derive(Debug, Deserialize)]
+pub struct PermissionRequest {
+    /// FGA user, e.g. `user:alice` or `group:eng#member`
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+    /// Client-supplied ID of the business operation the change belongs to,
+    /// recorded with the change; retrying with the same ID does not write again
+    #[serde(default)]
+    pub operation_id: Option<String>,
+}
+
+/// A tuple change recorded under an operation ID
+#[derive(Debug, Serialize)]
+pub struct OperationChangeView {
+    pub operation: String,
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+    pub changed_by: String,
+    /// RFC 3339 time of the change
+    pub timestamp: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OperationResponse {
+    pub operation_id: String,
+    #[serde(flatten)]
+    pub list: ListEnvelope<OperationChangeView>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReparentRequest {
+    pub parent_org_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShareRequest {
+    pub relation: String,
+    pub users: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShareObjectRequest {
+    /// `service:<service>`, `service_type:<service>/<type>` or
+    /// `resource:<service>/<type>/<org>/<name>`
+    pub object: String,
+    /// User ID to share with, without the `user:` prefix
+    pub user: String,
+    pub relation: String,
+}
+
+/// Outcome of granting a relation to one user
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareStatus {
+    Granted,
+    AlreadyGranted,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchCreateRequest {
+    /// Organisation every resource is created in
+    pub org_id: String,
+    pub resources: Vec<BatchResourceRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchResourceRequest {
+    pub service_name: String,
+    pub service_type: String,
+    pub name: String,
+    #[serde(default)]
+    pub properties: Option<Value>,
+}
+
+/// Outcome of creating one resource in a batch
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchCreateStatus {
+    Created,
+    /// A resource with the same key already exists
+    Conflict,
+    /// The item failed validation and was not attempted
+    Invalid,
+    /// Storing the resource or assigning its owner failed
+    Failed,
+}
+
+/// Outcome of one batch item, in the same position as in the request
+#[derive(Debug, Serialize)]
+pub struct BatchCreateResult {
+    pub resource_id: String,
+    pub status: BatchCreateStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource: Option<Resource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CanRequest {
+    pub relation: String,
+    pub object: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchCheckItemRequest {
+    pub relation: String,
+    pub object: String,
+}
+
+/// Outcome of one batch-check item, in the same position as in the request
+#[derive(Debug, Serialize)]
+pub struct BatchCheckResult {
+    pub relation: String,
+    pub object: String,
+    pub allowed: bool,
+    /// Set when OpenFGA could not evaluate this item; `allowed` is then false
+    pub error: Option<String>,
+}
+
+/// Outcome of fetching one resource in a batch-get
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchGetResult {
+    Found {
+        resource: Resource,
+    },
+    Forbidden,
+    NotFound,
+    /// OpenFGA could not evaluate the viewer check
+    Error {
+        error: String,
+    },
+}
+
+/// Outcome of deleting one resource in a bulk delete
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BulkDeleteResult {
+    Deleted {
+        tuples_removed: usize,
+    },
+    Forbidden,
+    NotFound,
+    /// Some of its tuples could not be removed, so the row was kept
+    CleanupFailed {
+        error: String,
+    },
+    /// OpenFGA could not evaluate the owner check
+    Error {
+        error: String,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareResult {
+    pub user: String,
+    pub status: ShareStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObjectRelations {
+    pub object: String,
+    pub relations: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AllAccessResponse {
+    /// Accessible objects grouped by type
+    pub types: BTreeMap<String, Vec<ObjectRelations>>,
+    /// Set when objects were dropped to stay within `MAX_ACCESS_ALL_OBJECTS`
+    pub truncated: bool,
+    /// Set when some lookups failed or did not finish in time
+    pub partial: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccessSummaryResponse {
+    pub object_type: String,
+    /// Number of objects per relation, capped at `MAX_SUMMARY_COUNT`
+    pub counts: BTreeMap<String, usize>,
+    /// Relations whose count reached the cap
+    pub capped: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SharedResourcesResponse {
+    pub services: ListEnvelope<SharedService>,
+    pub service_types: ListEnvelope<SharedServiceType>,
+    pub resources: ListEnvelope<SharedResource>,
+    /// Objects of configured types without a dedicated section, by type
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub other: BTreeMap<String, ListEnvelope<SharedObject>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SharedObject {
+    pub id: String,
+    pub shared_via: String,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SharedService {
+    pub id: String,
+    pub name: String,
+    pub shared_via: String,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SharedServiceType {
+    pub id: String,
+    pub service_name: String,
+    pub service_type: String,
+    pub shared_via: String,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SharedResource {
+    pub id: String,
+    pub service_name: String,
+    pub service_type: String,
+    pub org_id: String,
+    pub resource_name: String,
+    pub shared_via: String,
+    pub permissions: Vec<String>,
+}
+
+/// A share written by `share_resource`, shaped like the shared-resources entries
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ShareDescriptor {
+    Service(SharedService),
+    ServiceType(SharedServiceType),
+    Resource(SharedResource),
+}
+
+impl ShareDescriptor {
+    /// Describe a directly shared service, service type or resource object,
+    /// `None` for any other object
+    fn parse(object: &str, relation: &str) -> Option<Self> {
+        let id = object.to_string();
+        let shared_via = "direct".to_string();
+        let permissions = vec![relation.to_string()];
+        let (object_type, path) = object.split_once(':')?;
+        let parts: Vec<&str> = path.split('/').collect();
+        if parts.iter().any(|part| part.is_empty()) {
+            return None;
+        }
+
+        match (object_type, parts.as_slice()) {
+            ("service", [name]) => Some(Self::Service(SharedService {
+                id,
+                name: name.to_string(),
+                shared_via,
+                permissions,
+            })),
+            ("service_type", [service_name, service_type]) => {
+                Some(Self::ServiceType(SharedServiceType {
+                    id,
+                    service_name: service_name.to_string(),
+                    service_type: service_type.to_string(),
+                    shared_via,
+                    permissions,
+                }))
+            }
+            ("resource", [service_name, service_type, org_id, resource_name]) => {
+                Some(Self::Resource(SharedResource {
+                    id,
+                    service_name: service_name.to_string(),
+                    service_type: service_type.to_string(),
+                    org_id: org_id.to_string(),
+                    resource_name: resource_name.to_string(),
+                    shared_via,
+                    permissions,
+                }))
             }
+            _ => None,
+        }
+    }
+
+    fn object_type(&self) -> &'static str {
+        match self {
+            Self::Service(_) => "service",
+            Self::ServiceType(_) => "service_type",
+            Self::Resource(_) => "resource",
+        }
+    }
+}
+
+/// Resolve the configured store and authorization model IDs
+fn fga_ids(ctx: &Ctx) -> Result<(&str, &str), FgaError> {
+    // Get store ID from context
+    let store_id = &ctx.fga_config.store_id;
+    if store_id.is_empty() {
+        return Err(FgaError::StoreNotConfigured);
+    }
+
+    // Get authorization model ID from context
+    let authorization_model_id = match &ctx.fga_config.authorization_model_id {
+        Some(id) => id,
+        None => return Err(FgaError::ModelNotConfigured),
+    };
+
+    Ok((store_id, authorization_model_id))
+}
+
+/// Check if a user has the required permission for a resource
+#[tracing::instrument(name = "fga.check", skip(ctx), fields(allowed))]
+async fn check_permission(
+    ctx: &Arc<Ctx>,
+    user_id: &str,
+    relation: &str,
+    object_id: &str,
+) -> Result<bool, FgaError> {
+    tracing::info!(
+        user_id,
+        relation,
+        object = object_id,
+        "Checking if user {} has {} permission on {}",
+        user_id,
+        relation,
+        object_id
+    );
+
+    let cache_key = (
+        user_id.to_string(),
+        relation.to_string(),
+        object_id.to_string(),
+    );
+    if let Some(allowed) = ctx.check_cache.get(&cache_key) {
+        tracing::Span::current().record("allowed", allowed);
+        tracing::debug!(
+            user_id,
+            relation,
+            object = object_id,
+            allowed,
+            cached = true,
+            "Permission check served from cache: {}",
+            allowed
+        );
+        return Ok(allowed);
+    }
+
+    let (store_id, authorization_model_id) = fga_ids(ctx)?;
+
+    // Create the tuple key for checking
+    let tuple_key = TupleKeyWithoutCondition {
+        user: fga_user(ctx, user_id),
+        relation: relation.to_string(),
+        object: object_id.to_string(),
+    };
+
+    let check_request = CheckRequest {
+        store_id: store_id.to_string(),
+        tuple_key: Some(CheckRequestTupleKey {
+            user: tuple_key.user,
+            relation: tuple_key.relation,
+            object: tuple_key.object,
+        }),
+        authorization_model_id: authorization_model_id.to_string(),
+        consistency: ctx.fga_config.consistency as i32,
+        ..Default::default()
+    };
 
+    // Perform the check, retrying while OpenFGA is unreachable
+    let started = Instant::now();
+    let result = fga::with_retry(&ctx.fga_config.retry, "check", || {
+        let service_client = ctx.fga_client();
+        let request = fga::request(check_request.clone(), ctx.fga_config.timeouts.check);
+        async move { service_client.check(request).await }
+    })
+    .await;
+    ctx.stats.record_check(started.elapsed());
+    let result = result.map(|response| response.into_inner().allowed);
+    prometheus::record_check(relation, object_id, started.elapsed(), &result);
+
+    match result {
+        Ok(allowed) => {
+            tracing::Span::current().record("allowed", allowed);
             tracing::info!(
-                "User {} has editor permission for resource {}",
                 user_id,
-                resource_key
+                relation,
+                object = object_id,
+                allowed,
+                "Permission check result for user {} on {}: {}",
+                user_id,
+                object_id,
+                if allowed { "allowed" } else { "denied" }
+            );
+            ctx.check_cache.insert(cache_key, allowed);
+            Ok(allowed)
+        }
+        Err(e) => {
+            tracing::error!(
+                user_id,
+                relation,
+                object = object_id,
+                error = %e,
+                "Error checking permission with OpenFGA: {}",
+                e
             );
 
-            // In a real app, we would update the resource in the database
+            // Retries are exhausted if the server is still unreachable
+            let e = FgaError::from(e);
+            if let FgaError::Unavailable(_) = e {
+                tracing::error!("OpenFGA server appears to be unavailable. Please check:");
+                tracing::error!("1. OpenFGA server is running");
+                tracing::error!(
+                    "2. OPENFGA_CLIENT_URL is correct (default: http://localhost:8081)"
+                );
+                tracing::error!("3. Network connectivity to OpenFGA server");
+            }
+            // The HTTP status follows the gRPC code, see ErrorCode::from_grpc
+            Err(e)
+        }
+    }
+}
 
-            Ok((
-                StatusCode::OK,
-                Json(json!({
-                    "message": "Resource updated successfully",
-                    "resource_id": resource_key
-                })),
-            ))
+/// Build the OpenFGA request that writes and deletes the given tuples
+fn write_request(
+    ctx: &Ctx,
+    writes: Vec<TupleKey>,
+    deletes: Vec<TupleKeyWithoutCondition>,
+) -> WriteRequest {
+    WriteRequest {
+        store_id: ctx.fga_config.store_id.clone(),
+        authorization_model_id: ctx
+            .fga_config
+            .authorization_model_id
+            .clone()
+            .unwrap_or_default(),
+        writes: (!writes.is_empty()).then(|| WriteRequestWrites {
+            tuple_keys: writes,
+            ..Default::default()
+        }),
+        deletes: (!deletes.is_empty()).then(|| WriteRequestDeletes {
+            tuple_keys: deletes,
+            ..Default::default()
+        }),
+    }
+}
+
+/// Response to a `?dry_run=true` request: the tuple changes the write would
+/// have made, without making them
+fn dry_run_response(request: &WriteRequest) -> (StatusCode, Json<Value>) {
+    let writes: Vec<TupleView> = request
+        .writes
+        .iter()
+        .flat_map(|writes| &writes.tuple_keys)
+        .map(|key| TupleView {
+            user: key.user.clone(),
+            relation: key.relation.clone(),
+            object: key.object.clone(),
+        })
+        .collect();
+    let deletes: Vec<TupleView> = request
+        .deletes
+        .iter()
+        .flat_map(|deletes| &deletes.tuple_keys)
+        .map(|key| TupleView {
+            user: key.user.clone(),
+            relation: key.relation.clone(),
+            object: key.object.clone(),
+        })
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(json!({
+            "dry_run": true,
+            "writes": writes,
+            "deletes": deletes
+        })),
+    )
+}
+
+/// Write and delete tuples in a single OpenFGA transaction
+///
+/// Returns how many cached check results the change invalidated.
+#[tracing::instrument(name = "fga.write", skip_all, fields(writes = writes.len(), deletes = deletes.len()))]
+async fn fga_write(
+    ctx: &Ctx,
+    writes: Vec<TupleKey>,
+    deletes: Vec<TupleKeyWithoutCondition>,
+) -> Result<usize, tonic::Status> {
+    let write_request = write_request(ctx, writes, deletes);
+
+    // Writes are not idempotent: an attempt that reached OpenFGA but lost its
+    // response makes the retry fail on tuples the first attempt applied
+    let mut attempt = 0;
+    fga::with_retry(&ctx.fga_config.retry, "write", || {
+        attempt += 1;
+        let retried = attempt > 1;
+        let client = ctx.fga_client();
+        let request = fga::request(write_request.clone(), ctx.fga_config.timeouts.write);
+        async move {
+            match client.write(request).await {
+                Err(e) if retried && fga::is_already_applied(&e) => {
+                    tracing::warn!("Treating retried OpenFGA write as applied: {}", e);
+                    Ok(tonic::Response::new(WriteResponse::default()))
+                }
+                result => result,
+            }
         }
-        Err(e) => {
-            tracing::error!("Error checking permission: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to check permission",
-                    "message": e.to_string()
-                })),
-            ))
+    })
+    .await?;
+    Ok(invalidate_checks(ctx))
+}
+
+/// Drop every cached check result after tuples change
+///
+/// A single tuple can change checks on any object that inherits through it,
+/// so the affected entries cannot be singled out. Returns how many were dropped.
+fn invalidate_checks(ctx: &Ctx) -> usize {
+    let dropped = ctx.check_cache.clear();
+    if dropped > 0 {
+        tracing::debug!("Invalidated {} cached check results", dropped);
+    }
+    dropped
+}
+
+/// Drop the cached shared-resources responses that a tuple on `subject` can
+/// change
+///
+/// A single user only changes their own response. Any other subject, such as
+/// `group:eng#member` or `user:*`, stands for users that cannot be singled
+/// out, so every response is dropped.
+fn invalidate_shared_resources(ctx: &Ctx, subject: &str) {
+    match subject.strip_prefix("user:") {
+        Some(user_id) if user_id != "*" => {
+            ctx.shared_resources_cache.invalidate(&user_id.to_string());
+        }
+        _ => {
+            ctx.shared_resources_cache.clear();
+        }
+    }
+}
+
+/// Read the tuples that reference an object, up to `MAX_OBJECT_TUPLES`
+///
+/// Returns the tuples and whether more were left unread.
+async fn read_object_tuples(
+    ctx: &Ctx,
+    object: &str,
+) -> Result<(Vec<TupleView>, bool), tonic::Status> {
+    let mut tuples = Vec::new();
+    let mut continuation_token = String::new();
+
+    loop {
+        let request = fga::request(
+            ReadRequest {
+                store_id: ctx.fga_config.store_id.clone(),
+                tuple_key: Some(ReadRequestTupleKey {
+                    object: object.to_string(),
+                    ..Default::default()
+                }),
+                continuation_token,
+                consistency: ctx.fga_config.consistency as i32,
+                ..Default::default()
+            },
+            ctx.fga_config.timeouts.list,
+        );
+        let response = ctx.fga_client().read(request).await?.into_inner();
+
+        tuples.extend(response.tuples.into_iter().filter_map(|tuple| {
+            tuple.key.map(|key| TupleView {
+                user: key.user,
+                relation: key.relation,
+                object: key.object,
+            })
+        }));
+
+        if response.continuation_token.is_empty() {
+            return Ok((tuples, false));
+        }
+        if tuples.len() >= MAX_OBJECT_TUPLES {
+            return Ok((tuples, true));
+        }
+        continuation_token = response.continuation_token;
+    }
+}
+
+/// Delete every tuple that references an object, in writes of at most
+/// `MAX_TUPLES_PER_WRITE` tuples, returning how many were removed
+async fn delete_object_tuples(ctx: &Ctx, object: &str) -> Result<usize, tonic::Status> {
+    let mut removed = 0;
+    loop {
+        let (tuples, truncated) = read_object_tuples(ctx, object).await?;
+        for chunk in tuples.chunks(MAX_TUPLES_PER_WRITE) {
+            let deletes = chunk
+                .iter()
+                .map(|tuple| TupleKeyWithoutCondition {
+                    user: tuple.user.clone(),
+                    relation: tuple.relation.clone(),
+                    object: tuple.object.clone(),
+                })
+                .collect();
+            fga_write(ctx, Vec::new(), deletes).await?;
+            removed += chunk.len();
+        }
+
+        // Users who held a relation on the object see different shared resources
+        for tuple in &tuples {
+            invalidate_shared_resources(ctx, &tuple.user);
+        }
+
+        // Reads stop at `MAX_OBJECT_TUPLES`, read again for the rest
+        if !truncated {
+            return Ok(removed);
+        }
+    }
+}
+
+/// List objects of a type the user has a relation on, sharing the upstream call
+/// with any concurrent identical query
+#[tracing::instrument(name = "fga.list_objects", skip(ctx))]
+async fn fga_list_objects(
+    ctx: &Arc<Ctx>,
+    user: &str,
+    relation: &str,
+    object_type: &str,
+) -> Result<Vec<String>, tonic::Status> {
+    let key = ListObjectsKey {
+        store_id: ctx.fga_config.store_id.clone(),
+        authorization_model_id: ctx
+            .fga_config
+            .authorization_model_id
+            .clone()
+            .unwrap_or_default(),
+        consistency: ctx.fga_config.consistency as i32,
+        object_type: object_type.to_string(),
+        relation: relation.to_string(),
+        user: fga_user(ctx, user),
+    };
+
+    ctx.list_objects_flights
+        .run(key.clone(), || async move {
+            let (relation, object_type) = (key.relation.clone(), key.object_type.clone());
+            let list_request = ListObjectsRequest {
+                store_id: key.store_id,
+                authorization_model_id: key.authorization_model_id,
+                r#type: key.object_type,
+                consistency: key.consistency,
+                relation: key.relation,
+                user: key.user,
+                contextual_tuples: None,
+                context: None,
+            };
+
+            let started = Instant::now();
+            let result = fga::with_retry(&ctx.fga_config.retry, "list_objects", || {
+                let client = ctx.fga_client();
+                let request = fga::request(list_request.clone(), ctx.fga_config.timeouts.list);
+                async move { client.list_objects(request).await }
+            })
+            .await;
+            ctx.stats.record_list_objects(started.elapsed());
+            prometheus::record_list_objects(&relation, &object_type, started.elapsed(), &result);
+
+            result.map(|response| response.into_inner().objects)
+        })
+        .await
+}
+
+/// FGA principal of an authenticated user ID
+///
+/// Every check, list and write must go through this so a bare ID never
+/// reaches OpenFGA without its type. Anonymous callers map to `ANONYMOUS_USER`.
+fn fga_user(ctx: &Ctx, user_id: &str) -> String {
+    if user_id == ANONYMOUS_USER_ID {
+        return ctx.anonymous_user.clone();
+    }
+    format!("user:{}", user_id)
+}
+
+/// FGA object id of a resource
+fn resource_object(params: &ResourceParams) -> String {
+    resource_key_object(&params.key())
+}
+
+/// FGA object id of a stored resource
+fn resource_key_object(key: &ResourceKey) -> String {
+    format!(
+        "resource:{}/{}/{}/{}",
+        key.service_name, key.service_type, key.org_id, key.name
+    )
+}
+
+/// Parse a `resource:svc/type/org/name` object ID back into its key
+fn resource_key_from_object(object: &str) -> Option<ResourceKey> {
+    let mut parts = object.strip_prefix("resource:")?.splitn(4, '/');
+    Some(ResourceKey {
+        service_name: parts.next()?.to_string(),
+        service_type: parts.next()?.to_string(),
+        org_id: parts.next()?.to_string(),
+        name: parts.next()?.to_string(),
+    })
+}
+
+/// FGA object id of an organisation
+fn org_object(org_id: &str) -> String {
+    format!("organisation:{}", org_id)
+}
+
+/// When `ENFORCE_ORG_MEMBERSHIP` is set, require the user to be a member of
+/// the organisation a resource belongs to, on top of the resource relation
+async fn enforce_org_membership(
+    ctx: &Arc<Ctx>,
+    user_id: &str,
+    org_id: &str,
+) -> Result<(), ApiError> {
+    if !ctx.enforce_org_membership {
+        return Ok(());
+    }
+
+    if check_permission(ctx, user_id, "member", &org_object(org_id)).await? {
+        return Ok(());
+    }
+
+    tracing::warn!(
+        "User {} is not a member of organisation {}, rejecting resource access",
+        user_id,
+        org_id
+    );
+    Err(ApiError::new(
+        ErrorCode::OrgMismatch,
+        "You are not a member of the organisation that owns this resource",
+    ))
+}
+
+/// Ensure the user may call admin endpoints, either by being listed in
+/// `ADMIN_USERS` or by holding the admin relation on `ADMIN_OBJECT`
+async fn require_admin(ctx: &Arc<Ctx>, user_id: &str) -> Result<(), ApiError> {
+    if ctx.admin.user_ids.iter().any(|admin| admin == user_id) {
+        return Ok(());
+    }
+
+    if let Some(object) = &ctx.admin.object
+        && check_permission(ctx, user_id, &ctx.admin.relation, object).await?
+    {
+        return Ok(());
+    }
+
+    tracing::warn!("User {} denied access to admin endpoint", user_id);
+    Err(ApiError::forbidden("Admin access is required"))
+}
+
+/// List the relations and action mappings supported for an object type
+pub async fn get_type_relations(
+    State(ctx): State<Arc<Ctx>>,
+    Path(object_type): Path<String>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let Some(relations) = ctx.fga_config.relations_by_type.get(&object_type) else {
+        return Err(ApiError::not_found(format!(
+            "Type '{}' is not supported",
+            object_type
+        )));
+    };
+
+    // Only report actions whose relation exists on this type
+    let actions: BTreeMap<&String, &String> = ctx
+        .fga_config
+        .actions
+        .iter()
+        .filter(|(_, relation)| relations.contains(relation))
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "type": object_type,
+            "relations": relations,
+            "actions": actions,
+            "model": fga::model_info(&ctx).await
+        })),
+    ))
+}
+
+/// Endpoints that accept `page_size` and `cursor`
+const PAGED_ENDPOINTS: &[&str] = &[
+    "/api/list-objects",
+    "/api/resources/with-source",
+    "/api/admin/users/{user_id}/objects",
+    "/api/admin/changes",
+    "/api/resources",
+];
+
+/// Default and largest page of changelog entries; OpenFGA caps ReadChanges at 100
+const DEFAULT_CHANGES_PAGE_SIZE: usize = 50;
+const MAX_CHANGES_PAGE_SIZE: usize = 100;
+
+/// Describe what the service supports: object types and relations, action
+/// mappings, consistency, paging and API version
+pub async fn get_capabilities(
+    State(ctx): State<Arc<Ctx>>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let types: BTreeMap<&String, Value> = ctx
+        .fga_config
+        .relations_by_type
+        .iter()
+        .map(|(object_type, relations)| {
+            // Only report actions whose relation exists on this type
+            let actions: BTreeMap<&String, &String> = ctx
+                .fga_config
+                .actions
+                .iter()
+                .filter(|(_, relation)| relations.contains(relation))
+                .collect();
+            (
+                object_type,
+                json!({ "relations": relations, "actions": actions }),
+            )
+        })
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "api_version": env!("CARGO_PKG_VERSION"),
+            "types": types,
+            "actions": ctx.fga_config.actions,
+            // Consistency is configured server-wide and not selectable per request
+            "consistency": ctx.fga_config.consistency.as_str_name(),
+            "paging": {
+                "cursor": true,
+                "max_page_size": MAX_PAGE_SIZE,
+                "endpoints": PAGED_ENDPOINTS
+            },
+            "max_response_bytes": ctx.max_response_bytes,
+            "model": fga::model_info(&ctx).await
+        })),
+    ))
+}
+
+/// Report the highest configured role the user holds on an organisation
+pub async fn get_my_org_role(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(org_id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let org_key = org_object(&org_id);
+    let user_id = &auth_user.user_id;
+
+    // Roles are ordered highest first, so the first match wins
+    let mut role = None;
+    for relation in &ctx.org_roles {
+        if check_permission(&ctx, user_id, relation, &org_key).await? {
+            role = Some(relation.clone());
+            break;
+        }
+    }
+
+    tracing::info!(
+        "User {} has role {:?} in organisation {}",
+        user_id,
+        role,
+        org_id
+    );
+
+    if role.is_none() && ctx.hide_unknown_orgs {
+        return Err(ApiError::not_found(format!(
+            "Organisation '{}' not found",
+            org_id
+        )));
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "org_id": org_id,
+            "role": role.unwrap_or_else(|| "none".to_string())
+        })),
+    ))
+}
+
+/// Grant a relation on a resource to many users in a single write
+pub async fn share_resource_with_users(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(params): Path<ResourceParams>,
+    ApiJson(payload): ApiJson<ShareRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let object = resource_object(&params);
+    let user_id = &auth_user.user_id;
+
+    // Validate the request before touching OpenFGA
+    let valid_relation = ctx
+        .fga_config
+        .relations_by_type
+        .get("resource")
+        .is_some_and(|relations| relations.contains(&payload.relation));
+    if !valid_relation {
+        return Err(ApiError::validation(format!(
+            "Relation '{}' is not valid for resources",
+            payload.relation
+        )));
+    }
+    if payload.users.is_empty() || payload.users.len() > MAX_SHARE_USERS {
+        return Err(ApiError::validation(format!(
+            "Between 1 and {} users must be given",
+            MAX_SHARE_USERS
+        )));
+    }
+    if payload.users.iter().any(|user| user.trim().is_empty()) {
+        return Err(ApiError::validation("User IDs cannot be empty"));
+    }
+    // Entries such as `*`, `team#member` or `user:bob` would write a
+    // different principal than the plain user ID they look like
+    if let Some(user) = payload
+        .users
+        .iter()
+        .find(|user| !ctx.user_id_pattern.is_match(user))
+    {
+        return Err(ApiError::validation(format!(
+            "User ID '{}' must match the pattern {}",
+            user,
+            ctx.user_id_pattern.as_str()
+        )));
+    }
+
+    enforce_org_membership(&ctx, user_id, &params.org_id).await?;
+
+    // Sharing requires admin on the resource, which owners also hold
+    if !check_permission(&ctx, user_id, "admin", &object).await? {
+        tracing::warn!("User {} cannot share resource {}", user_id, object);
+        return Err(ApiError::forbidden(
+            "You do not have permission to share this resource",
+        ));
+    }
+
+    // Skip users that already hold the relation directly so the write is idempotent
+    let mut users = payload.users;
+    users.sort();
+    users.dedup();
+
+    let semaphore = Arc::new(Semaphore::new(FGA_FANOUT_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for (index, user) in users.iter().enumerate() {
+        let ctx = ctx.clone();
+        let semaphore = semaphore.clone();
+        let user = user.clone();
+        let relation = payload.relation.clone();
+        let object = object.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            (
+                index,
+                has_direct_tuple(&ctx, &user, &relation, &object).await,
+            )
+        });
+    }
+
+    let mut granted = vec![false; users.len()];
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok((index, Ok(already_granted))) => granted[index] = already_granted,
+            Ok((_, Err(e))) => return Err(ApiError::fga("Failed to read existing grants", &e)),
+            Err(e) => {
+                tracing::error!("Grant lookup task failed: {}", e);
+                return Err(ApiError::new(
+                    ErrorCode::Internal,
+                    "Failed to read existing grants",
+                ));
+            }
+        }
+    }
+
+    let mut results = Vec::with_capacity(users.len());
+    let mut writes = Vec::new();
+    for (user, already_granted) in users.into_iter().zip(granted) {
+        if !already_granted {
+            writes.push(TupleKey {
+                user: fga_user(&ctx, &user),
+                relation: payload.relation.clone(),
+                object: object.clone(),
+                ..Default::default()
+            });
+        }
+        results.push(ShareResult {
+            user,
+            status: if already_granted {
+                ShareStatus::AlreadyGranted
+            } else {
+                ShareStatus::Granted
+            },
+        });
+    }
+
+    if !writes.is_empty() {
+        fga_write(&ctx, writes, Vec::new()).await.map_err(|e| {
+            tracing::error!("Error sharing resource {}: {}", object, e);
+            ApiError::fga("Failed to write tuples", &e)
+        })?;
+    }
+
+    // Newly granted users see different shared resources
+    for result in &results {
+        if matches!(result.status, ShareStatus::Granted) {
+            ctx.shared_resources_cache.invalidate(&result.user);
+        }
+    }
+
+    tracing::info!(
+        "User {} shared {} as {} with {} users",
+        user_id,
+        object,
+        payload.relation,
+        results.len()
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "object": object,
+            "relation": payload.relation,
+            "results": results
+        })),
+    ))
+}
+
+/// Share a service, service type or resource with a user by writing one tuple
+///
+/// Resources can only be shared by their owners. Services and service types
+/// define no owner relation, so admin is required on them instead.
+pub async fn share_resource(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    ApiJson(payload): ApiJson<ShareObjectRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let user_id = &auth_user.user_id;
+    let object = &payload.object;
+
+    // Validate the request before touching OpenFGA
+    let Some(share) = ShareDescriptor::parse(object, &payload.relation) else {
+        return Err(ApiError::validation(format!(
+            "Invalid object '{}', expected service:<service>, service_type:<service>/<type> \
+             or resource:<service>/<type>/<org>/<name>",
+            object
+        )));
+    };
+    let object_type = share.object_type();
+    let valid_relation = ctx
+        .fga_config
+        .relations_by_type
+        .get(object_type)
+        .is_some_and(|relations| relations.contains(&payload.relation));
+    if !valid_relation {
+        return Err(ApiError::validation(format!(
+            "Relation '{}' is not valid for type '{}'",
+            payload.relation, object_type
+        )));
+    }
+    if !ctx.user_id_pattern.is_match(&payload.user) {
+        return Err(ApiError::validation(format!(
+            "User ID must match the pattern {}",
+            ctx.user_id_pattern.as_str()
+        )));
+    }
+
+    let required = if let Some(key) = resource_key_from_object(object) {
+        enforce_org_membership(&ctx, user_id, &key.org_id).await?;
+        "owner"
+    } else {
+        "admin"
+    };
+    if !check_permission(&ctx, user_id, required, object).await? {
+        tracing::warn!(
+            "User {} is not {} of {} and cannot share it",
+            user_id,
+            required,
+            object
+        );
+        return Err(ApiError::forbidden(format!(
+            "Sharing this {} requires the {} relation",
+            object_type, required
+        )));
+    }
+
+    // Leave an existing direct grant alone so sharing twice is harmless
+    let already_granted = has_direct_tuple(&ctx, &payload.user, &payload.relation, object)
+        .await
+        .map_err(|e| ApiError::fga("Failed to read existing grants", &e))?;
+    if !already_granted {
+        let tuple = TupleKey {
+            user: fga_user(&ctx, &payload.user),
+            relation: payload.relation.clone(),
+            object: object.clone(),
+            ..Default::default()
+        };
+        fga_write(&ctx, vec![tuple], Vec::new())
+            .await
+            .map_err(|e| {
+                tracing::error!("Error sharing {}: {}", object, e);
+                ApiError::fga("Failed to write tuples", &e)
+            })?;
+        ctx.shared_resources_cache.invalidate(&payload.user);
+
+        tracing::info!(
+            target: "audit",
+            granted_by = %user_id,
+            user = %payload.user,
+            relation = %payload.relation,
+            object = %object,
+            "Object shared"
+        );
+    }
+
+    let (status_code, status) = if already_granted {
+        (StatusCode::OK, ShareStatus::AlreadyGranted)
+    } else {
+        (StatusCode::CREATED, ShareStatus::Granted)
+    };
+    Ok((
+        status_code,
+        Json(json!({
+            "user": payload.user,
+            "status": status,
+            "share": share
+        })),
+    ))
+}
+
+// Create a new resource
+pub async fn create_resource(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(params): Path<ResourceParams>,
+    ApiJson(payload): ApiJson<Value>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    tracing::info!(
+        "Creating resource: {}/{}/{}/{}",
+        params.service_name,
+        params.service_type,
+        params.org_id,
+        params.name
+    );
+
+    // let resource_key = format!(
+    //     "{}/{}/{}/{}",
+    //     params.service_name, params.service_type, params.org_id, params.name
+    // );
+
+    let org_key = org_object(&params.org_id);
+
+    // Get user ID from authentication middleware
+    let user_id = &auth_user.user_id;
+
+    // To create a resource, user needs to be an admin of the organization
+    // In a real app, we would check if the user is an admin of the organization
+    // For this example, we'll check if the user has admin permission on the resource
+    match check_permission(&ctx, user_id, "admin", &org_key).await {
+        Ok(allowed) => {
+            if !allowed {
+                tracing::warn!(
+                    "User {} does not have admin permission for resource {}",
+                    user_id,
+                    org_key
+                );
+                return Err(ApiError::forbidden(
+                    "You do not have permission to create this resource",
+                ));
+            }
+
+            tracing::info!(
+                "User {} has admin permission for organisation {}",
+                user_id,
+                org_key
+            );
+
+            let properties = resource_properties(&payload);
+            validate_properties(&ctx, &params.service_type, &properties)?;
+
+            // Each completed step registers its undo, run in reverse if a
+            // later step fails so no row or tuple is left behind
+            let mut saga = Saga::new("Resource creation");
+
+            let resource = ctx
+                .resources
+                .create(Resource {
+                    id: Uuid::new_v4(),
+                    properties,
+                    name: params.name.clone(),
+                    service_name: params.service_name.clone(),
+                    service_type: params.service_type.clone(),
+                    org_id: params.org_id.clone(),
+                    version: 1,
+                })
+                .await?;
+            let resource_key = resource_object(&params);
+            tracing::info!("Stored resource {}", resource_key);
+            saga.completed("store resource", {
+                let (ctx, key) = (ctx.clone(), params.key());
+                async move {
+                    ctx.resources
+                        .delete(&key)
+                        .await
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                }
+            });
+
+            // Make the creator the owner, otherwise nobody could manage it
+            let owner = TupleKey {
+                user: fga_user(&ctx, user_id),
+                relation: "owner".to_string(),
+                object: resource_key.clone(),
+                ..Default::default()
+            };
+            if let Err(e) = fga_write(&ctx, vec![owner.clone()], Vec::new()).await {
+                let error = ApiError::fga("Failed to assign resource owner", &e);
+                return Err(saga.fail("write owner tuple", error).await);
+            }
+            tracing::info!("User {} is now owner of {}", user_id, resource_key);
+            saga.completed("write owner tuple", {
+                let ctx = ctx.clone();
+                let owner = TupleKeyWithoutCondition {
+                    user: owner.user,
+                    relation: owner.relation,
+                    object: owner.object,
+                };
+                async move {
+                    fga_write(&ctx, Vec::new(), vec![owner])
+                        .await
+                        .map(|_| ())
+                        .map_err(|e| e.message().to_string())
+                }
+            });
+            ctx.shared_resources_cache.invalidate(user_id);
+
+            Ok((
+                StatusCode::CREATED,
+                Json(json!({
+                    "message": "Resource created successfully",
+                    "resource_id": resource_key,
+                    "resource": resource
+                })),
+            ))
+        }
+        Err(e) => {
+            tracing::error!("Error checking permission: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Create many resources in one organisation, owned by the caller
+///
+/// Admin on the organisation is checked once for the whole batch. Rows are
+/// inserted in one transaction and owner tuples written in as few OpenFGA
+/// calls as possible; rows whose owner tuple cannot be written are removed
+/// again and reported as failed.
+pub async fn create_resources_batch(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    ApiJson(payload): ApiJson<BatchCreateRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let user_id = &auth_user.user_id;
+
+    if payload.org_id.trim().is_empty() || payload.org_id.contains('/') {
+        return Err(ApiError::validation(
+            "org_id must be non-empty and contain no '/'",
+        ));
+    }
+    if payload.resources.is_empty() || payload.resources.len() > MAX_BATCH_RESOURCES {
+        return Err(ApiError::validation(format!(
+            "Between 1 and {} resources must be given",
+            MAX_BATCH_RESOURCES
+        )));
+    }
+
+    let org_key = org_object(&payload.org_id);
+    if !check_permission(&ctx, user_id, "admin", &org_key).await? {
+        tracing::warn!(
+            "User {} does not have admin permission for {}, rejecting batch",
+            user_id,
+            org_key
+        );
+        return Err(ApiError::forbidden(
+            "You do not have permission to create resources in this organisation",
+        ));
+    }
+
+    // Validate every item, later duplicates of a key in the batch are invalid
+    let mut results = Vec::with_capacity(payload.resources.len());
+    let mut pending = Vec::new();
+    let mut seen = HashSet::new();
+    for (index, item) in payload.resources.into_iter().enumerate() {
+        let resource = Resource {
+            id: Uuid::new_v4(),
+            properties: item.properties.unwrap_or_else(|| json!({})),
+            name: item.name,
+            service_name: item.service_name,
+            service_type: item.service_type,
+            org_id: payload.org_id.clone(),
+            version: 1,
+        };
+        let key = resource.key();
+        let invalid = if [&key.service_name, &key.service_type, &key.name]
+            .iter()
+            .any(|part| part.trim().is_empty() || part.contains('/'))
+        {
+            Some(
+                "service_name, service_type and name must be non-empty and contain no '/'"
+                    .to_string(),
+            )
+        } else if !seen.insert(key.clone()) {
+            Some("Resource appears more than once in the batch".to_string())
+        } else if let Err(e) = validate_properties(&ctx, &key.service_type, &resource.properties) {
+            Some(e.message)
+        } else {
+            None
+        };
+
+        results.push(BatchCreateResult {
+            resource_id: resource_key_object(&key),
+            status: BatchCreateStatus::Invalid,
+            resource: None,
+            error: invalid.clone(),
+        });
+        if invalid.is_none() {
+            pending.push((index, resource));
+        }
+    }
+
+    // Insert all valid rows atomically, existing keys are reported as conflicts
+    let (indexes, resources): (Vec<usize>, Vec<Resource>) = pending.into_iter().unzip();
+    let stored = ctx.resources.create_many(resources).await?;
+    let mut created = Vec::new();
+    for (index, stored) in indexes.into_iter().zip(stored) {
+        let result = &mut results[index];
+        match stored {
+            Some(resource) => {
+                result.status = BatchCreateStatus::Created;
+                result.resource = Some(resource);
+                created.push(index);
+            }
+            None => {
+                result.status = BatchCreateStatus::Conflict;
+                result.error = Some("Resource already exists".to_string());
+            }
+        }
+    }
+
+    // Make the caller owner of every created resource
+    for chunk in created.chunks(MAX_TUPLES_PER_WRITE) {
+        let owners = chunk
+            .iter()
+            .map(|&index| TupleKey {
+                user: fga_user(&ctx, user_id),
+                relation: "owner".to_string(),
+                object: results[index].resource_id.clone(),
+                ..Default::default()
+            })
+            .collect();
+        let Err(e) = fga_write(&ctx, owners, Vec::new()).await else {
+            continue;
+        };
+
+        tracing::error!(
+            "Failed to write {} owner tuples, removing stored resources: {}",
+            chunk.len(),
+            e
+        );
+        for &index in chunk {
+            let result = &mut results[index];
+            if let Some(resource) = result.resource.take()
+                && let Err(delete_error) = ctx.resources.delete(&resource.key()).await
+            {
+                tracing::error!(
+                    "Failed to remove orphaned resource {}: {}",
+                    result.resource_id,
+                    delete_error
+                );
+            }
+            result.status = BatchCreateStatus::Failed;
+            result.error = Some(format!("Failed to assign resource owner: {}", e.message()));
+        }
+    }
+
+    let created_count = results
+        .iter()
+        .filter(|result| matches!(result.status, BatchCreateStatus::Created))
+        .count();
+    if created_count > 0 {
+        ctx.shared_resources_cache.invalidate(user_id);
+    }
+    tracing::info!(
+        "User {} created {} of {} resources in organisation {}",
+        user_id,
+        created_count,
+        results.len(),
+        payload.org_id
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "org_id": payload.org_id,
+            "created": created_count,
+            "results": results
+        })),
+    ))
+}
+
+// Update an existing resource, only if it is still at the version named in `If-Match`
+pub async fn update_resource(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(params): Path<ResourceParams>,
+    headers: HeaderMap,
+    ApiJson(payload): ApiJson<Value>,
+) -> Result<(StatusCode, [(header::HeaderName, String); 1], Json<Value>), ApiError> {
+    tracing::info!(
+        "Updating resource: {}/{}/{}/{}",
+        params.service_name,
+        params.service_type,
+        params.org_id,
+        params.name
+    );
+
+    let resource_key = resource_object(&params);
+
+    // Get user ID from authentication middleware
+    let user_id = &auth_user.user_id;
+
+    // Optionally require membership of the resource's organisation first
+    enforce_org_membership(&ctx, user_id, &params.org_id).await?;
+
+    // To update a resource, user needs to be an editor of the resource
+    match check_permission(&ctx, user_id, "editor", &resource_key).await {
+        Ok(allowed) => {
+            if !allowed {
+                tracing::warn!(
+                    "User {} does not have editor permission for resource {}",
+                    user_id,
+                    resource_key
+                );
+                return Err(ApiError::forbidden(
+                    "You do not have permission to update this resource",
+                ));
+            }
+
+            tracing::info!(
+                "User {} has editor permission for resource {}",
+                user_id,
+                resource_key
+            );
+
+            let properties = resource_properties(&payload);
+            validate_properties(&ctx, &params.service_type, &properties)?;
+
+            let expected_version = if_match_version(&headers)?;
+            let resource = match ctx
+                .resources
+                .update(&params.key(), properties, expected_version)
+                .await
+            {
+                Ok(Some(resource)) => resource,
+                Ok(None) => {
+                    return Err(ApiError::not_found(format!(
+                        "Resource {} not found",
+                        resource_key
+                    )));
+                }
+                Err(e) => {
+                    if let StoreError::VersionMismatch = e {
+                        tracing::warn!(
+                            "User {} tried to update {} from a stale version",
+                            user_id,
+                            resource_key
+                        );
+                    }
+                    return Err(e.into());
+                }
+            };
+
+            Ok((
+                StatusCode::OK,
+                [(header::ETAG, resource_etag(resource.version))],
+                Json(json!({
+                    "message": "Resource updated successfully",
+                    "resource_id": resource_key,
+                    "resource": resource
+                })),
+            ))
+        }
+        Err(e) => {
+            tracing::error!("Error checking permission: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+// Get a resource, with its version as the ETag
+pub async fn get_resource(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(params): Path<ResourceParams>,
+) -> Result<(StatusCode, [(header::HeaderName, String); 1], Json<Value>), ApiError> {
+    tracing::info!(
+        "Getting resource: {}/{}/{}/{}",
+        params.service_name,
+        params.service_type,
+        params.org_id,
+        params.name
+    );
+
+    let resource_key = resource_object(&params);
+
+    // Get user ID from authentication middleware
+    let user_id = &auth_user.user_id;
+
+    // Optionally require membership of the resource's organisation first
+    enforce_org_membership(&ctx, user_id, &params.org_id).await?;
+
+    // Check if user has viewer permission on the resource
+    match check_permission(&ctx, user_id, "viewer", &resource_key).await {
+        Ok(allowed) => {
+            if !allowed {
+                tracing::warn!(
+                    "User {} does not have viewer permission for resource {}",
+                    user_id,
+                    resource_key
+                );
+                // Not public: ask anonymous callers to authenticate instead
+                if auth_user.is_anonymous() {
+                    return Err(ApiError::new(
+                        ErrorCode::Unauthenticated,
+                        "Authentication is required to view this resource",
+                    ));
+                }
+                return Err(ApiError::forbidden(
+                    "You do not have permission to view this resource",
+                ));
+            }
+
+            tracing::info!(
+                "User {} has viewer permission for resource {}",
+                user_id,
+                resource_key
+            );
+
+            let Some(resource) = ctx.resources.get(&params.key()).await? else {
+                return Err(ApiError::not_found(format!(
+                    "Resource {} not found",
+                    resource_key
+                )));
+            };
+
+            Ok((
+                StatusCode::OK,
+                [(header::ETAG, resource_etag(resource.version))],
+                Json(resource_body(&resource)),
+            ))
+        }
+        Err(e) => {
+            tracing::error!("Error checking permission: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Response body of a single resource
+fn resource_body(resource: &Resource) -> Value {
+    json!({
+        "id": resource.id,
+        "resource_id": resource_key_object(&resource.key()),
+        "name": resource.name,
+        "service_name": resource.service_name,
+        "service_type": resource.service_type,
+        "org_id": resource.org_id,
+        "properties": resource.properties,
+        "version": resource.version
+    })
+}
+
+/// Parse the stable ID of a resource from the path
+fn parse_resource_id(id: &str) -> Result<Uuid, ApiError> {
+    Uuid::parse_str(id)
+        .map_err(|_| ApiError::validation(format!("Invalid resource ID '{}', expected a UUID", id)))
+}
+
+/// Get a resource by its stable ID
+///
+/// The permission check still runs against the composite-key object, which is
+/// what tuples are written for.
+pub async fn get_resource_by_id(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, [(header::HeaderName, String); 1], Json<Value>), ApiError> {
+    let id = parse_resource_id(&id)?;
+    let user_id = &auth_user.user_id;
+
+    let Some(resource) = ctx.resources.get_by_id(id).await? else {
+        return Err(ApiError::not_found(format!("Resource {} not found", id)));
+    };
+    let resource_key = resource_key_object(&resource.key());
+
+    enforce_org_membership(&ctx, user_id, &resource.org_id).await?;
+
+    if !check_permission(&ctx, user_id, "viewer", &resource_key).await? {
+        tracing::warn!(
+            "User {} does not have viewer permission for resource {}",
+            user_id,
+            id
+        );
+        return Err(ApiError::forbidden(
+            "You do not have permission to view this resource",
+        ));
+    }
+
+    Ok((
+        StatusCode::OK,
+        [(header::ETAG, resource_etag(resource.version))],
+        Json(resource_body(&resource)),
+    ))
+}
+
+/// Whether the caller may perform an action on a resource given by its stable
+/// ID, with the action mapped to a relation through `FGA_ACTIONS`
+///
+/// The answer never includes the resource's composite key.
+pub async fn can_by_resource_id(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, action)): Path<(String, String)>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let id = parse_resource_id(&id)?;
+    let user_id = &auth_user.user_id;
+
+    let relation = ctx
+        .fga_config
+        .actions
+        .get(&action)
+        .filter(|relation| {
+            ctx.fga_config
+                .relations_by_type
+                .get("resource")
+                .is_some_and(|relations| relations.contains(relation))
+        })
+        .ok_or_else(|| {
+            ApiError::validation(format!("Action '{}' is not defined for resources", action))
+        })?;
+
+    let Some(resource) = ctx.resources.get_by_id(id).await? else {
+        return Err(ApiError::not_found(format!("Resource {} not found", id)));
+    };
+    let resource_key = resource_key_object(&resource.key());
+
+    // Outside the organisation nothing is allowed, whatever the relation says
+    let member = !ctx.enforce_org_membership
+        || check_permission(&ctx, user_id, "member", &org_object(&resource.org_id)).await?;
+    let allowed = member && check_permission(&ctx, user_id, relation, &resource_key).await?;
+
+    tracing::info!(
+        "User {} may {} resource {}: {}",
+        user_id,
+        action,
+        id,
+        allowed
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "id": id,
+            "action": action,
+            "allowed": allowed
+        })),
+    ))
+}
+
+/// List the users holding a relation on a resource, for access reviews
+///
+/// Only admins or owners of the resource may list its users.
+pub async fn list_resource_users(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(params): Path<ResourceParams>,
+    Query(query): Query<ListUsersQueryParams>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let relation = query.relation.unwrap_or_else(|| "viewer".to_string());
+    let valid = ctx
+        .fga_config
+        .relations_by_type
+        .get("resource")
+        .is_some_and(|relations| relations.contains(&relation));
+    if !valid {
+        return Err(ApiError::validation(format!(
+            "Relation '{}' is not valid for type 'resource'",
+            relation
+        )));
+    }
+
+    let resource_key = resource_object(&params);
+    let user_id = &auth_user.user_id;
+
+    let allowed = check_permission(&ctx, user_id, "admin", &resource_key).await?
+        || check_permission(&ctx, user_id, "owner", &resource_key).await?;
+    if !allowed {
+        tracing::warn!(
+            "User {} may not list users of resource {}",
+            user_id,
+            resource_key
+        );
+        return Err(ApiError::forbidden(
+            "You do not have permission to list users of this resource",
+        ));
+    }
+
+    fga_ids(&ctx)?;
+    let (user_ids, public) = fga_list_users(&ctx, &resource_key, &relation)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error listing users: {}", e);
+            ApiError::fga("Failed to list users", &e)
+        })?;
+
+    let response = ListUsersResponse {
+        list: ListEnvelope::new(user_ids),
+        object: resource_key,
+        relation,
+        public,
+    };
+    Ok((StatusCode::OK, Json(json!(response))))
+}
+
+/// IDs of the users holding a relation on an object, sorted, and whether the
+/// relation is granted to every user (`user:*`)
+///
+/// Callers check the store and model are configured with `fga_ids` first.
+async fn fga_list_users(
+    ctx: &Ctx,
+    object: &str,
+    relation: &str,
+) -> Result<(Vec<String>, bool), tonic::Status> {
+    let Some((object_type, object_id)) = object.split_once(':') else {
+        return Err(tonic::Status::invalid_argument(format!(
+            "Object '{}' has no type",
+            object
+        )));
+    };
+    let list_request = ListUsersRequest {
+        store_id: ctx.fga_config.store_id.clone(),
+        authorization_model_id: ctx
+            .fga_config
+            .authorization_model_id
+            .clone()
+            .unwrap_or_default(),
+        object: Some(Object {
+            r#type: object_type.to_string(),
+            id: object_id.to_string(),
+        }),
+        relation: relation.to_string(),
+        user_filters: vec![UserTypeFilter {
+            r#type: "user".to_string(),
+            relation: String::new(),
+        }],
+        consistency: ctx.fga_config.consistency as i32,
+        ..Default::default()
+    };
+    let users = fga::with_retry(&ctx.fga_config.retry, "list_users", || {
+        let client = ctx.fga_client();
+        let request = fga::request(list_request.clone(), ctx.fga_config.timeouts.list);
+        async move { client.list_users(request).await }
+    })
+    .await?
+    .into_inner()
+    .users;
+
+    let mut public = false;
+    let mut user_ids = Vec::new();
+    for listed in users {
+        match listed.user {
+            Some(user::User::Object(object)) => user_ids.push(object.id),
+            Some(user::User::Wildcard(_)) => public = true,
+            _ => {}
+        }
+    }
+    user_ids.sort();
+    Ok((user_ids, public))
+}
+
+/// List objects of a type that a user has a relation on
+async fn list_objects_for(
+    ctx: &Arc<Ctx>,
+    user_id: &str,
+    relation: String,
+    object_type: String,
+) -> Result<ListResponse, ApiError> {
+    tracing::info!(
+        user_id,
+        relation = %relation,
+        object_type = %object_type,
+        "Listing {} objects for user {} with relation {}",
+        object_type,
+        user_id,
+        relation
+    );
+
+    match fga_list_objects(ctx, user_id, &relation, &object_type).await {
+        Ok(objects) => {
+            tracing::info!(
+                user_id,
+                relation = %relation,
+                object_type = %object_type,
+                count = objects.len(),
+                "Found {} {} objects for user {}",
+                objects.len(),
+                object_type,
+                user_id
+            );
+
+            Ok(ListResponse {
+                list: ListEnvelope::new(objects),
+                object_type,
+                relation,
+            })
+        }
+        Err(e) => {
+            tracing::error!(
+                user_id,
+                relation = %relation,
+                object_type = %object_type,
+                error = %e,
+                "Error listing objects: {}",
+                e
+            );
+            Err(ApiError::fga("Failed to list objects", &e))
+        }
+    }
+}
+
+/// List objects that a user has access to using OpenFGA ListObjects API
+///
+/// `relation` may be a comma-separated list, the objects matching any of the
+/// relations are then returned together with which relations each matched.
+pub async fn list_objects(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<ListQueryParams>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let relation = params.relation.unwrap_or_else(|| "viewer".to_string());
+    let object_type = params.object_type.unwrap_or_else(|| "resource".to_string());
+    if relation.contains(',') {
+        let relations: BTreeSet<String> = relation
+            .split(',')
+            .map(str::trim)
+            .filter(|relation| !relation.is_empty())
+            .map(String::from)
+            .collect();
+        if relations.is_empty() || relations.len() > MAX_LIST_RELATIONS {
+            return Err(ApiError::validation(format!(
+                "Between 1 and {} relations must be given",
+                MAX_LIST_RELATIONS
+            )));
+        }
+        let response = list_objects_multi(
+            &ctx,
+            &auth_user.user_id,
+            relations.into_iter().collect(),
+            object_type,
+            params.page_size,
+            params.cursor.as_deref(),
+        )
+        .await?;
+        return Ok((StatusCode::OK, Json(json!(response))));
+    }
+
+    let query = format!("{}|{}|{}", auth_user.user_id, object_type, relation);
+
+    // ListObjects has no upstream paging, so pages are cut from the full
+    // result; without parameters the first page of the default size is returned
+    let mut response = list_objects_for(&ctx, &auth_user.user_id, relation, object_type).await?;
+    response.list = response
+        .list
+        .paginate(
+            "list-objects",
+            &query,
+            Some(params.page_size.unwrap_or(DEFAULT_PAGE_SIZE)),
+            params.cursor.as_deref(),
+        )?
+        .bounded(&mut response_budget(&ctx));
+    Ok((StatusCode::OK, Json(json!(response))))
+}
+
+/// List objects for several relations concurrently and union the results
+async fn list_objects_multi(
+    ctx: &Arc<Ctx>,
+    user_id: &str,
+    relations: Vec<String>,
+    object_type: String,
+    page_size: Option<usize>,
+    cursor: Option<&str>,
+) -> Result<MultiRelationListResponse, ApiError> {
+    let relation_list = relations.join(",");
+    tracing::info!(
+        user_id,
+        relation = %relation_list,
+        object_type = %object_type,
+        "Listing {} objects for user {} with relations {}",
+        object_type,
+        user_id,
+        relation_list
+    );
+
+    let semaphore = Arc::new(Semaphore::new(FGA_FANOUT_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for relation in &relations {
+        let ctx = ctx.clone();
+        let semaphore = semaphore.clone();
+        let user_id = user_id.to_string();
+        let relation = relation.clone();
+        let object_type = object_type.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let objects = fga_list_objects(&ctx, &user_id, &relation, &object_type).await;
+            (relation, objects)
+        });
+    }
+
+    // Relations are listed in sorted order, so each object's matches are too
+    let mut matched: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok((relation, Ok(objects))) => {
+                for object in objects {
+                    matched.entry(object).or_default().insert(relation.clone());
+                }
+            }
+            Ok((relation, Err(e))) => {
+                tracing::error!(
+                    user_id,
+                    relation = %relation,
+                    object_type = %object_type,
+                    error = %e,
+                    "Error listing objects with relation {}: {}",
+                    relation,
+                    e
+                );
+                return Err(ApiError::fga("Failed to list objects", &e));
+            }
+            Err(e) => {
+                tracing::error!("List objects task failed: {}", e);
+                return Err(ApiError::new(ErrorCode::Internal, "Failed to list objects"));
+            }
+        }
+    }
+    tracing::info!(
+        user_id,
+        relation = %relation_list,
+        object_type = %object_type,
+        count = matched.len(),
+        "Found {} {} objects for user {}",
+        matched.len(),
+        object_type,
+        user_id
+    );
+
+    let query = format!("{}|{}|{}", user_id, object_type, relation_list);
+    let list = ListEnvelope::new(matched.keys().cloned().collect())
+        .paginate(
+            "list-objects",
+            &query,
+            Some(page_size.unwrap_or(DEFAULT_PAGE_SIZE)),
+            cursor,
+        )?
+        .bounded(&mut response_budget(ctx));
+    let matched = list
+        .items
+        .iter()
+        .filter_map(|object| {
+            let relations = matched.remove(object)?;
+            Some((object.clone(), relations.into_iter().collect()))
+        })
+        .collect();
+
+    Ok(MultiRelationListResponse {
+        list,
+        object_type,
+        relations,
+        matched,
+    })
+}
+
+/// List an organisation's stored resources that the user can view
+///
+/// Rows come from the resource store and are kept only if ListObjects returns
+/// them for the viewer relation, so metadata is never shown for resources the
+/// user cannot see.
+pub async fn list_org_resources(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<OrgResourcesQueryParams>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let user_id = &auth_user.user_id;
+    enforce_org_membership(&ctx, user_id, &params.org_id).await?;
+
+    let (stored, viewable) = tokio::join!(
+        ctx.resources.list_by_org(&params.org_id),
+        fga_list_objects(&ctx, user_id, "viewer", "resource"),
+    );
+    let viewable: BTreeSet<String> = viewable
+        .map_err(|e| ApiError::fga("Failed to list objects", &e))?
+        .into_iter()
+        .collect();
+    let resources: Vec<Resource> = stored?
+        .into_iter()
+        .filter(|resource| viewable.contains(&resource_key_object(&resource.key())))
+        .collect();
+
+    tracing::info!(
+        "User {} can view {} stored resources in organisation {}",
+        user_id,
+        resources.len(),
+        params.org_id
+    );
+
+    let query = format!("{}|{}", user_id, params.org_id);
+    let list = ListEnvelope::new(resources)
+        .paginate(
+            "resources",
+            &query,
+            params.page_size,
+            params.cursor.as_deref(),
+        )?
+        .bounded(&mut response_budget(&ctx));
+    Ok((StatusCode::OK, Json(json!(list))))
+}
+
+/// Preview what ListObjects returns for another user (admin only)
+pub async fn admin_list_user_objects(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(target_user_id): Path<String>,
+    Query(params): Query<AdminListQueryParams>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&ctx, &auth_user.user_id).await?;
+
+    let relation = params.relation.unwrap_or_else(|| "viewer".to_string());
+    let object_type = params.object_type.unwrap_or_else(|| "resource".to_string());
+
+    tracing::info!(
+        target: "audit",
+        admin = %auth_user.user_id,
+        user_id = %target_user_id,
+        relation = %relation,
+        object_type = %object_type,
+        "Admin listed objects on behalf of another user"
+    );
+
+    let query = format!("{}|{}|{}", target_user_id, object_type, relation);
+    let mut response = list_objects_for(&ctx, &target_user_id, relation, object_type).await?;
+    response.list = response
+        .list
+        .paginate(
+            "admin-list-objects",
+            &query,
+            params.page_size,
+            params.cursor.as_deref(),
+        )?
+        .bounded(&mut response_budget(&ctx));
+    Ok((StatusCode::OK, Json(json!(response))))
+}
+
+/// Check whether a tuple grants the relation on the object to the user directly
+async fn has_direct_tuple(
+    ctx: &Ctx,
+    user_id: &str,
+    relation: &str,
+    object: &str,
+) -> Result<bool, tonic::Status> {
+    let request = fga::request(
+        ReadRequest {
+            store_id: ctx.fga_config.store_id.clone(),
+            tuple_key: Some(ReadRequestTupleKey {
+                user: fga_user(ctx, user_id),
+                relation: relation.to_string(),
+                object: object.to_string(),
+            }),
+            page_size: Some(1),
+            consistency: ctx.fga_config.consistency as i32,
+            ..Default::default()
+        },
+        ctx.fga_config.timeouts.list,
+    );
+
+    let response = ctx.fga_client().read(request).await?;
+    Ok(!response.into_inner().tuples.is_empty())
+}
+
+/// List objects the user has access to, annotated with whether the access is
+/// direct or inherited
+pub async fn list_objects_with_source(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<ListQueryParams>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let relation = params.relation.unwrap_or_else(|| "viewer".to_string());
+    let object_type = params.object_type.unwrap_or_else(|| "resource".to_string());
+    let query = format!("{}|{}|{}", auth_user.user_id, object_type, relation);
+
+    // Page before classifying so each page gets its own classification budget
+    let mut listed = list_objects_for(&ctx, &auth_user.user_id, relation, object_type).await?;
+    listed.list = listed.list.paginate(
+        "resources-with-source",
+        &query,
+        params.page_size,
+        params.cursor.as_deref(),
+    )?;
+
+    // Classify the first objects with bounded concurrency
+    let semaphore = Arc::new(Semaphore::new(FGA_FANOUT_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for (index, object) in listed
+        .list
+        .items
+        .iter()
+        .take(MAX_CLASSIFIED_OBJECTS)
+        .enumerate()
+    {
+        let ctx = ctx.clone();
+        let semaphore = semaphore.clone();
+        let user_id = auth_user.user_id.clone();
+        let relation = listed.relation.clone();
+        let object = object.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let source = match has_direct_tuple(&ctx, &user_id, &relation, &object).await {
+                Ok(true) => Some(AccessSource::Direct),
+                Ok(false) => Some(AccessSource::Inherited),
+                Err(e) => {
+                    tracing::warn!("Error classifying access to {}: {}", object, e);
+                    None
+                }
+            };
+            (index, source)
+        });
+    }
+
+    let mut sources = vec![None; listed.list.items.len()];
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok((index, source)) => sources[index] = source,
+            Err(e) => tracing::warn!("Classification task failed: {}", e),
+        }
+    }
+
+    let classified_count = listed.list.items.len().min(MAX_CLASSIFIED_OBJECTS);
+    let response = ListWithSourceResponse {
+        classified_count,
+        classification_truncated: listed.list.items.len() > classified_count,
+        list: ListEnvelope {
+            items: listed
+                .list
+                .items
+                .into_iter()
+                .zip(sources)
+                .map(|(object, source)| ObjectWithSource { object, source })
+                .collect(),
+            page: listed.list.page,
+        }
+        .bounded(&mut response_budget(&ctx)),
+        object_type: listed.object_type,
+        relation: listed.relation,
+    };
+
+    Ok((StatusCode::OK, Json(json!(response))))
+}
+
+/// Count the objects of a type the user holds each configured relation on
+pub async fn get_access_summary(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<TypeQueryParams>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let object_type = params.object_type.unwrap_or_else(|| "resource".to_string());
+    let Some(relations) = ctx.fga_config.relations_by_type.get(&object_type) else {
+        return Err(ApiError::not_found(format!(
+            "Type '{}' is not supported",
+            object_type
+        )));
+    };
+
+    tracing::info!(
+        "Summarising {} access for user {} across {} relations",
+        object_type,
+        auth_user.user_id,
+        relations.len()
+    );
+
+    let semaphore = Arc::new(Semaphore::new(FGA_FANOUT_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for relation in relations {
+        let ctx = ctx.clone();
+        let semaphore = semaphore.clone();
+        let user_id = auth_user.user_id.clone();
+        let relation = relation.clone();
+        let object_type = object_type.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let objects = fga_list_objects(&ctx, &user_id, &relation, &object_type).await;
+            (relation, objects)
+        });
+    }
+
+    let mut counts = BTreeMap::new();
+    let mut capped = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        let (relation, objects) = result.map_err(|e| {
+            ApiError::new(ErrorCode::Internal, format!("Summary task failed: {}", e))
+        })?;
+        let objects = objects.map_err(|e| {
+            tracing::error!("Error listing {} objects for summary: {}", relation, e);
+            ApiError::fga("Failed to list objects", &e)
+        })?;
+
+        if objects.len() >= MAX_SUMMARY_COUNT {
+            capped.push(relation.clone());
+        }
+        counts.insert(relation, objects.len().min(MAX_SUMMARY_COUNT));
+    }
+    capped.sort();
+
+    let response = AccessSummaryResponse {
+        object_type,
+        counts,
+        capped,
+    };
+
+    Ok((StatusCode::OK, Json(json!(response))))
+}
+
+/// Compare the objects two users can access with a relation (admin only)
+pub async fn compare_access(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    ApiJson(payload): ApiJson<CompareAccessRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&ctx, &auth_user.user_id).await?;
+
+    tracing::info!(
+        target: "audit",
+        admin = %auth_user.user_id,
+        user_a = %payload.user_a,
+        user_b = %payload.user_b,
+        relation = %payload.relation,
+        object_type = %payload.object_type,
+        "Admin compared access of two users"
+    );
+
+    let (objects_a, objects_b) = tokio::join!(
+        fga_list_objects(
+            &ctx,
+            &payload.user_a,
+            &payload.relation,
+            &payload.object_type
+        ),
+        fga_list_objects(
+            &ctx,
+            &payload.user_b,
+            &payload.relation,
+            &payload.object_type
+        ),
+    );
+    let objects_a: BTreeSet<String> = objects_a
+        .map_err(|e| ApiError::fga("Failed to list objects", &e))?
+        .into_iter()
+        .collect();
+    let objects_b: BTreeSet<String> = objects_b
+        .map_err(|e| ApiError::fga("Failed to list objects", &e))?
+        .into_iter()
+        .collect();
+
+    let mut truncated = false;
+    let mut capped = |objects: Vec<String>| -> Vec<String> {
+        truncated |= objects.len() > MAX_COMPARE_RESULTS;
+        objects.into_iter().take(MAX_COMPARE_RESULTS).collect()
+    };
+
+    let response = CompareAccessResponse {
+        only_a: capped(objects_a.difference(&objects_b).cloned().collect()),
+        only_b: capped(objects_b.difference(&objects_a).cloned().collect()),
+        shared: capped(objects_a.intersection(&objects_b).cloned().collect()),
+        truncated,
+    };
+
+    Ok((StatusCode::OK, Json(json!(response))))
+}
+
+/// Enumerate every object the user can access across all configured types
+/// and relations, grouped by type
+pub async fn get_all_access(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let user_id = &auth_user.user_id;
+    tracing::info!("Enumerating all access for user {}", user_id);
+
+    let semaphore = Arc::new(Semaphore::new(FGA_FANOUT_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for (object_type, relations) in &ctx.fga_config.relations_by_type {
+        for relation in relations {
+            let ctx = ctx.clone();
+            let semaphore = semaphore.clone();
+            let user_id = user_id.clone();
+            let object_type = object_type.clone();
+            let relation = relation.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let objects = fga_list_objects(&ctx, &user_id, &relation, &object_type).await;
+                (object_type, relation, objects)
+            });
+        }
+    }
+
+    // Collect results until all lookups finish or the time budget runs out
+    let mut grouped: BTreeMap<String, BTreeMap<String, BTreeSet<String>>> = BTreeMap::new();
+    let mut partial = false;
+    let collect = async {
+        while let Some(result) = tasks.join_next().await {
+            match result {
+                Ok((object_type, relation, Ok(objects))) => {
+                    let by_object = grouped.entry(object_type).or_default();
+                    for object in objects {
+                        by_object
+                            .entry(object)
+                            .or_default()
+                            .insert(relation.clone());
+                    }
+                }
+                Ok((object_type, relation, Err(e))) => {
+                    tracing::warn!(
+                        "Error listing {} objects with relation {}: {}",
+                        object_type,
+                        relation,
+                        e
+                    );
+                    partial = true;
+                }
+                Err(e) => {
+                    tracing::warn!("Access lookup task failed: {}", e);
+                    partial = true;
+                }
+            }
+        }
+    };
+    if tokio::time::timeout(ACCESS_ALL_TIMEOUT, collect)
+        .await
+        .is_err()
+    {
+        tracing::warn!("Enumerating access for user {} timed out", user_id);
+        partial = true;
+    }
+    tasks.abort_all();
+
+    // Flatten into the response, capping the total number of objects
+    let mut remaining = MAX_ACCESS_ALL_OBJECTS;
+    let mut truncated = false;
+    let mut types = BTreeMap::new();
+    for (object_type, by_object) in grouped {
+        truncated |= by_object.len() > remaining;
+        let objects: Vec<ObjectRelations> = by_object
+            .into_iter()
+            .take(remaining)
+            .map(|(object, relations)| ObjectRelations {
+                object,
+                relations: relations.into_iter().collect(),
+            })
+            .collect();
+        remaining -= objects.len();
+        types.insert(object_type, objects);
+    }
+
+    let response = AllAccessResponse {
+        types,
+        truncated,
+        partial,
+    };
+
+    Ok((StatusCode::OK, Json(json!(response))))
+}
+
+/// Get shared resources from parent organizations (comprehensive approach)
+pub async fn get_shared_resources(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let user_id = &auth_user.user_id;
+
+    let cached = ctx.shared_resources_cache.get(user_id);
+    if ctx.shared_resources_cache.is_enabled() {
+        ctx.stats.record_cache_lookup(cached.is_some());
+    }
+    if let Some(cached) = cached {
+        tracing::info!(
+            user_id = %user_id,
+            cached = true,
+            "Serving cached shared resources for user {}",
+            user_id
+        );
+        return Ok((StatusCode::OK, Json(cached)));
+    }
+
+    tracing::info!(
+        user_id = %user_id,
+        "Getting shared resources for user {}",
+        user_id
+    );
+
+    let mut shared_services = Vec::new();
+    let mut shared_service_types = Vec::new();
+    let mut shared_resources = Vec::new();
+    let mut shared_other: BTreeMap<String, BTreeMap<String, SharedObject>> = BTreeMap::new();
+
+    // List the configured object types and relations, concurrently, skipping
+    // relations a type does not define
+    let fga_config = &ctx.fga_config;
+    let semaphore = Arc::new(Semaphore::new(FGA_FANOUT_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for object_type in &fga_config.shared_object_types {
+        let defined = &fga_config.relations_by_type[object_type];
+        for relation in &fga_config.shared_relations {
+            if !defined.contains(relation) {
+                continue;
+            }
+            let ctx = ctx.clone();
+            let semaphore = semaphore.clone();
+            let user_id = user_id.clone();
+            let object_type = object_type.clone();
+            let relation = relation.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let objects = fga_list_objects(&ctx, &user_id, &relation, &object_type).await;
+                (object_type, relation, objects)
+            });
+        }
+    }
+
+    // A partial result must not be cached, or the gap would outlive the outage
+    let mut failed = false;
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok((object_type, relation, Ok(objects))) => {
+                for object_id in objects {
+                    match object_type.as_str() {
+                        "service" => {
+                            if let Some(service_name) = object_id.clone().strip_prefix("service:") {
+                                shared_services.push(SharedService {
+                                    id: object_id,
+                                    name: service_name.to_string(),
+                                    shared_via: "parent_organization".to_string(),
+                                    permissions: vec![relation.to_string()],
+                                });
+                            }
+                        }
+                        "service_type" => {
+                            if let Some(service_type_path) =
+                                object_id.clone().strip_prefix("service_type:")
+                            {
+                                let parts: Vec<&str> = service_type_path.split('/').collect();
+                                if parts.len() == 2 {
+                                    shared_service_types.push(SharedServiceType {
+                                        id: object_id,
+                                        service_name: parts[0].to_string(),
+                                        service_type: parts[1].to_string(),
+                                        shared_via: "parent_organization".to_string(),
+                                        permissions: vec![relation.to_string()],
+                                    });
+                                }
+                            }
+                        }
+                        "resource" => {
+                            if let Some(key) = resource_key_from_object(&object_id) {
+                                shared_resources.push(SharedResource {
+                                    id: object_id,
+                                    service_name: key.service_name,
+                                    service_type: key.service_type,
+                                    org_id: key.org_id,
+                                    resource_name: key.name,
+                                    shared_via: "parent_organization".to_string(),
+                                    permissions: vec![relation.to_string()],
+                                });
+                            }
+                        }
+                        _ => {
+                            shared_other
+                                .entry(object_type.clone())
+                                .or_default()
+                                .entry(object_id.clone())
+                                .or_insert_with(|| SharedObject {
+                                    id: object_id,
+                                    shared_via: "parent_organization".to_string(),
+                                    permissions: Vec::new(),
+                                })
+                                .permissions
+                                .push(relation.clone());
+                        }
+                    }
+                }
+            }
+            Ok((object_type, relation, Err(e))) => {
+                tracing::warn!(
+                    user_id = %user_id,
+                    relation = %relation,
+                    object_type = %object_type,
+                    error = %e,
+                    "Error listing {} objects with relation {}: {}",
+                    object_type,
+                    relation,
+                    e
+                );
+                failed = true;
+            }
+            Err(e) => {
+                tracing::warn!("Shared resources lookup task failed: {}", e);
+                failed = true;
+            }
+        }
+    }
+
+    // Deduplicate and merge permissions, ordered by ID so a truncated response
+    // always keeps the same entries
+    let mut service_map: BTreeMap<String, SharedService> = BTreeMap::new();
+    let mut service_type_map: BTreeMap<String, SharedServiceType> = BTreeMap::new();
+    let mut resource_map: BTreeMap<String, SharedResource> = BTreeMap::new();
+
+    for service in shared_services {
+        service_map
+            .entry(service.id.clone())
+            .and_modify(|existing| {
+                existing.permissions.extend(service.permissions.clone());
+                existing.permissions.sort();
+                existing.permissions.dedup();
+            })
+            .or_insert(service);
+    }
+
+    for service_type in shared_service_types {
+        service_type_map
+            .entry(service_type.id.clone())
+            .and_modify(|existing| {
+                existing
+                    .permissions
+                    .extend(service_type.permissions.clone());
+                existing.permissions.sort();
+                existing.permissions.dedup();
+            })
+            .or_insert(service_type);
+    }
+
+    for resource in shared_resources {
+        resource_map
+            .entry(resource.id.clone())
+            .and_modify(|existing| {
+                existing.permissions.extend(resource.permissions.clone());
+                existing.permissions.sort();
+                existing.permissions.dedup();
+            })
+            .or_insert(resource);
+    }
+
+    let mut budget = response_budget(&ctx);
+    let response = SharedResourcesResponse {
+        services: ListEnvelope::new(service_map.into_values().collect()).bounded(&mut budget),
+        service_types: ListEnvelope::new(service_type_map.into_values().collect())
+            .bounded(&mut budget),
+        resources: ListEnvelope::new(resource_map.into_values().collect()).bounded(&mut budget),
+        other: shared_other
+            .into_iter()
+            .map(|(object_type, objects)| {
+                let objects = objects
+                    .into_values()
+                    .map(|mut object| {
+                        object.permissions.sort();
+                        object
+                    })
+                    .collect();
+                (object_type, ListEnvelope::new(objects).bounded(&mut budget))
+            })
+            .collect(),
+    };
+
+    let response = json!(response);
+    if !failed {
+        ctx.shared_resources_cache
+            .insert(user_id.clone(), response.clone());
+    }
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// List what the caller has shared out: for each resource they own, the other
+/// users holding each resource relation, paged over the owned resources
+///
+/// Resources nobody else can access are left out, so a page may hold fewer
+/// items than `page_size` while `next_token` is still set.
+pub async fn get_outgoing_shares(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<OutgoingSharesQueryParams>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let user_id = &auth_user.user_id;
+    tracing::info!(user_id = %user_id, "Getting outgoing shares for user {}", user_id);
+
+    let mut owned = fga_list_objects(&ctx, user_id, "owner", "resource")
+        .await
+        .map_err(|e| {
+            tracing::error!("Error listing owned resources: {}", e);
+            ApiError::fga("Failed to list owned resources", &e)
+        })?;
+    // ListObjects order is not stable, sort so cursors address the same items
+    owned.sort();
+    let owned = ListEnvelope::new(owned).paginate(
+        "outgoing-shares",
+        user_id,
+        Some(params.page_size.unwrap_or(DEFAULT_PAGE_SIZE)),
+        params.cursor.as_deref(),
+    )?;
+
+    fga_ids(&ctx)?;
+    let relations = ctx
+        .fga_config
+        .relations_by_type
+        .get("resource")
+        .cloned()
+        .unwrap_or_default();
+    let semaphore = Arc::new(Semaphore::new(FGA_FANOUT_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for object in &owned.items {
+        for relation in &relations {
+            let ctx = ctx.clone();
+            let semaphore = semaphore.clone();
+            let object = object.clone();
+            let relation = relation.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let users = fga_list_users(&ctx, &object, &relation).await;
+                (object, relation, users)
+            });
+        }
+    }
+
+    let mut shared: BTreeMap<String, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok((object, relation, Ok((user_ids, public)))) => {
+                let mut users: Vec<String> =
+                    user_ids.into_iter().filter(|id| id != user_id).collect();
+                if public {
+                    users.insert(0, ANONYMOUS_USER_ID.to_string());
+                }
+                if !users.is_empty() {
+                    shared.entry(object).or_default().insert(relation, users);
+                }
+            }
+            Ok((object, relation, Err(e))) => {
+                tracing::error!(
+                    user_id = %user_id,
+                    object = %object,
+                    relation = %relation,
+                    error = %e,
+                    "Error listing users of {} with relation {}: {}",
+                    object,
+                    relation,
+                    e
+                );
+                return Err(ApiError::fga("Failed to list users", &e));
+            }
+            Err(e) => {
+                tracing::error!("Outgoing shares lookup task failed: {}", e);
+                return Err(ApiError::new(
+                    ErrorCode::Internal,
+                    "Failed to list outgoing shares",
+                ));
+            }
+        }
+    }
+
+    let items: Vec<OutgoingShare> = shared
+        .into_iter()
+        .filter_map(|(id, shared_with)| {
+            let key = resource_key_from_object(&id)?;
+            Some(OutgoingShare {
+                id,
+                service_name: key.service_name,
+                service_type: key.service_type,
+                org_id: key.org_id,
+                resource_name: key.name,
+                shared_with,
+            })
+        })
+        .collect();
+    tracing::info!(
+        user_id = %user_id,
+        count = items.len(),
+        "Found {} shared out resources for user {}",
+        items.len(),
+        user_id
+    );
+
+    let list = ListEnvelope {
+        page: PageInfo {
+            size: items.len(),
+            ..owned.page
+        },
+        items,
+    }
+    .bounded(&mut response_budget(&ctx));
+    Ok((StatusCode::OK, Json(json!(list))))
+}
+
+/// Drop a user's cached shared resources so the next request recomputes them
+pub async fn invalidate_shared_cache(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(target_user_id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&ctx, &auth_user.user_id).await?;
+
+    let invalidated = ctx.shared_resources_cache.invalidate(&target_user_id);
+    tracing::info!(
+        target: "audit",
+        admin = %auth_user.user_id,
+        user_id = %target_user_id,
+        invalidated,
+        "Admin invalidated shared resources cache"
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "user_id": target_user_id,
+            "invalidated": invalidated
+        })),
+    ))
+}
+
+/// Outcome of a single check made while diagnosing access
+#[derive(Debug, Serialize)]
+pub struct DiagnoseCheck {
+    pub relation: String,
+    pub allowed: Option<bool>,
+    pub error: Option<String>,
+    pub latency_ms: u128,
+}
+
+/// Run the identity, configuration, check and database stages for a user
+/// and resource and report each stage's outcome (admin only)
+pub async fn diagnose(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<DiagnoseQueryParams>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&ctx, &auth_user.user_id).await?;
+
+    tracing::info!(
+        target: "audit",
+        admin = %auth_user.user_id,
+        user_id = %params.user,
+        object = %params.resource,
+        "Admin ran access diagnostics"
+    );
+
+    let user_id_valid = ctx.user_id_pattern.is_match(&params.user);
+    let store_configured = !ctx.fga_config.store_id.is_empty();
+    let model_configured = ctx.fga_config.authorization_model_id.is_some();
+
+    let object_type = params
+        .resource
+        .split_once(':')
+        .map(|(object_type, _)| object_type)
+        .unwrap_or_default();
+    let relations = ctx
+        .fga_config
+        .relations_by_type
+        .get(object_type)
+        .cloned()
+        .unwrap_or_default();
+
+    // Checks are skipped when they could only fail on configuration
+    let mut checks = Vec::with_capacity(relations.len());
+    if user_id_valid && store_configured && model_configured {
+        for relation in relations {
+            let started = Instant::now();
+            let result = check_permission(&ctx, &params.user, &relation, &params.resource).await;
+            checks.push(DiagnoseCheck {
+                relation,
+                allowed: result.as_ref().ok().copied(),
+                error: result.err().map(|e| e.to_string()),
+                latency_ms: started.elapsed().as_millis(),
+            });
+        }
+    }
+
+    // Look up the row when the object names a resource, otherwise only
+    // probe database reachability
+    let started = Instant::now();
+    let (row_exists, db_error) = match resource_key_from_object(&params.resource) {
+        Some(key) => match ctx.resources.get(&key).await {
+            Ok(row) => (Some(row.is_some()), None),
+            Err(e) => (None, Some(e.to_string())),
+        },
+        None => (
+            None,
+            sqlx::query("SELECT 1")
+                .execute(&ctx.db)
+                .await
+                .err()
+                .map(|e| e.to_string()),
+        ),
+    };
+    let db_latency_ms = started.elapsed().as_millis();
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "user": {
+                "id": params.user,
+                "valid": user_id_valid
+            },
+            "fga": {
+                "store_configured": store_configured,
+                "model_configured": model_configured,
+                "object_type_known": ctx.fga_config.relations_by_type.contains_key(object_type)
+            },
+            "checks": checks,
+            "db": {
+                "reachable": db_error.is_none(),
+                "error": db_error,
+                "row_exists": row_exists,
+                "latency_ms": db_latency_ms
+            }
+        })),
+    ))
+}
+
+/// Report recent decision latency percentiles, response counts and cache hit
+/// ratios (admin only)
+pub async fn get_stats(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&ctx, &auth_user.user_id).await?;
+    Ok((StatusCode::OK, Json(json!(ctx.stats.snapshot()))))
+}
+
+/// Discard the recorded statistics so the next report starts fresh (admin only)
+pub async fn reset_stats(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&ctx, &auth_user.user_id).await?;
+
+    ctx.stats.reset();
+    tracing::info!(target: "audit", admin = %auth_user.user_id, "Admin reset stats");
+
+    Ok((StatusCode::OK, Json(json!({ "reset": true }))))
+}
+
+/// Explain step by step how the model decides a user's access to a resource:
+/// the tuples stored on it, how each relation expands, and the check outcome.
+/// Only routed in the dev profile.
+pub async fn get_walkthrough(
+    State(ctx): State<Arc<Ctx>>,
+    Query(params): Query<WalkthroughQueryParams>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let Some((object_type, _)) = params.resource.split_once(':') else {
+        return Err(ApiError::validation(
+            "resource must be an object ID like type:id",
+        ));
+    };
+    let Some(relations) = ctx.fga_config.relations_by_type.get(object_type) else {
+        return Err(ApiError::not_found(format!(
+            "Type '{}' is not supported",
+            object_type
+        )));
+    };
+    let user = fga_user(&ctx, &params.user);
+
+    let mut steps = Vec::new();
+
+    let (tuples, truncated) = read_object_tuples(&ctx, &params.resource)
+        .await
+        .map_err(|e| ApiError::fga("Failed to read tuples", &e))?;
+    if tuples.is_empty() {
+        steps.push(format!("No tuples are stored on {}", params.resource));
+    } else {
+        steps.push(format!("Tuples stored on {}:", params.resource));
+        steps.extend(
+            tuples
+                .iter()
+                .map(|tuple| format!("  {} is {} of {}", tuple.user, tuple.relation, tuple.object)),
+        );
+        if truncated {
+            steps.push(format!("  (only the first {} are shown)", tuples.len()));
+        }
+    }
+
+    for relation in relations {
+        let request = fga::request(
+            ExpandRequest {
+                store_id: ctx.fga_config.store_id.clone(),
+                tuple_key: Some(ExpandRequestTupleKey {
+                    relation: relation.clone(),
+                    object: params.resource.clone(),
+                }),
+                authorization_model_id: ctx
+                    .fga_config
+                    .authorization_model_id
+                    .clone()
+                    .unwrap_or_default(),
+                consistency: ctx.fga_config.consistency as i32,
+                ..Default::default()
+            },
+            ctx.fga_config.timeouts.list,
+        );
+        let root = ctx
+            .fga_client()
+            .expand(request)
+            .await
+            .map_err(|e| ApiError::fga("Failed to expand relation", &e))?
+            .into_inner()
+            .tree
+            .and_then(|tree| tree.root);
+
+        steps.push(format!("Expanding {}#{}:", params.resource, relation));
+        if let Some(root) = root {
+            fga::describe_expansion(&root, 1, &mut steps);
+        }
+
+        let allowed = check_permission(&ctx, &params.user, relation, &params.resource).await?;
+        steps.push(format!(
+            "Therefore {} {} {} on {}",
+            user,
+            if allowed { "has" } else { "does not have" },
+            relation,
+            params.resource
+        ));
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "user": user,
+            "resource": params.resource,
+            "steps": steps
+        })),
+    ))
+}
+
+/// Expand a relation on an object into its userset tree, for debugging
+/// unexpected check results
+///
+/// The tree is returned verbatim as OpenFGA produced it. Only admins of the
+/// object may expand it.
+pub async fn expand_relation(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<ExpandQueryParams>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let object_type = match params.object.split_once(':') {
+        Some((object_type, id)) if !object_type.is_empty() && !id.is_empty() => object_type,
+        _ => {
+            return Err(ApiError::validation(
+                "object must be an object ID like type:id",
+            ));
+        }
+    };
+    let Some(relations) = ctx.fga_config.relations_by_type.get(object_type) else {
+        return Err(ApiError::not_found(format!(
+            "Type '{}' is not supported",
+            object_type
+        )));
+    };
+    if !relations.contains(&params.relation) {
+        return Err(ApiError::validation(format!(
+            "Relation '{}' is not valid for type '{}'",
+            params.relation, object_type
+        )));
+    }
+    if !relations.iter().any(|relation| relation == "admin") {
+        return Err(ApiError::forbidden(format!(
+            "Type '{}' has no admin relation, its relations cannot be expanded",
+            object_type
+        )));
+    }
+
+    let user_id = &auth_user.user_id;
+    if !check_permission(&ctx, user_id, "admin", &params.object).await? {
+        tracing::warn!("User {} may not expand {}", user_id, params.object);
+        return Err(ApiError::forbidden(
+            "You do not have permission to expand relations on this object",
+        ));
+    }
+
+    let (store_id, authorization_model_id) = fga_ids(&ctx)?;
+    let expand_request = ExpandRequest {
+        store_id: store_id.to_string(),
+        tuple_key: Some(ExpandRequestTupleKey {
+            relation: params.relation.clone(),
+            object: params.object.clone(),
+        }),
+        authorization_model_id: authorization_model_id.to_string(),
+        consistency: ctx.fga_config.consistency as i32,
+        ..Default::default()
+    };
+    let tree = fga::with_retry(&ctx.fga_config.retry, "expand", || {
+        let client = ctx.fga_client();
+        let request = fga::request(expand_request.clone(), ctx.fga_config.timeouts.list);
+        async move { client.expand(request).await }
+    })
+    .await
+    .map_err(|e| {
+        tracing::error!("Error expanding relation: {}", e);
+        ApiError::fga("Failed to expand relation", &e)
+    })?
+    .into_inner()
+    .tree;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "object": params.object,
+            "relation": params.relation,
+            "tree": tree
+        })),
+    ))
+}
+
+/// Validate a grant or revoke and ensure the caller owns the object
+async fn authorize_permission_change(
+    ctx: &Arc<Ctx>,
+    user_id: &str,
+    payload: &PermissionRequest,
+) -> Result<(), ApiError> {
+    if let Some(operation_id) = &payload.operation_id {
+        validate_operation_id(operation_id)?;
+    }
+    if !is_valid_fga_user(&payload.user) {
+        return Err(ApiError::validation(format!(
+            "Invalid user '{}', expected type:id, type:* or type:id#relation",
+            payload.user
+        )));
+    }
+    let object_type = match payload.object.split_once(':') {
+        Some((object_type, id)) if !object_type.is_empty() && !id.is_empty() => object_type,
+        _ => {
+            return Err(ApiError::validation(format!(
+                "Invalid object '{}', expected type:id",
+                payload.object
+            )));
+        }
+    };
+    let Some(relations) = ctx.fga_config.relations_by_type.get(object_type) else {
+        return Err(ApiError::not_found(format!(
+            "Type '{}' is not supported",
+            object_type
+        )));
+    };
+    if !relations.contains(&payload.relation) {
+        return Err(ApiError::validation(format!(
+            "Relation '{}' is not valid for type '{}'",
+            payload.relation, object_type
+        )));
+    }
+
+    if !check_permission(ctx, user_id, "owner", &payload.object).await? {
+        tracing::warn!(
+            "User {} is not an owner of {} and cannot change its access",
+            user_id,
+            payload.object
+        );
+        return Err(ApiError::forbidden(
+            "You do not have permission to change access to this object",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Reject operation IDs that are empty, too long, or contain anything but
+/// ASCII letters, digits, `.`, `_`, `:` and `-`
+fn validate_operation_id(operation_id: &str) -> Result<(), ApiError> {
+    let valid = !operation_id.is_empty()
+        && operation_id.len() <= MAX_OPERATION_ID_LEN
+        && operation_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | ':' | '-'));
+    if !valid {
+        return Err(ApiError::validation(format!(
+            "operation_id must be 1 to {} letters, digits, '.', '_', ':' or '-'",
+            MAX_OPERATION_ID_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// The change a grant (`write`) or revoke (`delete`) makes, for the change log
+fn tuple_change(operation: &str, payload: &PermissionRequest, changed_by: &str) -> TupleChange {
+    TupleChange {
+        operation_id: payload.operation_id.clone(),
+        operation: operation.to_string(),
+        user: payload.user.clone(),
+        relation: payload.relation.clone(),
+        object: payload.object.clone(),
+        changed_by: changed_by.to_string(),
+        changed_at: OffsetDateTime::now_utc(),
+    }
+}
+
+/// Whether the change was already made under its operation ID, so a retried
+/// request succeeds without touching OpenFGA again
+async fn already_recorded(ctx: &Ctx, change: &TupleChange) -> Result<bool, ApiError> {
+    let Some(operation_id) = &change.operation_id else {
+        return Ok(false);
+    };
+    let recorded = ctx.tuple_changes.by_operation(operation_id).await?;
+    Ok(recorded.iter().any(|previous| previous.same_change(change)))
+}
+
+/// Add an applied change to the change log. OpenFGA already has it, so a
+/// failure is logged instead of failing the request.
+async fn record_tuple_change(ctx: &Ctx, change: TupleChange) {
+    if let Err(e) = ctx.tuple_changes.record(change).await {
+        tracing::error!("Failed to record tuple change: {}", e);
+    }
+}
+
+/// Response body of grant and revoke
+fn permission_body(payload: &PermissionRequest, replayed: bool) -> Value {
+    let mut body = json!({
+        "user": payload.user,
+        "relation": payload.relation,
+        "object": payload.object
+    });
+    if let Some(operation_id) = &payload.operation_id {
+        body["operation_id"] = json!(operation_id);
+        body["replayed"] = json!(replayed);
+    }
+    body
+}
+
+/// Grant a relation on an object by writing a single tuple
+///
+/// Only owners of the object may grant access to it. With `?dry_run=true`
+/// the same checks run but the tuple is only described, not written. A grant
+/// repeated under the same `operation_id` answers as before without writing.
+pub async fn grant_permission(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<DryRunQueryParams>,
+    ApiJson(payload): ApiJson<PermissionRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    authorize_permission_change(&ctx, &auth_user.user_id, &payload).await?;
+
+    fga_ids(&ctx)?;
+    let tuple = TupleKey {
+        user: payload.user.clone(),
+        relation: payload.relation.clone(),
+        object: payload.object.clone(),
+        ..Default::default()
+    };
+    if query.dry_run {
+        tracing::info!(
+            "Dry-run grant of {} on {} to {} by {}",
+            payload.relation,
+            payload.object,
+            payload.user,
+            auth_user.user_id
+        );
+        return Ok(dry_run_response(&write_request(
+            &ctx,
+            vec![tuple],
+            Vec::new(),
+        )));
+    }
+
+    let change = tuple_change("write", &payload, &auth_user.user_id);
+    if already_recorded(&ctx, &change).await? {
+        return Ok((StatusCode::CREATED, Json(permission_body(&payload, true))));
+    }
+
+    fga_write(&ctx, vec![tuple], Vec::new())
+        .await
+        .map_err(|e| ApiError::fga("Failed to grant permission", &e))?;
+    record_tuple_change(&ctx, change).await;
+
+    invalidate_shared_resources(&ctx, &payload.user);
+
+    tracing::info!(
+        target: "audit",
+        granted_by = %auth_user.user_id,
+        user = %payload.user,
+        relation = %payload.relation,
+        object = %payload.object,
+        operation_id = payload.operation_id.as_deref().unwrap_or(""),
+        "Permission granted"
+    );
+
+    Ok((StatusCode::CREATED, Json(permission_body(&payload, false))))
+}
+
+/// Revoke a relation on an object by deleting a single tuple
+///
+/// Only owners of the object may revoke access to it. With `?dry_run=true`
+/// the same checks run but the tuple is only described, not deleted. A revoke
+/// repeated under the same `operation_id` answers as before without deleting.
+pub async fn revoke_permission(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<DryRunQueryParams>,
+    ApiJson(payload): ApiJson<PermissionRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    authorize_permission_change(&ctx, &auth_user.user_id, &payload).await?;
+
+    fga_ids(&ctx)?;
+    let tuple = TupleKeyWithoutCondition {
+        user: payload.user.clone(),
+        relation: payload.relation.clone(),
+        object: payload.object.clone(),
+    };
+    if query.dry_run {
+        tracing::info!(
+            "Dry-run revoke of {} on {} from {} by {}",
+            payload.relation,
+            payload.object,
+            payload.user,
+            auth_user.user_id
+        );
+        return Ok(dry_run_response(&write_request(
+            &ctx,
+            Vec::new(),
+            vec![tuple],
+        )));
+    }
+
+    let change = tuple_change("delete", &payload, &auth_user.user_id);
+    if already_recorded(&ctx, &change).await? {
+        return Ok((StatusCode::OK, Json(permission_body(&payload, true))));
+    }
+
+    if let Err(e) = fga_write(&ctx, Vec::new(), vec![tuple]).await {
+        // OpenFGA rejects deleting a tuple that was never written
+        if fga::is_tuple_missing(&e) {
+            return Err(ApiError::not_found(format!(
+                "{} does not have {} on {}",
+                payload.user, payload.relation, payload.object
+            )));
+        }
+        return Err(ApiError::fga("Failed to revoke permission", &e));
+    }
+    record_tuple_change(&ctx, change).await;
+
+    invalidate_shared_resources(&ctx, &payload.user);
+
+    tracing::info!(
+        target: "audit",
+        revoked_by = %auth_user.user_id,
+        user = %payload.user,
+        relation = %payload.relation,
+        object = %payload.object,
+        operation_id = payload.operation_id.as_deref().unwrap_or(""),
+        "Permission revoked"
+    );
+
+    Ok((StatusCode::OK, Json(permission_body(&payload, false))))
+}
+
+/// Check whether the caller holds a relation on an object, so clients can
+/// gate UI elements without attempting the operation
+pub async fn can(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    ApiJson(payload): ApiJson<CanRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let object_type = match payload.object.split_once(':') {
+        Some((object_type, id)) if !object_type.is_empty() && !id.is_empty() => object_type,
+        _ => {
+            return Err(ApiError::validation(
+                "object must be an object ID like type:id",
+            ));
+        }
+    };
+    let valid = ctx
+        .fga_config
+        .relations_by_type
+        .get(object_type)
+        .is_some_and(|relations| relations.contains(&payload.relation));
+    if !valid {
+        return Err(ApiError::validation(format!(
+            "Relation '{}' is not valid for type '{}'",
+            payload.relation, object_type
+        )));
+    }
+
+    let allowed =
+        check_permission(&ctx, &auth_user.user_id, &payload.relation, &payload.object).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "relation": payload.relation,
+            "object": payload.object,
+            "allowed": allowed
+        })),
+    ))
+}
+
+/// Check `(relation, object)` pairs for a user in a single BatchCheck call
+///
+/// Returns one outcome per pair, in order: whether it is allowed, or why
+/// OpenFGA could not evaluate it. Each item is sent with its index as the
+/// correlation ID so results map back even when objects repeat.
+async fn fga_batch_check(
+    ctx: &Ctx,
+    user_id: &str,
+    checks: &[(String, String)],
+) -> Result<Vec<Result<bool, String>>, ApiError> {
+    let (store_id, authorization_model_id) = fga_ids(ctx)?;
+    let user = fga_user(ctx, user_id);
+
+    let request = fga::request(
+        BatchCheckRequest {
+            store_id: store_id.to_string(),
+            authorization_model_id: authorization_model_id.to_string(),
+            consistency: ctx.fga_config.consistency as i32,
+            checks: checks
+                .iter()
+                .enumerate()
+                .map(|(index, (relation, object))| BatchCheckItem {
+                    tuple_key: Some(CheckRequestTupleKey {
+                        user: user.clone(),
+                        relation: relation.clone(),
+                        object: object.clone(),
+                    }),
+                    correlation_id: index.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+        },
+        ctx.fga_config.timeouts.check,
+    );
+    let mut outcomes = ctx
+        .fga_client()
+        .batch_check(request)
+        .await
+        .map_err(|e| ApiError::fga("Batch check failed", &e))?
+        .into_inner()
+        .result;
+
+    Ok((0..checks.len())
+        .map(|index| {
+            match outcomes
+                .remove(&index.to_string())
+                .and_then(|result| result.check_result)
+            {
+                Some(batch_check_single_result::CheckResult::Allowed(allowed)) => Ok(allowed),
+                Some(batch_check_single_result::CheckResult::Error(e)) => Err(e.message),
+                None => Err("No result returned".to_string()),
+            }
+        })
+        .collect())
+}
+
+/// Check many relations for the caller in a single OpenFGA round-trip
+///
+/// Results are returned in request order.
+pub async fn batch_check(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    ApiJson(items): ApiJson<Vec<BatchCheckItemRequest>>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    if items.len() > MAX_BATCH_CHECK_ITEMS {
+        return Err(ApiError::validation(format!(
+            "A batch may contain at most {} checks",
+            MAX_BATCH_CHECK_ITEMS
+        )));
+    }
+    if items.is_empty() {
+        return Ok((StatusCode::OK, Json(json!({ "results": [] }))));
+    }
+
+    let checks: Vec<(String, String)> = items
+        .into_iter()
+        .map(|item| (item.relation, item.object))
+        .collect();
+    let outcomes = fga_batch_check(&ctx, &auth_user.user_id, &checks).await?;
+
+    let results: Vec<BatchCheckResult> = checks
+        .into_iter()
+        .zip(outcomes)
+        .map(|((relation, object), outcome)| {
+            let (allowed, error) = match outcome {
+                Ok(allowed) => (allowed, None),
+                Err(e) => (false, Some(e)),
+            };
+            BatchCheckResult {
+                relation,
+                object,
+                allowed,
+                error,
+            }
+        })
+        .collect();
+
+    Ok((StatusCode::OK, Json(json!({ "results": results }))))
+}
+
+/// Resources split by the outcome of a batch permission check
+#[derive(Default)]
+struct BatchAuthorization {
+    allowed: Vec<(String, ResourceKey)>,
+    denied: Vec<String>,
+    /// Resources OpenFGA could not evaluate, with the reason
+    failed: Vec<(String, String)>,
+}
+
+/// Parse resource IDs and check `relation` on all of them in one BatchCheck,
+/// also requiring membership of each resource's organisation when enforced
+async fn authorize_resources(
+    ctx: &Arc<Ctx>,
+    user_id: &str,
+    relation: &str,
+    ids: Vec<String>,
+    max: usize,
+) -> Result<BatchAuthorization, ApiError> {
+    if ids.len() > max {
+        return Err(ApiError::validation(format!(
+            "A batch may contain at most {} resource IDs",
+            max
+        )));
+    }
+
+    let mut keys = BTreeMap::new();
+    for id in ids {
+        let Some(key) = resource_key_from_object(&id) else {
+            return Err(ApiError::validation(format!(
+                "Invalid resource ID '{}', expected resource:<service>/<type>/<org>/<name>",
+                id
+            )));
+        };
+        keys.insert(id, key);
+    }
+    if keys.is_empty() {
+        return Ok(BatchAuthorization::default());
+    }
+
+    let checks: Vec<(String, String)> = keys
+        .keys()
+        .map(|id| (relation.to_string(), id.clone()))
+        .collect();
+    let outcomes = fga_batch_check(ctx, user_id, &checks).await?;
+
+    let mut members = BTreeMap::new();
+    if ctx.enforce_org_membership {
+        let org_ids: BTreeSet<&String> = keys.values().map(|key| &key.org_id).collect();
+        for org_id in org_ids {
+            let member = check_permission(ctx, user_id, "member", &org_object(org_id)).await?;
+            members.insert(org_id.clone(), member);
+        }
+    }
+
+    let mut authorization = BatchAuthorization::default();
+    for ((id, key), outcome) in keys.into_iter().zip(outcomes) {
+        let member = members.get(&key.org_id).copied().unwrap_or(true);
+        match outcome {
+            Ok(true) if member => authorization.allowed.push((id, key)),
+            Ok(_) => authorization.denied.push(id),
+            Err(error) => {
+                tracing::warn!("Batch check of {} on {} failed: {}", relation, id, error);
+                authorization.failed.push((id, error));
+            }
         }
     }
+    Ok(authorization)
 }
 
-// Get a resource
-pub async fn get_resource(
+/// Fetch many resources by ID, checking viewer on all of them in one
+/// BatchCheck and reading the allowed ones in one query
+///
+/// Resources the caller may not view are reported as forbidden whether or not
+/// they exist, so the response does not reveal them.
+pub async fn batch_get_resources(
     State(ctx): State<Arc<Ctx>>,
     Extension(auth_user): Extension<AuthUser>,
-    Path(params): Path<ResourceParams>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    ApiJson(ids): ApiJson<Vec<String>>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let user_id = &auth_user.user_id;
+    let authorization =
+        authorize_resources(&ctx, user_id, "viewer", ids, MAX_BATCH_GET_ITEMS).await?;
+
+    let mut results = BTreeMap::new();
+    for id in authorization.denied {
+        results.insert(id, BatchGetResult::Forbidden);
+    }
+    for (id, error) in authorization.failed {
+        results.insert(id, BatchGetResult::Error { error });
+    }
+
+    let keys: Vec<ResourceKey> = authorization
+        .allowed
+        .iter()
+        .map(|(_, key)| key.clone())
+        .collect();
+    let mut found: HashMap<ResourceKey, Resource> = ctx
+        .resources
+        .list_by_ids(&keys)
+        .await?
+        .into_iter()
+        .map(|resource| (resource.key(), resource))
+        .collect();
+    for (id, key) in authorization.allowed {
+        let result = match found.remove(&key) {
+            Some(resource) => BatchGetResult::Found { resource },
+            None => BatchGetResult::NotFound,
+        };
+        results.insert(id, result);
+    }
+
     tracing::info!(
-        "Getting resource: {}/{}/{}/{}",
-        params.service_name,
-        params.service_type,
-        params.org_id,
-        params.name
+        "User {} fetched {} resources in a batch",
+        user_id,
+        results.len()
     );
 
-    let resource_key = format!(
-        "{}/{}/{}/{}",
-        params.service_name, params.service_type, params.org_id, params.name
-    );
+    Ok((StatusCode::OK, Json(json!({ "resources": results }))))
+}
 
-    // Get user ID from authentication middleware
+/// Delete many resources by ID: owner is checked on all of them in one
+/// BatchCheck, the tuples of the allowed resources are removed and then their
+/// rows are deleted in one transaction
+///
+/// As in a single delete, tuples go before the row. A resource whose tuples
+/// could not all be removed keeps its row and is reported as
+/// `cleanup_failed`, so it can be deleted again.
+pub async fn bulk_delete_resources(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    ApiJson(ids): ApiJson<Vec<String>>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
     let user_id = &auth_user.user_id;
+    let authorization =
+        authorize_resources(&ctx, user_id, "owner", ids, MAX_BULK_DELETE_ITEMS).await?;
 
-    // Check if user has viewer permission on the resource
-    match check_permission(&ctx, user_id, "viewer", &resource_key).await {
-        Ok(allowed) => {
-            if !allowed {
-                tracing::warn!(
-                    "User {} does not have viewer permission for resource {}",
-                    user_id,
-                    resource_key
+    let mut results = BTreeMap::new();
+    for id in authorization.denied {
+        results.insert(id, BulkDeleteResult::Forbidden);
+    }
+    for (id, error) in authorization.failed {
+        results.insert(id, BulkDeleteResult::Error { error });
+    }
+
+    let keys: Vec<ResourceKey> = authorization
+        .allowed
+        .iter()
+        .map(|(_, key)| key.clone())
+        .collect();
+    let existing: HashSet<ResourceKey> = ctx
+        .resources
+        .list_by_ids(&keys)
+        .await?
+        .into_iter()
+        .map(|resource| resource.key())
+        .collect();
+
+    let mut cleaned = Vec::new();
+    for (id, key) in authorization.allowed {
+        if !existing.contains(&key) {
+            results.insert(id, BulkDeleteResult::NotFound);
+            continue;
+        }
+        match delete_object_tuples(&ctx, &id).await {
+            Ok(tuples_removed) => cleaned.push((id, key, tuples_removed)),
+            Err(e) => {
+                tracing::error!("Error removing tuples of {}: {}", id, e);
+                results.insert(
+                    id,
+                    BulkDeleteResult::CleanupFailed {
+                        error: e.message().to_string(),
+                    },
                 );
-                return Err((
-                    StatusCode::FORBIDDEN,
-                    Json(json!({
-                        "error": "Permission denied",
-                        "message": "You do not have permission to view this resource"
-                    })),
-                ));
             }
+        }
+    }
 
-            tracing::info!(
-                "User {} has viewer permission for resource {}",
-                user_id,
-                resource_key
-            );
+    let cleaned_keys: Vec<ResourceKey> = cleaned.iter().map(|(_, key, _)| key.clone()).collect();
+    let deleted: HashSet<ResourceKey> = ctx
+        .resources
+        .delete_many(&cleaned_keys)
+        .await?
+        .into_iter()
+        .collect();
+    for (id, key, tuples_removed) in cleaned {
+        let result = if deleted.contains(&key) {
+            BulkDeleteResult::Deleted { tuples_removed }
+        } else {
+            BulkDeleteResult::NotFound
+        };
+        results.insert(id, result);
+    }
 
-            Ok((
-                StatusCode::OK,
-                Json(json!({
-                    "resource_id": resource_key,
-                    "name": params.name,
-                    "service_name": params.service_name,
-                    "service_type": params.service_type,
-                    "org_id": params.org_id
-                })),
-            ))
-        }
-        Err(e) => {
-            tracing::error!("Error checking permission: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to check permission",
-                    "message": e.to_string()
-                })),
-            ))
+    tracing::info!("User {} bulk-deleted {} resources", user_id, deleted.len());
+
+    Ok((StatusCode::OK, Json(json!({ "resources": results }))))
+}
+
+/// Read the assertions stored for the configured authorization model
+async fn read_assertions(ctx: &Ctx) -> Result<Vec<AssertionView>, ApiError> {
+    let (store_id, authorization_model_id) = fga_ids(ctx)?;
+    let request = fga::request(
+        ReadAssertionsRequest {
+            store_id: store_id.to_string(),
+            authorization_model_id: authorization_model_id.to_string(),
+        },
+        ctx.fga_config.timeouts.list,
+    );
+    let assertions = ctx
+        .fga_client()
+        .read_assertions(request)
+        .await
+        .map_err(|e| ApiError::fga("Failed to read assertions", &e))?
+        .into_inner()
+        .assertions;
+    Ok(assertions.into_iter().map(AssertionView::from).collect())
+}
+
+/// List the assertions stored for the configured model (admin only)
+pub async fn get_assertions(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&ctx, &auth_user.user_id).await?;
+
+    let assertions = read_assertions(&ctx).await?;
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "authorization_model_id": ctx.fga_config.authorization_model_id,
+            "assertions": assertions
+        })),
+    ))
+}
+
+/// Replace the assertions stored for the configured model (admin only)
+pub async fn put_assertions(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    ApiJson(payload): ApiJson<WriteAssertionsBody>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&ctx, &auth_user.user_id).await?;
+
+    if payload.assertions.len() > MAX_ASSERTIONS {
+        return Err(ApiError::validation(format!(
+            "At most {} assertions can be stored",
+            MAX_ASSERTIONS
+        )));
+    }
+    let users = payload.assertions.iter().flat_map(|assertion| {
+        std::iter::once(&assertion.user)
+            .chain(assertion.contextual_tuples.iter().map(|tuple| &tuple.user))
+    });
+    for user in users {
+        if !is_valid_fga_user(user) {
+            return Err(ApiError::validation(format!(
+                "Invalid user '{}', expected type:id, type:* or type:id#relation",
+                user
+            )));
         }
     }
+
+    let (store_id, authorization_model_id) = fga_ids(&ctx)?;
+    let request = fga::request(
+        WriteAssertionsRequest {
+            store_id: store_id.to_string(),
+            authorization_model_id: authorization_model_id.to_string(),
+            assertions: payload
+                .assertions
+                .iter()
+                .map(|assertion| Assertion {
+                    tuple_key: Some(AssertionTupleKey {
+                        user: assertion.user.clone(),
+                        relation: assertion.relation.clone(),
+                        object: assertion.object.clone(),
+                    }),
+                    expectation: assertion.expectation,
+                    contextual_tuples: assertion.contextual_tuple_keys(),
+                    ..Default::default()
+                })
+                .collect(),
+        },
+        ctx.fga_config.timeouts.write,
+    );
+    ctx.fga_client()
+        .write_assertions(request)
+        .await
+        .map_err(|e| ApiError::fga("Failed to write assertions", &e))?;
+
+    tracing::info!(
+        target: "audit",
+        admin = %auth_user.user_id,
+        model = %authorization_model_id,
+        count = payload.assertions.len(),
+        "Admin replaced model assertions"
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "authorization_model_id": authorization_model_id,
+            "written": payload.assertions.len()
+        })),
+    ))
 }
 
-/// List objects that a user has access to using OpenFGA ListObjects API
-pub async fn list_objects(
+/// Run the stored assertions against the configured model (admin only)
+///
+/// Each assertion is checked with its contextual tuples and passes when the
+/// result matches its expectation. The response is 200 whether or not all
+/// pass, test runners read `failed` and the per-assertion results.
+pub async fn run_assertions(
     State(ctx): State<Arc<Ctx>>,
     Extension(auth_user): Extension<AuthUser>,
-    Query(params): Query<ListQueryParams>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    let user_id = &auth_user.user_id;
-    let relation = params.relation.unwrap_or_else(|| "viewer".to_string());
-    let object_type = params.object_type.unwrap_or_else(|| "resource".to_string());
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&ctx, &auth_user.user_id).await?;
+
+    let assertions = read_assertions(&ctx).await?;
+    let (store_id, authorization_model_id) = fga_ids(&ctx)?;
+
+    let semaphore = Arc::new(Semaphore::new(FGA_FANOUT_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for (index, assertion) in assertions.into_iter().enumerate() {
+        let ctx = ctx.clone();
+        let semaphore = semaphore.clone();
+        let check_request = CheckRequest {
+            store_id: store_id.to_string(),
+            tuple_key: Some(CheckRequestTupleKey {
+                user: assertion.user.clone(),
+                relation: assertion.relation.clone(),
+                object: assertion.object.clone(),
+            }),
+            contextual_tuples: Some(ContextualTupleKeys {
+                tuple_keys: assertion.contextual_tuple_keys(),
+            }),
+            authorization_model_id: authorization_model_id.to_string(),
+            consistency: ctx.fga_config.consistency as i32,
+            ..Default::default()
+        };
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let result = fga::with_retry(&ctx.fga_config.retry, "check", || {
+                let client = ctx.fga_client();
+                let request = fga::request(check_request.clone(), ctx.fga_config.timeouts.check);
+                async move { client.check(request).await }
+            })
+            .await;
+            (index, assertion, result)
+        });
+    }
+
+    let mut results = Vec::with_capacity(tasks.len());
+    while let Some(joined) = tasks.join_next().await {
+        let Ok((index, assertion, result)) = joined else {
+            return Err(ApiError::new(
+                ErrorCode::Internal,
+                "Assertion task failed unexpectedly",
+            ));
+        };
+        let (allowed, error) = match result {
+            Ok(response) => (Some(response.into_inner().allowed), None),
+            Err(e) => (None, Some(e.message().to_string())),
+        };
+        results.push((
+            index,
+            AssertionResult {
+                passed: allowed == Some(assertion.expectation),
+                assertion,
+                allowed,
+                error,
+            },
+        ));
+    }
+    results.sort_by_key(|(index, _)| *index);
+    let results: Vec<AssertionResult> = results.into_iter().map(|(_, result)| result).collect();
 
+    let passed = results.iter().filter(|result| result.passed).count();
+    let failed = results.len() - passed;
     tracing::info!(
-        "Listing {} objects for user {} with relation {}",
-        object_type,
-        user_id,
-        relation
+        "Ran {} assertions against model {}: {} passed, {} failed",
+        results.len(),
+        authorization_model_id,
+        passed,
+        failed
     );
 
-    // Create ListObjects request
-    let request = Request::new(ListObjectsRequest {
-        store_id: ctx.fga_config.store_id.clone(),
-        authorization_model_id: ctx
-            .fga_config
-            .authorization_model_id
-            .clone()
-            .unwrap_or_default(),
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "authorization_model_id": authorization_model_id,
+            "passed": passed,
+            "failed": failed,
+            "results": results
+        })),
+    ))
+}
+
+/// Page through the tuple changelog, optionally for one object type (admin only)
+///
+/// Unlike the ListObjects-backed listings this pages natively: the cursor
+/// wraps OpenFGA's own continuation token.
+pub async fn get_changes(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<ChangesQueryParams>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&ctx, &auth_user.user_id).await?;
+
+    let page_size = params.page_size.unwrap_or(DEFAULT_CHANGES_PAGE_SIZE);
+    if page_size == 0 || page_size > MAX_CHANGES_PAGE_SIZE {
+        return Err(ApiError::validation(format!(
+            "page_size must be between 1 and {}",
+            MAX_CHANGES_PAGE_SIZE
+        )));
+    }
+    let object_type = params.object_type.unwrap_or_default();
+    let continuation_token = match params.cursor.as_deref() {
+        Some(cursor) => Cursor::decode(cursor, "changes", &object_type)?,
+        None => String::new(),
+    };
+
+    let (store_id, _) = fga_ids(&ctx)?;
+    let read_request = ReadChangesRequest {
+        store_id: store_id.to_string(),
         r#type: object_type.clone(),
-        consistency: 8,
-        relation: relation.clone(),
-        user: user_id.to_string(),
-        contextual_tuples: None,
-        context: None,
-    });
+        page_size: Some(page_size as i32),
+        continuation_token,
+        ..Default::default()
+    };
+    let response = fga::with_retry(&ctx.fga_config.retry, "read_changes", || {
+        let client = ctx.fga_client();
+        let request = fga::request(read_request.clone(), ctx.fga_config.timeouts.list);
+        async move { client.read_changes(request).await }
+    })
+    .await
+    .map_err(|e| {
+        tracing::error!("Error reading changes: {}", e);
+        ApiError::fga("Failed to read changes", &e)
+    })?
+    .into_inner();
 
-    match ctx.fga_client.clone().list_objects(request).await {
-        Ok(response) => {
-            let objects = response.into_inner().objects;
-            tracing::info!(
-                "Found {} {} objects for user {}",
-                objects.len(),
-                object_type,
-                user_id
-            );
+    let items: Vec<TupleChangeView> = response
+        .changes
+        .into_iter()
+        .filter_map(|change| {
+            let key = change.tuple_key?;
+            let operation = match TupleOperation::try_from(change.operation) {
+                Ok(TupleOperation::Write) => "write",
+                Ok(TupleOperation::Delete) => "delete",
+                Err(_) => "unknown",
+            };
+            let timestamp = change.timestamp.and_then(|timestamp| {
+                let changed_at = OffsetDateTime::from_unix_timestamp(timestamp.seconds).ok()?
+                    + time::Duration::nanoseconds(timestamp.nanos.into());
+                changed_at.format(&Rfc3339).ok()
+            });
+            Some(TupleChangeView {
+                operation,
+                user: key.user,
+                relation: key.relation,
+                object: key.object,
+                timestamp,
+            })
+        })
+        .collect();
 
-            Ok((
-                StatusCode::OK,
-                Json(json!(ListResponse {
-                    total_count: objects.len(),
-                    objects: objects,
-                    object_type: object_type,
-                    relation: relation,
-                })),
-            ))
-        }
-        Err(e) => {
-            tracing::error!("Error listing objects: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to list objects",
-                    "message": e.to_string()
-                })),
-            ))
-        }
+    // OpenFGA returns a token even on the last page, an empty page ends the log
+    let mut list = ListEnvelope::new(items);
+    if !list.items.is_empty() && !response.continuation_token.is_empty() {
+        list.page.next_token =
+            Some(Cursor::new("changes", &object_type, response.continuation_token).encode());
     }
+
+    tracing::info!(
+        target: "audit",
+        admin = %auth_user.user_id,
+        object_type = %object_type,
+        "Admin read the tuple changelog"
+    );
+
+    Ok((StatusCode::OK, Json(json!(list))))
 }
 
-/// Get shared resources from parent organizations (comprehensive approach)
-pub async fn get_shared_resources(
+/// Tuple changes made through grant and revoke under an operation ID, oldest
+/// first (admin only)
+pub async fn get_operation(
     State(ctx): State<Arc<Ctx>>,
     Extension(auth_user): Extension<AuthUser>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    let user_id = &auth_user.user_id;
+    Path(operation_id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&ctx, &auth_user.user_id).await?;
+    validate_operation_id(&operation_id)?;
 
-    tracing::info!("Getting shared resources for user {}", user_id);
+    let changes = ctx.tuple_changes.by_operation(&operation_id).await?;
+    if changes.is_empty() {
+        return Err(ApiError::not_found(format!(
+            "No tuple changes recorded for operation '{}'",
+            operation_id
+        )));
+    }
 
-    let mut shared_services = Vec::new();
-    let mut shared_service_types = Vec::new();
-    let mut shared_resources = Vec::new();
+    let items = changes
+        .into_iter()
+        .map(|change| OperationChangeView {
+            operation: change.operation,
+            user: change.user,
+            relation: change.relation,
+            object: change.object,
+            changed_by: change.changed_by,
+            timestamp: change.changed_at.format(&Rfc3339).ok(),
+        })
+        .collect();
 
-    // List all object types that the user can view
-    let object_types = vec!["service", "service_type", "resource"];
-    let relations = vec!["viewer", "editor", "admin"];
+    tracing::info!(
+        target: "audit",
+        admin = %auth_user.user_id,
+        operation_id = %operation_id,
+        "Admin read the changes of an operation"
+    );
 
-    for object_type in object_types {
-        for relation in &relations {
-            let request = Request::new(ListObjectsRequest {
-                store_id: ctx.fga_config.store_id.clone(),
-                authorization_model_id: ctx
-                    .fga_config
-                    .authorization_model_id
-                    .clone()
-                    .unwrap_or_default(),
-                r#type: object_type.to_string(),
-                consistency: 8,
-                relation: relation.to_string(),
-                user: user_id.to_string(),
-                contextual_tuples: None,
-                context: None,
-            });
+    let response = OperationResponse {
+        operation_id,
+        list: ListEnvelope::new(items),
+    };
+    Ok((StatusCode::OK, Json(json!(response))))
+}
 
-            match ctx.fga_client.clone().list_objects(request).await {
-                Ok(response) => {
-                    let objects = response.into_inner().objects;
-
-                    for object_id in objects {
-                        match object_type {
-                            "service" => {
-                                if let Some(service_name) =
-                                    object_id.clone().strip_prefix("service:")
-                                {
-                                    shared_services.push(SharedService {
-                                        id: object_id,
-                                        name: service_name.to_string(),
-                                        shared_via: "parent_organization".to_string(),
-                                        permissions: vec![relation.to_string()],
-                                    });
-                                }
-                            }
-                            "service_type" => {
-                                if let Some(service_type_path) =
-                                    object_id.clone().strip_prefix("service_type:")
-                                {
-                                    let parts: Vec<&str> = service_type_path.split('/').collect();
-                                    if parts.len() == 2 {
-                                        shared_service_types.push(SharedServiceType {
-                                            id: object_id,
-                                            service_name: parts[0].to_string(),
-                                            service_type: parts[1].to_string(),
-                                            shared_via: "parent_organization".to_string(),
-                                            permissions: vec![relation.to_string()],
-                                        });
-                                    }
-                                }
-                            }
-                            "resource" => {
-                                if let Some(resource_path) =
-                                    object_id.clone().strip_prefix("resource:")
-                                {
-                                    let parts: Vec<&str> = resource_path.split('/').collect();
-                                    if parts.len() == 3 {
-                                        shared_resources.push(SharedResource {
-                                            id: object_id,
-                                            service_name: parts[0].to_string(),
-                                            service_type: parts[1].to_string(),
-                                            resource_name: parts[2].to_string(),
-                                            shared_via: "parent_organization".to_string(),
-                                            permissions: vec![relation.to_string()],
-                                        });
-                                    }
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!(
-                        "Error listing {} objects with relation {}: {}",
-                        object_type,
-                        relation,
-                        e
-                    );
-                }
-            }
-        }
+/// Move an organisation under a new parent (admin only)
+///
+/// Swaps the parent's `child` tuple in a single write. OpenFGA recomputes
+/// inherited access lazily on the next check, so this only handles our own
+/// side effects: cached check decisions and shared-resources responses are
+/// dropped for every user, since any of them may have gained or lost access
+/// through the hierarchy. The move is recorded in the outbox as an
+/// `organisation.reparented` event.
+/// With `?dry_run=true` the tuple swap is only described.
+pub async fn reparent_organization(
+    State(ctx): State<Arc<Ctx>>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(org_id): Path<String>,
+    Query(query): Query<DryRunQueryParams>,
+    ApiJson(payload): ApiJson<ReparentRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&ctx, &auth_user.user_id).await?;
+
+    if payload.parent_org_id == org_id {
+        return Err(ApiError::validation(
+            "An organisation cannot be its own parent",
+        ));
     }
 
-    // Deduplicate and merge permissions
-    let mut service_map: HashMap<String, SharedService> = HashMap::new();
-    let mut service_type_map: HashMap<String, SharedServiceType> = HashMap::new();
-    let mut resource_map: HashMap<String, SharedResource> = HashMap::new();
+    let org = org_object(&org_id);
+    let new_parent = org_object(&payload.parent_org_id);
 
-    for service in shared_services {
-        service_map
-            .entry(service.id.clone())
-            .and_modify(|existing| {
-                existing.permissions.extend(service.permissions.clone());
-                existing.permissions.sort();
-                existing.permissions.dedup();
-            })
-            .or_insert(service);
+    // Find the current parents: tuples `organisation:<parent>#child@<org>`
+    let request = fga::request(
+        ReadRequest {
+            store_id: ctx.fga_config.store_id.clone(),
+            tuple_key: Some(ReadRequestTupleKey {
+                user: org.clone(),
+                relation: "child".to_string(),
+                object: "organisation:".to_string(),
+            }),
+            consistency: ctx.fga_config.consistency as i32,
+            ..Default::default()
+        },
+        ctx.fga_config.timeouts.list,
+    );
+    let old_parents: Vec<String> = ctx
+        .fga_client()
+        .read(request)
+        .await
+        .map_err(|e| ApiError::fga("Failed to read current parent", &e))?
+        .into_inner()
+        .tuples
+        .into_iter()
+        .filter_map(|tuple| tuple.key.map(|key| key.object))
+        .collect();
+
+    if old_parents.contains(&new_parent) && old_parents.len() == 1 {
+        return Ok((
+            StatusCode::OK,
+            Json(json!({
+                "organisation": org,
+                "parent": new_parent,
+                "previous_parents": old_parents,
+                "changed": false
+            })),
+        ));
     }
 
-    for service_type in shared_service_types {
-        service_type_map
-            .entry(service_type.id.clone())
-            .and_modify(|existing| {
-                existing
-                    .permissions
-                    .extend(service_type.permissions.clone());
-                existing.permissions.sort();
-                existing.permissions.dedup();
-            })
-            .or_insert(service_type);
+    let writes = if old_parents.contains(&new_parent) {
+        Vec::new()
+    } else {
+        vec![TupleKey {
+            user: org.clone(),
+            relation: "child".to_string(),
+            object: new_parent.clone(),
+            ..Default::default()
+        }]
+    };
+    let deletes = old_parents
+        .iter()
+        .filter(|parent| **parent != new_parent)
+        .map(|parent| TupleKeyWithoutCondition {
+            user: org.clone(),
+            relation: "child".to_string(),
+            object: parent.clone(),
+        })
+        .collect();
+
+    if query.dry_run {
+        tracing::info!(
+            "Dry-run reparent of {} under {} by {}",
+            org,
+            new_parent,
+            auth_user.user_id
+        );
+        return Ok(dry_run_response(&write_request(&ctx, writes, deletes)));
     }
 
-    for resource in shared_resources {
-        resource_map
-            .entry(resource.id.clone())
-            .and_modify(|existing| {
-                existing.permissions.extend(resource.permissions.clone());
-                existing.permissions.sort();
-                existing.permissions.dedup();
-            })
-            .or_insert(resource);
+    let invalidated_checks = fga_write(&ctx, writes, deletes)
+        .await
+        .map_err(|e| ApiError::fga("Failed to reparent organisation", &e))?;
+
+    let invalidated = ctx.shared_resources_cache.clear() + invalidated_checks;
+
+    // OpenFGA already has the new parent, so a failure is logged instead of
+    // failing the request
+    let event = json!({
+        "organisation": org,
+        "parent": new_parent,
+        "previous_parents": old_parents,
+        "changed_by": auth_user.user_id
+    });
+    if let Err(e) = ctx
+        .resources
+        .record_organisation_event(ORGANISATION_REPARENTED, event)
+        .await
+    {
+        tracing::error!("Failed to record reparent event for {}: {}", org, e);
     }
 
-    let response = SharedResourcesResponse {
-        services: service_map.into_values().collect(),
-        service_types: service_type_map.into_values().collect(),
-        resources: resource_map.into_values().collect(),
-    };
+    tracing::info!(
+        target: "audit",
+        admin = %auth_user.user_id,
+        organisation = %org,
+        parent = %new_parent,
+        previous_parents = ?old_parents,
+        invalidated,
+        "Admin reparented organisation"
+    );
 
-    Ok((StatusCode::OK, Json(json!(response))))
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "organisation": org,
+            "parent": new_parent,
+            "previous_parents": old_parents,
+            "changed": true,
+            "invalidated_cache_entries": invalidated
+        })),
+    ))
 }
 
 // Delete a resource
@@ -594,7 +4617,8 @@ pub async fn delete_resource(
     State(ctx): State<Arc<Ctx>>,
     Extension(auth_user): Extension<AuthUser>,
     Path(params): Path<ResourceParams>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    Query(query): Query<DryRunQueryParams>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
     tracing::info!(
         "Deleting resource: {}/{}/{}/{}",
         params.service_name,
@@ -603,14 +4627,14 @@ pub async fn delete_resource(
         params.name
     );
 
-    let resource_key = format!(
-        "{}/{}/{}/{}",
-        params.service_name, params.service_type, params.org_id, params.name
-    );
+    let resource_key = resource_object(&params);
 
     // Get user ID from authentication middleware
     let user_id = &auth_user.user_id;
 
+    // Optionally require membership of the resource's organisation first
+    enforce_org_membership(&ctx, user_id, &params.org_id).await?;
+
     // To delete a resource, user needs to be an owner of the resource
     match check_permission(&ctx, user_id, "owner", &resource_key).await {
         Ok(allowed) => {
@@ -620,12 +4644,8 @@ pub async fn delete_resource(
                     user_id,
                     resource_key
                 );
-                return Err((
-                    StatusCode::FORBIDDEN,
-                    Json(json!({
-                        "error": "Permission denied",
-                        "message": "You do not have permission to delete this resource"
-                    })),
+                return Err(ApiError::forbidden(
+                    "You do not have permission to delete this resource",
                 ));
             }
 
@@ -635,25 +4655,75 @@ pub async fn delete_resource(
                 resource_key
             );
 
-            // In a real app, we would delete the resource from the database
+            // A dry run only reports the tuples that reference the resource
+            // and whether its row exists
+            if query.dry_run {
+                let (tuples, truncated) = read_object_tuples(&ctx, &resource_key)
+                    .await
+                    .map_err(|e| ApiError::fga("Failed to read tuples", &e))?;
+                let row_exists = ctx.resources.get(&params.key()).await?.is_some();
+
+                tracing::info!(
+                    "Dry-run delete of {} would remove {} tuples",
+                    resource_key,
+                    tuples.len()
+                );
+
+                return Ok((
+                    StatusCode::OK,
+                    Json(json!({
+                        "dry_run": true,
+                        "resource_id": resource_key,
+                        "row_exists": row_exists,
+                        "tuples": tuples,
+                        "tuples_truncated": truncated
+                    })),
+                ));
+            }
+
+            if ctx.resources.get(&params.key()).await?.is_none() {
+                return Err(ApiError::not_found(format!(
+                    "Resource {} not found",
+                    resource_key
+                )));
+            }
+
+            // Remove the tuples before the row, so a failure leaves a resource
+            // that can be deleted again rather than grants that would carry
+            // over to a new resource with the same key
+            let tuples_removed = delete_object_tuples(&ctx, &resource_key)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error removing tuples of {}: {}", resource_key, e);
+                    ApiError::fga("Failed to delete tuples", &e)
+                })?;
+
+            if !ctx.resources.delete(&params.key()).await? {
+                return Err(ApiError::not_found(format!(
+                    "Resource {} not found",
+                    resource_key
+                )));
+            }
+
+            tracing::info!(
+                "User {} deleted resource {} and {} tuples",
+                user_id,
+                resource_key,
+                tuples_removed
+            );
 
             Ok((
                 StatusCode::OK,
                 Json(json!({
                     "message": "Resource deleted successfully",
-                    "resource_id": resource_key
+                    "resource_id": resource_key,
+                    "tuples_removed": tuples_removed
                 })),
             ))
         }
         Err(e) => {
             tracing::error!("Error checking permission: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to check permission",
-                    "message": e.to_string()
-                })),
-            ))
+            Err(e.into())
         }
     }
 }
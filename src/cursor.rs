@@ -0,0 +1,55 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ApiError;
+
+/// Opaque pagination cursor shared by all list endpoints
+///
+/// The cursor carries the underlying continuation token together with the
+/// endpoint and query it was issued for, so a cursor cannot be replayed
+/// against a different endpoint or query. Endpoints backed by ListObjects,
+/// which has no native paging, use the offset of the next item as the token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    #[serde(rename = "e")]
+    pub endpoint: String,
+    #[serde(rename = "q")]
+    pub query: String,
+    #[serde(rename = "t")]
+    pub token: String,
+}
+
+impl Cursor {
+    pub fn new(endpoint: &str, query: &str, token: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            query: query.to_string(),
+            token: token.into(),
+        }
+    }
+
+    /// Encode as URL-safe base64 for the `next_token` field
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("cursor serializes to JSON");
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// Decode a cursor sent by a client and return its continuation token,
+    /// rejecting cursors issued for another endpoint or query
+    pub fn decode(encoded: &str, endpoint: &str, query: &str) -> Result<String, ApiError> {
+        let cursor: Cursor = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| ApiError::validation("Malformed cursor"))?;
+
+        if cursor.endpoint != endpoint || cursor.query != query {
+            return Err(ApiError::validation(
+                "Cursor was issued for a different endpoint or query",
+            ));
+        }
+
+        Ok(cursor.token)
+    }
+}
@@ -0,0 +1,371 @@
+use axum::{
+    Json,
+    extract::{Request, State, rejection::JsonRejection},
+    http::{StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::context::Ctx;
+use crate::fga::FgaError;
+use crate::request_id;
+use crate::store::StoreError;
+
+/// Stable, machine-readable error codes returned in the `code` field of error
+/// responses. Clients should branch on these rather than on `message`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// No identity was supplied with the request (401)
+    Unauthenticated,
+    /// The bearer token is expired, badly signed or from an untrusted issuer (401)
+    InvalidToken,
+    /// The supplied identity could not be parsed (400)
+    InvalidIdentity,
+    /// The user ID does not match the configured format (400)
+    InvalidUserId,
+    /// The caller lacks the relation required for the operation (403)
+    Forbidden,
+    /// The caller is not a member of the resource's organisation (403)
+    OrgMismatch,
+    /// The requested route, type or object does not exist (404)
+    NotFound,
+    /// The route exists but not for this HTTP method (405)
+    MethodNotAllowed,
+    /// The object already exists (409)
+    Conflict,
+    /// The object changed since the version named in `If-Match` (412)
+    PreconditionFailed,
+    /// An update was sent without an `If-Match` header (428)
+    PreconditionRequired,
+    /// The request parameters or body failed validation (400)
+    ValidationFailed,
+    /// Resource properties do not conform to the service type's schema (422)
+    InvalidProperties,
+    /// The request body is not valid JSON or does not fit the expected shape (400)
+    InvalidJson,
+    /// The request body exceeds the configured size limit (413)
+    PayloadTooLarge,
+    /// The request did not complete within the configured request timeout (408)
+    Timeout,
+    /// The request body is not declared as `application/json` (415)
+    UnsupportedMediaType,
+    /// A mutating request did not state an `X-Action-Reason` (400)
+    ReasonRequired,
+    /// The OpenFGA store or authorization model is not configured (500)
+    FgaNotConfigured,
+    /// The OpenFGA server could not be reached (503)
+    FgaUnavailable,
+    /// OpenFGA did not answer within the call's deadline (504)
+    FgaTimeout,
+    /// OpenFGA rejected or failed the call (500)
+    FgaError,
+    /// The service is in a read-only window and rejects mutations (503)
+    ReadOnly,
+    /// The endpoint's concurrency limit is reached (503)
+    Overloaded,
+    /// The caller sent more requests than its rate limit allows (429)
+    RateLimited,
+    /// The database could not be reached (503)
+    DbUnavailable,
+    /// Any other unexpected failure (500)
+    Internal,
+}
+
+impl ErrorCode {
+    /// The code as it appears in responses
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::Unauthenticated => "unauthenticated",
+            ErrorCode::InvalidToken => "invalid_token",
+            ErrorCode::InvalidIdentity => "invalid_identity",
+            ErrorCode::InvalidUserId => "invalid_user_id",
+            ErrorCode::Forbidden => "forbidden",
+            ErrorCode::OrgMismatch => "org_mismatch",
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::MethodNotAllowed => "method_not_allowed",
+            ErrorCode::Conflict => "conflict",
+            ErrorCode::PreconditionFailed => "precondition_failed",
+            ErrorCode::PreconditionRequired => "precondition_required",
+            ErrorCode::ValidationFailed => "validation_failed",
+            ErrorCode::InvalidProperties => "invalid_properties",
+            ErrorCode::InvalidJson => "invalid_json",
+            ErrorCode::PayloadTooLarge => "payload_too_large",
+            ErrorCode::Timeout => "timeout",
+            ErrorCode::UnsupportedMediaType => "unsupported_media_type",
+            ErrorCode::ReasonRequired => "reason_required",
+            ErrorCode::FgaNotConfigured => "fga_not_configured",
+            ErrorCode::FgaUnavailable => "fga_unavailable",
+            ErrorCode::FgaTimeout => "fga_timeout",
+            ErrorCode::FgaError => "fga_error",
+            ErrorCode::ReadOnly => "read_only",
+            ErrorCode::Overloaded => "overloaded",
+            ErrorCode::RateLimited => "rate_limited",
+            ErrorCode::DbUnavailable => "db_unavailable",
+            ErrorCode::Internal => "internal",
+        }
+    }
+
+    /// The HTTP status returned for this code
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::Unauthenticated | ErrorCode::InvalidToken => StatusCode::UNAUTHORIZED,
+            ErrorCode::InvalidIdentity
+            | ErrorCode::InvalidUserId
+            | ErrorCode::ValidationFailed
+            | ErrorCode::InvalidJson
+            | ErrorCode::ReasonRequired => StatusCode::BAD_REQUEST,
+            ErrorCode::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorCode::Timeout => StatusCode::REQUEST_TIMEOUT,
+            ErrorCode::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ErrorCode::Forbidden | ErrorCode::OrgMismatch => StatusCode::FORBIDDEN,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            ErrorCode::Conflict => StatusCode::CONFLICT,
+            ErrorCode::InvalidProperties => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+            ErrorCode::PreconditionRequired => StatusCode::PRECONDITION_REQUIRED,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::FgaUnavailable
+            | ErrorCode::ReadOnly
+            | ErrorCode::Overloaded
+            | ErrorCode::DbUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::FgaTimeout => StatusCode::GATEWAY_TIMEOUT,
+            ErrorCode::FgaNotConfigured | ErrorCode::FgaError | ErrorCode::Internal => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    /// The code for a failed OpenFGA call, by its gRPC status
+    ///
+    /// `Unauthenticated` means OpenFGA rejected our own credentials, which the
+    /// caller cannot fix, so it stays a server error.
+    pub fn from_grpc(code: tonic::Code) -> Self {
+        match code {
+            tonic::Code::NotFound => ErrorCode::NotFound,
+            tonic::Code::InvalidArgument
+            | tonic::Code::OutOfRange
+            | tonic::Code::FailedPrecondition => ErrorCode::ValidationFailed,
+            tonic::Code::PermissionDenied => ErrorCode::Forbidden,
+            tonic::Code::AlreadyExists | tonic::Code::Aborted => ErrorCode::Conflict,
+            tonic::Code::ResourceExhausted => ErrorCode::RateLimited,
+            tonic::Code::Unavailable => ErrorCode::FgaUnavailable,
+            tonic::Code::DeadlineExceeded => ErrorCode::FgaTimeout,
+            _ => ErrorCode::FgaError,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error returned by handlers and middleware, rendered as
+/// `{code, message, request_id}` plus `details` when set
+#[derive(Clone, Debug)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Seconds the client should wait before retrying, sent as `Retry-After`
+    pub retry_after: Option<u64>,
+    /// Structured context for the client, e.g. individual validation errors
+    pub details: Option<Value>,
+}
+
+impl ApiError {
+    /// Create an error with the given code and human-readable message
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retry_after: None,
+            details: None,
+        }
+    }
+
+    /// Ask the client to wait the given number of seconds before retrying
+    pub fn with_retry_after(mut self, seconds: u64) -> Self {
+        self.retry_after = Some(seconds);
+        self
+    }
+
+    /// Attach structured context, sent as `details`
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Forbidden, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::ValidationFailed, message)
+    }
+
+    /// Wrap a failed OpenFGA call, prefixing the message with what was attempted
+    pub fn fga(context: &str, status: &tonic::Status) -> Self {
+        Self::new(
+            ErrorCode::from_grpc(status.code()),
+            format!("{}: {}", context, status.message()),
+        )
+    }
+}
+
+impl From<FgaError> for ApiError {
+    fn from(e: FgaError) -> Self {
+        let code = match &e {
+            FgaError::StoreNotConfigured | FgaError::ModelNotConfigured => {
+                ErrorCode::FgaNotConfigured
+            }
+            FgaError::Unavailable(_) => {
+                return Self::new(
+                    ErrorCode::FgaUnavailable,
+                    "OpenFGA server is not available. Please check server status and configuration.",
+                );
+            }
+            // OpenFGA refused our credentials, which the caller cannot fix,
+            // so neither code is passed through as the caller's 401 or 403
+            FgaError::Denied(_) => ErrorCode::FgaError,
+            FgaError::Other(status) => ErrorCode::from_grpc(status.code()),
+        };
+        Self::new(code, e.to_string())
+    }
+}
+
+impl From<tonic::Status> for ApiError {
+    fn from(status: tonic::Status) -> Self {
+        FgaError::from(status).into()
+    }
+}
+
+impl From<StoreError> for ApiError {
+    fn from(e: StoreError) -> Self {
+        match e {
+            StoreError::Conflict => Self::new(ErrorCode::Conflict, "Resource already exists"),
+            StoreError::VersionMismatch => Self::new(
+                ErrorCode::PreconditionFailed,
+                "Resource was modified since it was read, fetch it again and retry",
+            ),
+            StoreError::Unavailable(message) => {
+                tracing::error!("Resource store unavailable: {}", message);
+                Self::new(ErrorCode::DbUnavailable, "The database is not available")
+            }
+            StoreError::Internal(message) => {
+                tracing::error!("Resource store error: {}", message);
+                Self::new(ErrorCode::Internal, "Failed to access stored resources")
+            }
+        }
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        // Syntax and shape errors alike are the client's payload to fix, so
+        // both are 400 rather than axum's split into 400 and 422
+        let code = match rejection.status() {
+            StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => ErrorCode::UnsupportedMediaType,
+            _ => ErrorCode::InvalidJson,
+        };
+        Self::new(code, rejection.body_text())
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut body = json!({
+            "code": self.code,
+            "message": self.message
+        });
+        if let Some(details) = self.details {
+            body["details"] = details;
+        }
+        if let Some(id) = request_id::current() {
+            body["request_id"] = id.into();
+        }
+        let mut response = (self.code.status(), Json(body)).into_response();
+
+        if let Some(seconds) = self.retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, seconds.into());
+        }
+
+        response
+    }
+}
+
+/// Give the 408 of `TimeoutLayer` and the 413 of `RequestBodyLimitLayer` the
+/// standard error body
+///
+/// The layers answer with an empty and a plain-text body. A 413 that already
+/// is an `ApiError`, from a body cut off while being parsed, is left alone.
+pub async fn limit_rejection_body(State(ctx): State<Arc<Ctx>>, response: Response) -> Response {
+    let error = match response.status() {
+        StatusCode::REQUEST_TIMEOUT => ApiError::new(
+            ErrorCode::Timeout,
+            format!(
+                "Request did not complete within {}s",
+                ctx.request_timeout.as_secs_f64()
+            ),
+        ),
+        StatusCode::PAYLOAD_TOO_LARGE => ApiError::new(
+            ErrorCode::PayloadTooLarge,
+            format!(
+                "Request body exceeds the limit of {} bytes",
+                ctx.request_body_limit
+            ),
+        ),
+        _ => return response,
+    };
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if is_json {
+        return response;
+    }
+    error.into_response()
+}
+
+/// Ensure every 503 response tells the client when to retry
+///
+/// Errors that know their own backoff (e.g. a read-only window) set
+/// `Retry-After` themselves; all other 503s get the configured base backoff.
+pub async fn retry_after_middleware(
+    State(ctx): State<Arc<Ctx>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+
+    if response.status() == StatusCode::SERVICE_UNAVAILABLE
+        && !response.headers().contains_key(header::RETRY_AFTER)
+    {
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, ctx.retry_after_secs.into());
+    }
+
+    response
+}
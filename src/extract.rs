@@ -0,0 +1,28 @@
+use axum::{
+    Json,
+    extract::{FromRequest, Request, rejection::JsonRejection},
+};
+
+use crate::error::ApiError;
+
+/// `Json` body extractor whose rejections use the API error shape, with the
+/// parse error in the message so clients can fix their payload
+pub struct ApiJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    Json<T>: FromRequest<S, Rejection = JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Json::<T>::from_request(request, state).await {
+            Ok(Json(value)) => Ok(Self(value)),
+            Err(rejection) => {
+                tracing::warn!("Rejecting request body: {}", rejection.body_text());
+                Err(rejection.into())
+            }
+        }
+    }
+}
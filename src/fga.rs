@@ -0,0 +1,750 @@
+use async_trait::async_trait;
+use axum::{
+    extract::{Request as HttpRequest, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use openfga_client::client::{
+    AuthorizationModel, BatchCheckRequest, BatchCheckResponse, CheckRequest, CheckResponse,
+    ConsistencyPreference, CreateStoreRequest, ExpandRequest, ExpandResponse, ListObjectsRequest,
+    ListObjectsResponse, ListStoresRequest, ListUsersRequest, ListUsersResponse, Node,
+    OpenFgaServiceClient, ReadAssertionsRequest, ReadAssertionsResponse,
+    ReadAuthorizationModelRequest, ReadAuthorizationModelResponse, ReadAuthorizationModelsRequest,
+    ReadAuthorizationModelsResponse, ReadChangesRequest, ReadChangesResponse, ReadRequest,
+    ReadResponse, WriteAssertionsRequest, WriteAssertionsResponse, WriteAuthorizationModelRequest,
+    WriteRequest, WriteResponse, leaf, node,
+};
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::env;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tonic::Request;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::Channel;
+
+use crate::context::Ctx;
+use crate::telemetry;
+
+/// Identity of the authorization model requests are evaluated against
+#[derive(Clone, Debug, Serialize)]
+pub struct ModelInfo {
+    pub id: String,
+    pub schema_version: String,
+}
+
+/// Timeouts applied to outgoing OpenFGA calls, per operation type
+#[derive(Clone, Debug)]
+pub struct FgaTimeouts {
+    /// `Check` calls, expected to be fast
+    pub check: Duration,
+    /// `ListObjects` and `Read` calls, which may scan many tuples
+    pub list: Duration,
+    /// `Write` calls
+    pub write: Duration,
+}
+
+impl Default for FgaTimeouts {
+    fn default() -> Self {
+        Self {
+            check: Duration::from_millis(2_000),
+            list: Duration::from_millis(10_000),
+            write: Duration::from_millis(5_000),
+        }
+    }
+}
+
+impl FgaTimeouts {
+    /// Read `FGA_CHECK_TIMEOUT_MS`, `FGA_LIST_TIMEOUT_MS` and `FGA_WRITE_TIMEOUT_MS`,
+    /// falling back to the defaults for unset variables
+    pub fn from_env() -> Result<Self, String> {
+        let defaults = Self::default();
+        Ok(Self {
+            check: timeout_from_env("FGA_CHECK_TIMEOUT_MS", defaults.check)?,
+            list: timeout_from_env("FGA_LIST_TIMEOUT_MS", defaults.list)?,
+            write: timeout_from_env("FGA_WRITE_TIMEOUT_MS", defaults.write)?,
+        })
+    }
+}
+
+/// Consistency used when `OPENFGA_CONSISTENCY` is unset
+pub const DEFAULT_CONSISTENCY: ConsistencyPreference = ConsistencyPreference::MinimizeLatency;
+
+/// Parse a consistency preference by its OpenFGA name, e.g. `HIGHER_CONSISTENCY`
+pub fn parse_consistency(value: &str) -> Result<ConsistencyPreference, String> {
+    match ConsistencyPreference::from_str_name(value.trim()) {
+        Some(ConsistencyPreference::Unspecified) | None => Err(format!(
+            "unknown consistency '{}', expected MINIMIZE_LATENCY or HIGHER_CONSISTENCY",
+            value
+        )),
+        Some(consistency) => Ok(consistency),
+    }
+}
+
+/// Retry policy for transient OpenFGA failures
+#[derive(Clone, Debug)]
+pub struct FgaRetry {
+    /// Total attempts including the first, 1 disables retries
+    pub max_attempts: u32,
+    /// Backoff before the first retry, doubled for each further retry
+    pub base_delay: Duration,
+}
+
+impl Default for FgaRetry {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+        }
+    }
+}
+
+impl FgaRetry {
+    /// Read `FGA_RETRY_MAX_ATTEMPTS` and `FGA_RETRY_BASE_DELAY_MS`
+    pub fn from_env() -> Result<Self, String> {
+        let defaults = Self::default();
+        let max_attempts = match env::var("FGA_RETRY_MAX_ATTEMPTS") {
+            Ok(value) => match value.parse::<u32>() {
+                Ok(attempts) if attempts > 0 => attempts,
+                _ => return Err("FGA_RETRY_MAX_ATTEMPTS must be a positive number".to_string()),
+            },
+            Err(_) => defaults.max_attempts,
+        };
+        Ok(Self {
+            max_attempts,
+            base_delay: timeout_from_env("FGA_RETRY_BASE_DELAY_MS", defaults.base_delay)?,
+        })
+    }
+
+    /// Full-jitter backoff before the given retry (1 for the first retry)
+    fn backoff(&self, retry: u32) -> Duration {
+        let ceiling = self.base_delay.saturating_mul(1 << (retry - 1).min(16));
+        // A fresh RandomState is randomly keyed, which is enough for jitter
+        let random = RandomState::new().build_hasher().finish();
+        ceiling.mul_f64((random % 1_000) as f64 / 1_000.0)
+    }
+}
+
+/// Background connection check for the OpenFGA client
+#[derive(Clone, Debug)]
+pub struct FgaHealth {
+    /// Time between pings, zero disables the check
+    pub interval: Duration,
+    /// Consecutive failed pings after which the connection is rebuilt
+    pub failure_threshold: u32,
+}
+
+impl Default for FgaHealth {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            failure_threshold: 3,
+        }
+    }
+}
+
+impl FgaHealth {
+    /// Read `FGA_HEALTH_INTERVAL_SECS` and `FGA_HEALTH_FAILURE_THRESHOLD`
+    pub fn from_env() -> Result<Self, String> {
+        let defaults = Self::default();
+        let interval = match env::var("FGA_HEALTH_INTERVAL_SECS") {
+            Ok(value) => value
+                .parse()
+                .map(Duration::from_secs)
+                .map_err(|_| format!("Invalid FGA_HEALTH_INTERVAL_SECS '{}'", value))?,
+            Err(_) => defaults.interval,
+        };
+        let failure_threshold = match env::var("FGA_HEALTH_FAILURE_THRESHOLD") {
+            Ok(value) => match value.parse::<u32>() {
+                Ok(threshold) if threshold > 0 => threshold,
+                _ => {
+                    return Err(
+                        "FGA_HEALTH_FAILURE_THRESHOLD must be a positive number".to_string()
+                    );
+                }
+            },
+            Err(_) => defaults.failure_threshold,
+        };
+        Ok(Self {
+            interval,
+            failure_threshold,
+        })
+    }
+}
+
+/// Make the cheapest call that needs a working connection, whether or not a
+/// store is configured
+pub async fn ping(client: &FgaClient, timeout: Duration) -> Result<(), tonic::Status> {
+    let request = request(
+        ListStoresRequest {
+            page_size: Some(1),
+            ..Default::default()
+        },
+        timeout,
+    );
+    client.clone().list_stores(request).await.map(|_| ())
+}
+
+/// Whether a failed call is worth retrying: the server could not be reached,
+/// as opposed to rejecting the request
+///
+/// tonic reports connection failures (refused, reset, DNS) as `Unavailable`.
+pub fn is_transient(status: &tonic::Status) -> bool {
+    status.code() == tonic::Code::Unavailable
+}
+
+/// Start of OpenFGA's message for a write of a tuple that is already stored
+const TUPLE_EXISTS_MESSAGE: &str = "cannot write a tuple which already exists";
+
+/// Start of OpenFGA's message for a delete of a tuple that is not stored
+const TUPLE_MISSING_MESSAGE: &str = "cannot delete a tuple which does not exist";
+
+/// Whether OpenFGA rejected a write with `write_failed_due_to_invalid_input`
+/// and the given message
+///
+/// That error code (2017) is not a gRPC code, so tonic reports it as
+/// `Unknown`; it is `InvalidArgument` when mapped to a standard code. The
+/// code does not say which tuple was wrong, so OpenFGA's message is matched
+/// from its start rather than searched.
+fn is_invalid_write(status: &tonic::Status, message: &str) -> bool {
+    matches!(
+        status.code(),
+        tonic::Code::InvalidArgument | tonic::Code::Unknown
+    ) && status.message().starts_with(message)
+}
+
+/// Whether a write was rejected because a tuple to write is already stored
+pub fn is_tuple_exists(status: &tonic::Status) -> bool {
+    is_invalid_write(status, TUPLE_EXISTS_MESSAGE)
+}
+
+/// Whether a write was rejected because a tuple to delete is not stored
+pub fn is_tuple_missing(status: &tonic::Status) -> bool {
+    is_invalid_write(status, TUPLE_MISSING_MESSAGE)
+}
+
+/// Whether a write was rejected because its tuples are already in the
+/// requested state: the tuples to write exist, or the tuples to delete are gone
+///
+/// On a retried write this means an earlier attempt was applied even though
+/// its response was lost.
+pub fn is_already_applied(status: &tonic::Status) -> bool {
+    is_tuple_exists(status) || is_tuple_missing(status)
+}
+
+/// Why an OpenFGA operation could not produce an answer
+#[derive(Debug)]
+pub enum FgaError {
+    /// `OPENFGA_STORE_ID` is not set
+    StoreNotConfigured,
+    /// No authorization model ID is configured
+    ModelNotConfigured,
+    /// OpenFGA could not be reached, even after retrying
+    Unavailable(tonic::Status),
+    /// OpenFGA refused our credentials (e.g. a wrong `OPENFGA_API_TOKEN`)
+    Denied(tonic::Status),
+    /// Any other failure reported by OpenFGA
+    Other(tonic::Status),
+}
+
+impl From<tonic::Status> for FgaError {
+    fn from(status: tonic::Status) -> Self {
+        match status.code() {
+            tonic::Code::Unavailable => FgaError::Unavailable(status),
+            tonic::Code::Unauthenticated | tonic::Code::PermissionDenied => {
+                FgaError::Denied(status)
+            }
+            _ => FgaError::Other(status),
+        }
+    }
+}
+
+impl fmt::Display for FgaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FgaError::StoreNotConfigured => f.write_str("OpenFGA store ID not configured"),
+            FgaError::ModelNotConfigured => {
+                f.write_str("OpenFGA authorization model ID not configured")
+            }
+            FgaError::Unavailable(status) => {
+                write!(f, "OpenFGA is unavailable: {}", status.message())
+            }
+            FgaError::Denied(status) => {
+                write!(f, "OpenFGA rejected our credentials: {}", status.message())
+            }
+            FgaError::Other(status) => write!(f, "OpenFGA request failed: {}", status.message()),
+        }
+    }
+}
+
+impl std::error::Error for FgaError {}
+
+/// Run an OpenFGA call, retrying transient failures with jittered exponential
+/// backoff until the attempt budget is spent
+///
+/// `call` is invoked once per attempt since tonic requests cannot be reused.
+pub async fn with_retry<T, F, Fut>(
+    retry: &FgaRetry,
+    operation: &str,
+    mut call: F,
+) -> Result<T, tonic::Status>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, tonic::Status>>,
+{
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Err(e) if is_transient(&e) && attempt < retry.max_attempts => {
+                let delay = retry.backoff(attempt);
+                tracing::warn!(
+                    "OpenFGA {} failed (attempt {}/{}), retrying in {:?}: {}",
+                    operation,
+                    attempt,
+                    retry.max_attempts,
+                    delay,
+                    e
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn timeout_from_env(name: &str, default: Duration) -> Result<Duration, String> {
+    match env::var(name) {
+        Ok(value) => match value.parse::<u64>() {
+            Ok(ms) if ms > 0 => Ok(Duration::from_millis(ms)),
+            _ => Err(format!(
+                "{} must be a positive number of milliseconds",
+                name
+            )),
+        },
+        Err(_) => Ok(default),
+    }
+}
+
+/// Create a new store, returning its ID
+pub async fn create_store(
+    client: &FgaClient,
+    name: &str,
+    timeout: Duration,
+) -> Result<String, tonic::Status> {
+    let request = request(
+        CreateStoreRequest {
+            name: name.to_string(),
+        },
+        timeout,
+    );
+    let store = client.clone().create_store(request).await?.into_inner();
+    Ok(store.id)
+}
+
+/// Write the authorization model in a JSON file to the store, returning the new model ID
+///
+/// The file must be the JSON form of the model, as produced by
+/// `fga model transform` (e.g. `etc/auth_model.json`).
+pub async fn write_model_file(
+    client: &FgaClient,
+    store_id: &str,
+    path: &str,
+    timeout: Duration,
+) -> Result<String, String> {
+    if path.ends_with(".fga") {
+        return Err(format!(
+            "{} is in the DSL format, convert it with `fga model transform` first",
+            path
+        ));
+    }
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    let model: AuthorizationModel = serde_json::from_str(&contents)
+        .map_err(|e| format!("Invalid authorization model in {}: {}", path, e))?;
+
+    let request = request(
+        WriteAuthorizationModelRequest {
+            store_id: store_id.to_string(),
+            type_definitions: model.type_definitions,
+            schema_version: model.schema_version,
+            conditions: model.conditions,
+        },
+        timeout,
+    );
+    let written = client
+        .clone()
+        .write_authorization_model(request)
+        .await
+        .map_err(|e| format!("Failed to write the model in {}: {}", path, e.message()))?;
+    Ok(written.into_inner().authorization_model_id)
+}
+
+/// Read the pinned authorization model once and cache its identity
+///
+/// Returns `None` when no model is pinned or it cannot be read; failures are
+/// not cached so a later call retries.
+pub async fn model_info(ctx: &Ctx) -> Option<ModelInfo> {
+    let model_id = ctx.fga_config.authorization_model_id.clone()?;
+
+    let result = ctx
+        .model_info
+        .get_or_try_init(|| async {
+            let request = request(
+                ReadAuthorizationModelRequest {
+                    store_id: ctx.fga_config.store_id.clone(),
+                    id: model_id.clone(),
+                },
+                ctx.fga_config.timeouts.check,
+            );
+            let model = ctx
+                .fga_client()
+                .read_authorization_model(request)
+                .await?
+                .into_inner()
+                .authorization_model
+                .ok_or_else(|| tonic::Status::not_found("authorization model not found"))?;
+
+            tracing::info!(
+                "Authorization model {} uses schema version {}",
+                model.id,
+                model.schema_version
+            );
+            Ok::<_, tonic::Status>(ModelInfo {
+                id: model.id,
+                schema_version: model.schema_version,
+            })
+        })
+        .await;
+
+    match result {
+        Ok(info) => Some(info.clone()),
+        Err(e) => {
+            tracing::warn!("Failed to read authorization model {}: {}", model_id, e);
+            None
+        }
+    }
+}
+
+/// Add `X-FGA-Model-Version` and `X-FGA-Model-Id` to successful responses so
+/// clients can invalidate UI state derived from the model when it changes
+pub async fn model_version_middleware(
+    State(ctx): State<Arc<Ctx>>,
+    request: HttpRequest,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+
+    if response.status().is_success()
+        && let Some(info) = model_info(&ctx).await
+    {
+        let headers = response.headers_mut();
+        if let Ok(value) = HeaderValue::from_str(&info.schema_version) {
+            headers.insert("x-fga-model-version", value);
+        }
+        if let Ok(value) = HeaderValue::from_str(&info.id) {
+            headers.insert("x-fga-model-id", value);
+        }
+    }
+
+    response
+}
+
+/// Describe an `Expand` tree as ordered, human-readable steps, one per node,
+/// indented by depth
+pub fn describe_expansion(node: &Node, depth: usize, steps: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    let name = &node.name;
+
+    match &node.value {
+        Some(node::Value::Leaf(leaf)) => match &leaf.value {
+            Some(leaf::Value::Users(users)) if users.users.is_empty() => {
+                steps.push(format!("{}{} has no direct grants", indent, name));
+            }
+            Some(leaf::Value::Users(users)) => {
+                steps.push(format!(
+                    "{}{} is granted directly to {}",
+                    indent,
+                    name,
+                    users.users.join(", ")
+                ));
+            }
+            Some(leaf::Value::Computed(computed)) => {
+                steps.push(format!(
+                    "{}{} includes everyone in {}",
+                    indent, name, computed.userset
+                ));
+            }
+            Some(leaf::Value::TupleToUserset(ttu)) => {
+                let computed: Vec<&str> = ttu
+                    .computed
+                    .iter()
+                    .map(|computed| computed.userset.as_str())
+                    .collect();
+                steps.push(format!(
+                    "{}{} is inherited through {}: {}",
+                    indent,
+                    name,
+                    ttu.tupleset,
+                    if computed.is_empty() {
+                        "no related objects".to_string()
+                    } else {
+                        computed.join(", ")
+                    }
+                ));
+            }
+            None => steps.push(format!("{}{} is empty", indent, name)),
+        },
+        Some(node::Value::Union(nodes)) => {
+            steps.push(format!("{}{} is granted by any of:", indent, name));
+            for child in &nodes.nodes {
+                describe_expansion(child, depth + 1, steps);
+            }
+        }
+        Some(node::Value::Intersection(nodes)) => {
+            steps.push(format!("{}{} requires all of:", indent, name));
+            for child in &nodes.nodes {
+                describe_expansion(child, depth + 1, steps);
+            }
+        }
+        Some(node::Value::Difference(difference)) => {
+            steps.push(format!("{}{} is granted by:", indent, name));
+            if let Some(base) = &difference.base {
+                describe_expansion(base, depth + 1, steps);
+            }
+            steps.push(format!("{}except:", indent));
+            if let Some(subtract) = &difference.subtract {
+                describe_expansion(subtract, depth + 1, steps);
+            }
+        }
+        None => steps.push(format!("{}{} is empty", indent, name)),
+    }
+}
+
+/// OpenFGA client whose calls carry the configured API token
+pub type FgaClient = OpenFgaServiceClient<InterceptedService<Channel, ApiToken>>;
+
+/// Outcome of an OpenFGA call
+pub type FgaResult<T> = Result<tonic::Response<T>, tonic::Status>;
+
+/// The OpenFGA calls made while serving requests
+///
+/// Handlers reach OpenFGA only through this trait, so tests can run them
+/// against a double instead of a live server.
+#[async_trait]
+pub trait FgaApi: Send + Sync {
+    async fn check(&self, request: Request<CheckRequest>) -> FgaResult<CheckResponse>;
+    async fn batch_check(
+        &self,
+        request: Request<BatchCheckRequest>,
+    ) -> FgaResult<BatchCheckResponse>;
+    async fn expand(&self, request: Request<ExpandRequest>) -> FgaResult<ExpandResponse>;
+    async fn list_objects(
+        &self,
+        request: Request<ListObjectsRequest>,
+    ) -> FgaResult<ListObjectsResponse>;
+    async fn list_users(&self, request: Request<ListUsersRequest>) -> FgaResult<ListUsersResponse>;
+    async fn read(&self, request: Request<ReadRequest>) -> FgaResult<ReadResponse>;
+    async fn read_changes(
+        &self,
+        request: Request<ReadChangesRequest>,
+    ) -> FgaResult<ReadChangesResponse>;
+    async fn write(&self, request: Request<WriteRequest>) -> FgaResult<WriteResponse>;
+    async fn read_assertions(
+        &self,
+        request: Request<ReadAssertionsRequest>,
+    ) -> FgaResult<ReadAssertionsResponse>;
+    async fn write_assertions(
+        &self,
+        request: Request<WriteAssertionsRequest>,
+    ) -> FgaResult<WriteAssertionsResponse>;
+    async fn read_authorization_model(
+        &self,
+        request: Request<ReadAuthorizationModelRequest>,
+    ) -> FgaResult<ReadAuthorizationModelResponse>;
+    async fn read_authorization_models(
+        &self,
+        request: Request<ReadAuthorizationModelsRequest>,
+    ) -> FgaResult<ReadAuthorizationModelsResponse>;
+}
+
+/// The gRPC client, replaced by the health check when the connection breaks
+pub struct ReconnectingClient {
+    client: RwLock<FgaClient>,
+}
+
+impl ReconnectingClient {
+    pub fn new(client: FgaClient) -> Self {
+        Self {
+            client: RwLock::new(client),
+        }
+    }
+
+    /// Current client, cheap to clone
+    pub fn current(&self) -> FgaClient {
+        self.client.read().unwrap().clone()
+    }
+
+    /// Send later calls through a freshly connected client
+    pub fn replace(&self, client: FgaClient) {
+        *self.client.write().unwrap() = client;
+    }
+}
+
+#[async_trait]
+impl FgaApi for ReconnectingClient {
+    async fn check(&self, request: Request<CheckRequest>) -> FgaResult<CheckResponse> {
+        self.current().check(request).await
+    }
+
+    async fn batch_check(
+        &self,
+        request: Request<BatchCheckRequest>,
+    ) -> FgaResult<BatchCheckResponse> {
+        self.current().batch_check(request).await
+    }
+
+    async fn expand(&self, request: Request<ExpandRequest>) -> FgaResult<ExpandResponse> {
+        self.current().expand(request).await
+    }
+
+    async fn list_objects(
+        &self,
+        request: Request<ListObjectsRequest>,
+    ) -> FgaResult<ListObjectsResponse> {
+        self.current().list_objects(request).await
+    }
+
+    async fn list_users(&self, request: Request<ListUsersRequest>) -> FgaResult<ListUsersResponse> {
+        self.current().list_users(request).await
+    }
+
+    async fn read(&self, request: Request<ReadRequest>) -> FgaResult<ReadResponse> {
+        self.current().read(request).await
+    }
+
+    async fn read_changes(
+        &self,
+        request: Request<ReadChangesRequest>,
+    ) -> FgaResult<ReadChangesResponse> {
+        self.current().read_changes(request).await
+    }
+
+    async fn write(&self, request: Request<WriteRequest>) -> FgaResult<WriteResponse> {
+        self.current().write(request).await
+    }
+
+    async fn read_assertions(
+        &self,
+        request: Request<ReadAssertionsRequest>,
+    ) -> FgaResult<ReadAssertionsResponse> {
+        self.current().read_assertions(request).await
+    }
+
+    async fn write_assertions(
+        &self,
+        request: Request<WriteAssertionsRequest>,
+    ) -> FgaResult<WriteAssertionsResponse> {
+        self.current().write_assertions(request).await
+    }
+
+    async fn read_authorization_model(
+        &self,
+        request: Request<ReadAuthorizationModelRequest>,
+    ) -> FgaResult<ReadAuthorizationModelResponse> {
+        self.current().read_authorization_model(request).await
+    }
+
+    async fn read_authorization_models(
+        &self,
+        request: Request<ReadAuthorizationModelsRequest>,
+    ) -> FgaResult<ReadAuthorizationModelsResponse> {
+        self.current().read_authorization_models(request).await
+    }
+}
+
+/// Adds `authorization: Bearer <token>` to every OpenFGA call, along with the
+/// trace context of the calling span when trace export is enabled
+///
+/// Without a token or exporter requests are passed through unchanged.
+#[derive(Clone, Default)]
+pub struct ApiToken {
+    authorization: Option<MetadataValue<Ascii>>,
+}
+
+impl ApiToken {
+    /// Read `OPENFGA_API_TOKEN`
+    pub fn from_env() -> Result<Self, String> {
+        let Ok(token) = env::var("OPENFGA_API_TOKEN") else {
+            return Ok(Self::default());
+        };
+        let authorization = format!("Bearer {}", token.trim())
+            .parse()
+            .map_err(|_| "OPENFGA_API_TOKEN contains characters not allowed in a header")?;
+        Ok(Self {
+            authorization: Some(authorization),
+        })
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.authorization.is_some()
+    }
+}
+
+impl Interceptor for ApiToken {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, tonic::Status> {
+        if let Some(authorization) = &self.authorization {
+            request
+                .metadata_mut()
+                .insert("authorization", authorization.clone());
+        }
+        telemetry::inject_trace_context(request.metadata_mut());
+        Ok(request)
+    }
+}
+
+/// Wrap a message in a tonic request carrying the given timeout
+pub fn request<T>(message: T, timeout: Duration) -> Request<T> {
+    let mut request = Request::new(message);
+    request.set_timeout(timeout);
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Messages as returned by OpenFGA for write_failed_due_to_invalid_input
+    const EXISTS: &str = "cannot write a tuple which already exists: user: 'user:alice', relation: 'viewer', object: 'resource:report': tuple to be written already existed or the tuple to be deleted did not exist";
+    const MISSING: &str = "cannot delete a tuple which does not exist: user: 'user:alice', relation: 'viewer', object: 'resource:report': tuple to be written already existed or the tuple to be deleted did not exist";
+
+    #[test]
+    fn openfga_write_rejections_are_classified() {
+        for code in [tonic::Code::InvalidArgument, tonic::Code::Unknown] {
+            let exists = tonic::Status::new(code, EXISTS);
+            let missing = tonic::Status::new(code, MISSING);
+
+            assert!(is_tuple_exists(&exists));
+            assert!(!is_tuple_missing(&exists));
+            assert!(is_tuple_missing(&missing));
+            assert!(!is_tuple_exists(&missing));
+            assert!(is_already_applied(&exists) && is_already_applied(&missing));
+        }
+    }
+
+    #[test]
+    fn other_failures_are_not_already_applied() {
+        for status in [
+            tonic::Status::invalid_argument("type 'widget' does not exist in the model"),
+            tonic::Status::internal(EXISTS),
+            tonic::Status::unavailable("connection refused"),
+        ] {
+            assert!(!is_already_applied(&status), "{:?}", status);
+        }
+    }
+}
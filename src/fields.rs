@@ -0,0 +1,76 @@
+use axum::{
+    body::Body,
+    extract::{Query, Request, State},
+    http::{Method, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::context::Ctx;
+use crate::error::{ApiError, ErrorCode};
+
+/// Project successful JSON responses of GET requests to the top-level fields
+/// named in the `fields` query parameter (comma-separated)
+///
+/// Unknown field names are ignored, or rejected with 400 when `STRICT_FIELDS`
+/// is set. An empty `fields` value leaves the response untouched.
+pub async fn fields_middleware(
+    State(ctx): State<Arc<Ctx>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let fields = match requested_fields(&request) {
+        Some(fields) if request.method() == Method::GET => fields,
+        _ => return next.run(request).await,
+    };
+
+    let response = next.run(request).await;
+    if !response.status().is_success() {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to buffer response for field projection: {}", e);
+            return ApiError::new(ErrorCode::Internal, "Failed to read response").into_response();
+        }
+    };
+
+    let Ok(Value::Object(mut object)) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    if ctx.strict_fields
+        && let Some(unknown) = fields.iter().find(|field| !object.contains_key(*field))
+    {
+        return ApiError::validation(format!("Unknown field '{}'", unknown)).into_response();
+    }
+
+    object.retain(|key, _| fields.contains(key));
+    let body = Value::Object(object).to_string();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[derive(Deserialize)]
+struct FieldsParams {
+    fields: Option<String>,
+}
+
+/// Parse the `fields` query parameter, `None` when absent or empty
+fn requested_fields(request: &Request) -> Option<Vec<String>> {
+    let Query(params) = Query::<FieldsParams>::try_from_uri(request.uri()).ok()?;
+
+    let fields: Vec<String> = params
+        .fields?
+        .split(',')
+        .map(|field| field.trim().to_string())
+        .filter(|field| !field.is_empty())
+        .collect();
+    (!fields.is_empty()).then_some(fields)
+}
@@ -1,5 +1,27 @@
+pub mod audit;
 pub mod auth;
+pub mod body_log;
+pub mod cache;
+pub mod coalesce;
+pub mod concurrency;
+pub mod config;
 pub mod context;
 pub mod controller;
+pub mod cursor;
+pub mod error;
+pub mod extract;
+pub mod fga;
+pub mod fields;
 pub mod listener;
+pub mod maintenance;
+pub mod outbox;
+pub mod prometheus;
+pub mod rate_limit;
+pub mod readiness;
+pub mod request_id;
 pub mod routes;
+pub mod saga;
+pub mod schema;
+pub mod stats;
+pub mod store;
+pub mod telemetry;
@@ -1,12 +1,319 @@
-use axum::Router;
-use std::net::SocketAddr;
+use axum::{Extension, Router, extract::ConnectInfo};
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use hyper_util::server::conn::auto::Builder;
+use hyper_util::service::TowerToHyperService;
+use socket2::{SockRef, TcpKeepalive};
+use std::env;
+use std::future::IntoFuture;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
 use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+/// How long in-flight requests may take to finish once shutdown begins
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Connection-level server settings
+///
+/// When no connection setting is changed from its default the server is
+/// started via `axum::serve`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServerConfig {
+    /// Set `TCP_NODELAY` on accepted sockets
+    pub tcp_nodelay: bool,
+    /// Idle time before TCP keep-alive probes are sent on accepted sockets
+    pub tcp_keepalive: Option<Duration>,
+    /// Accept cleartext HTTP/2 alongside HTTP/1.1, detected per connection
+    pub http2: bool,
+    /// Maximum concurrent HTTP/2 streams per connection
+    pub http2_max_concurrent_streams: Option<u32>,
+    /// Interval between HTTP/2 keep-alive pings
+    pub keepalive_interval: Option<Duration>,
+    /// How long to wait for a keep-alive ping to be acknowledged
+    pub keepalive_timeout: Option<Duration>,
+    /// How long to let in-flight requests drain after SIGINT or SIGTERM
+    pub shutdown_timeout: Duration,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            tcp_nodelay: false,
+            tcp_keepalive: None,
+            http2: false,
+            http2_max_concurrent_streams: None,
+            keepalive_interval: None,
+            keepalive_timeout: None,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Read `SERVER_TCP_NODELAY`, `SERVER_TCP_KEEPALIVE_SECS`, `SERVER_HTTP2`,
+    /// `SERVER_HTTP2_MAX_STREAMS`, `SERVER_KEEPALIVE_INTERVAL_SECS`,
+    /// `SERVER_KEEPALIVE_TIMEOUT_SECS` and `SERVER_SHUTDOWN_TIMEOUT_SECS`
+    ///
+    /// HTTP/2 is enabled when `SERVER_HTTP2` is set, or when unset and one of
+    /// the HTTP/2 settings is given.
+    pub fn from_env() -> Result<Self, String> {
+        let flag = |value: String| value == "1" || value.eq_ignore_ascii_case("true");
+        let http2_max_concurrent_streams = positive_from_env("SERVER_HTTP2_MAX_STREAMS")?;
+        let keepalive_interval =
+            positive_from_env("SERVER_KEEPALIVE_INTERVAL_SECS")?.map(Duration::from_secs);
+        let http2_tuned = http2_max_concurrent_streams.is_some() || keepalive_interval.is_some();
+
+        let config = Self {
+            tcp_nodelay: env::var("SERVER_TCP_NODELAY").map(flag).unwrap_or(false),
+            tcp_keepalive: positive_from_env("SERVER_TCP_KEEPALIVE_SECS")?.map(Duration::from_secs),
+            http2: env::var("SERVER_HTTP2").map(flag).unwrap_or(http2_tuned),
+            http2_max_concurrent_streams,
+            keepalive_interval,
+            keepalive_timeout: positive_from_env("SERVER_KEEPALIVE_TIMEOUT_SECS")?
+                .map(Duration::from_secs),
+            shutdown_timeout: positive_from_env("SERVER_SHUTDOWN_TIMEOUT_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT),
+        };
+
+        if config.keepalive_timeout.is_some() && config.keepalive_interval.is_none() {
+            return Err(
+                "SERVER_KEEPALIVE_TIMEOUT_SECS requires SERVER_KEEPALIVE_INTERVAL_SECS".to_string(),
+            );
+        }
+        if http2_tuned && !config.http2 {
+            return Err(
+                "SERVER_HTTP2_MAX_STREAMS and SERVER_KEEPALIVE_INTERVAL_SECS need SERVER_HTTP2"
+                    .to_string(),
+            );
+        }
+
+        Ok(config)
+    }
+
+    /// Whether any connection setting differs from hyper's defaults
+    fn customizes_connections(&self) -> bool {
+        Self {
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            ..self.clone()
+        } != Self::default()
+    }
+}
+
+/// Read the address to listen on from `BIND_ADDR` (an IP) and `PORT`,
+/// defaulting to 127.0.0.1:5001
+pub fn bind_addr_from_env() -> Result<SocketAddr, String> {
+    let ip = match env::var("BIND_ADDR") {
+        Ok(value) => value
+            .parse::<IpAddr>()
+            .map_err(|_| format!("BIND_ADDR '{}' is not an IP address", value))?,
+        Err(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+    };
+    let port = match env::var("PORT") {
+        Ok(value) => value
+            .parse::<u16>()
+            .map_err(|_| format!("PORT '{}' is not a valid port number", value))?,
+        Err(_) => 5001,
+    };
+    Ok(SocketAddr::new(ip, port))
+}
+
+fn positive_from_env<T>(name: &str) -> Result<Option<T>, String>
+where
+    T: std::str::FromStr + PartialOrd + Default,
+{
+    match env::var(name) {
+        Ok(value) => match value.parse::<T>() {
+            Ok(parsed) if parsed > T::default() => Ok(Some(parsed)),
+            _ => Err(format!("{} must be a positive number", name)),
+        },
+        Err(_) => Ok(None),
+    }
+}
+
+/// Say which address could not be bound, with a hint for the usual causes
+fn bind_error(addr: SocketAddr, e: io::Error) -> io::Error {
+    let hint = match e.kind() {
+        io::ErrorKind::AddrNotAvailable => " (BIND_ADDR is not an address of this host)",
+        io::ErrorKind::PermissionDenied => " (ports below 1024 need elevated privileges)",
+        io::ErrorKind::AddrInUse => " (another process is listening on PORT)",
+        _ => "",
+    };
+    io::Error::new(
+        e.kind(),
+        format!("cannot listen on {}: {}{}", addr, e, hint),
+    )
+}
+
+/// Resolve once SIGINT or SIGTERM is received
+async fn shutdown_signal() {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for SIGINT: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    let signal = tokio::select! {
+        _ = interrupt => "SIGINT",
+        _ = terminate => "SIGTERM",
+    };
+    tracing::info!("Received {}, shutting down gracefully", signal);
+}
+
+/// Resolve once shutdown has been requested
+async fn shutdown_requested(mut shutdown: watch::Receiver<bool>) {
+    // An error means the sender is gone, which only happens after it fired
+    let _ = shutdown.wait_for(|stopping| *stopping).await;
+}
+
+/// Run the server until it exits, giving it at most `timeout` to drain
+/// in-flight requests once shutdown has been requested
+async fn drain(
+    server: impl Future<Output = Result<(), io::Error>>,
+    shutdown: watch::Receiver<bool>,
+    timeout: Duration,
+) -> Result<(), io::Error> {
+    tokio::pin!(server);
+    tokio::select! {
+        result = &mut server => return result,
+        _ = shutdown_requested(shutdown) => {}
+    }
+
+    tracing::info!(
+        "Waiting up to {}s for in-flight requests to finish",
+        timeout.as_secs()
+    );
+    match tokio::time::timeout(timeout, server).await {
+        Ok(result) => {
+            tracing::info!("Shutdown complete, all requests drained");
+            result
+        }
+        Err(_) => {
+            tracing::warn!(
+                "Shutdown timeout of {}s elapsed, dropping remaining connections",
+                timeout.as_secs()
+            );
+            Ok(())
+        }
+    }
+}
 
 /// Starts the HTTP server with the given router
-pub async fn serve(app: Router, addr: SocketAddr) -> Result<(), std::io::Error> {
-    tracing::info!("Listener starting on {}", addr);
+///
+/// On SIGINT or SIGTERM the server stops accepting connections and lets
+/// in-flight requests finish, for at most the configured shutdown timeout.
+pub async fn serve(app: Router, addr: SocketAddr, config: ServerConfig) -> Result<(), io::Error> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| bind_error(addr, e))?;
+    tracing::info!("Server listening on {}", addr);
+
+    let (shutdown_tx, shutdown) = watch::channel(false);
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        let _ = shutdown_tx.send(true);
+    });
+
+    if !config.customizes_connections() {
+        let server = axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(shutdown_requested(shutdown.clone()))
+        .into_future();
+        return drain(server, shutdown, config.shutdown_timeout).await;
+    }
+
+    tracing::info!("Serving with custom connection settings: {:?}", config);
+    let mut builder = Builder::new(TokioExecutor::new());
+    if !config.http2 {
+        builder = builder.http1_only();
+    }
+    {
+        let mut http2 = builder.http2();
+        http2.timer(TokioTimer::new());
+        if let Some(streams) = config.http2_max_concurrent_streams {
+            http2.max_concurrent_streams(streams);
+        }
+        if let Some(interval) = config.keepalive_interval {
+            http2.keep_alive_interval(interval);
+        }
+        if let Some(timeout) = config.keepalive_timeout {
+            http2.keep_alive_timeout(timeout);
+        }
+    }
+
+    let mut connections = JoinSet::new();
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            // Reap finished connections so the set does not grow without bound
+            Some(_) = connections.join_next(), if !connections.is_empty() => continue,
+            _ = shutdown_requested(shutdown.clone()) => break,
+        };
+        let (stream, remote_addr) = match accepted {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                tracing::warn!("Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = stream.set_nodelay(config.tcp_nodelay) {
+            tracing::warn!("Failed to set TCP_NODELAY for {}: {}", remote_addr, e);
+        }
+        if let Some(idle) = config.tcp_keepalive {
+            let keepalive = TcpKeepalive::new().with_time(idle);
+            if let Err(e) = SockRef::from(&stream).set_tcp_keepalive(&keepalive) {
+                tracing::warn!("Failed to set TCP keep-alive for {}: {}", remote_addr, e);
+            }
+        }
+
+        let builder = builder.clone();
+        // Expose the peer address like `into_make_service_with_connect_info`
+        let service =
+            TowerToHyperService::new(app.clone().layer(Extension(ConnectInfo(remote_addr))));
+        let shutdown = shutdown.clone();
+        connections.spawn(async move {
+            let connection = builder.serve_connection_with_upgrades(TokioIo::new(stream), service);
+            tokio::pin!(connection);
+            let result = tokio::select! {
+                result = connection.as_mut() => result,
+                _ = shutdown_requested(shutdown) => {
+                    // Finish the in-flight request, then close the connection
+                    connection.as_mut().graceful_shutdown();
+                    connection.await
+                }
+            };
+            if let Err(e) = result {
+                tracing::debug!("Connection from {} closed with error: {}", remote_addr, e);
+            }
+        });
+    }
 
-    let listener = TcpListener::bind(addr).await.unwrap();
-    tracing::info!("Server started successfully");
-    axum::serve(listener, app.into_make_service()).await
+    // Stop accepting before waiting on the open connections
+    drop(listener);
+    let open = async move {
+        while connections.join_next().await.is_some() {}
+        Ok(())
+    };
+    drain(open, shutdown, config.shutdown_timeout).await
 }
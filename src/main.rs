@@ -1,18 +1,29 @@
 use openfga_demo::context::Ctx;
 use openfga_demo::listener;
 use openfga_demo::routes;
-use std::net::SocketAddr;
+use openfga_demo::telemetry;
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
 async fn main() {
+    // Initialize the OTLP exporter, if configured
+    let (otlp_layer, telemetry) = match telemetry::otlp_layer() {
+        Ok(Some((layer, telemetry))) => (Some(layer), Some(telemetry)),
+        Ok(None) => (None, None),
+        Err(e) => {
+            eprintln!("Failed to initialize OpenTelemetry exporter: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     // Initialize tracing
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(
             std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
         ))
         .with(tracing_subscriber::fmt::layer())
+        .with(otlp_layer)
         .init();
 
     // Initialize the application context
@@ -27,9 +38,36 @@ async fn main() {
     // Initialize the application
     let app = routes::create_routes(ctx).layer(TraceLayer::new_for_http());
 
+    // Read connection-level server settings
+    let server_config = match listener::ServerConfig::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("Invalid server configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Read the address to listen on
+    let addr = match listener::bind_addr_from_env() {
+        Ok(addr) => addr,
+        Err(e) => {
+            tracing::error!("Invalid bind address: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     // Start the server
-    let addr = SocketAddr::from(([127, 0, 0, 1], 5001));
-    tracing::info!("Server listening on {}", addr);
+    let result = listener::serve(app, addr, server_config).await;
+    if let Err(e) = &result {
+        tracing::error!("Server failed: {}", e);
+    }
+
+    // Flush any spans still buffered in the exporter
+    if let Some(telemetry) = telemetry {
+        telemetry.shutdown();
+    }
 
-    listener::serve(app, addr).await.unwrap();
+    if result.is_err() {
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,82 @@
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use crate::context::Ctx;
+use crate::error::{ApiError, ErrorCode};
+
+/// A scheduled period during which mutating requests are rejected
+#[derive(Clone, Debug)]
+pub struct ReadOnlyWindow {
+    pub start: OffsetDateTime,
+    pub end: OffsetDateTime,
+}
+
+impl ReadOnlyWindow {
+    /// Whether `now` falls inside the window (start inclusive, end exclusive)
+    pub fn contains(&self, now: OffsetDateTime) -> bool {
+        self.start <= now && now < self.end
+    }
+}
+
+/// Parse comma-separated `start/end` RFC 3339 intervals, e.g.
+/// `2026-01-10T02:00:00+01:00/2026-01-10T04:00:00+01:00`. The offset on each
+/// timestamp carries the window's timezone.
+pub fn parse_read_only_windows(value: &str) -> Result<Vec<ReadOnlyWindow>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (start, end) = entry
+                .split_once('/')
+                .ok_or_else(|| format!("window '{}' must be start/end", entry))?;
+            let start = OffsetDateTime::parse(start.trim(), &Rfc3339)
+                .map_err(|e| format!("invalid window start '{}': {}", start, e))?;
+            let end = OffsetDateTime::parse(end.trim(), &Rfc3339)
+                .map_err(|e| format!("invalid window end '{}': {}", end, e))?;
+            if end <= start {
+                return Err(format!("window '{}' ends before it starts", entry));
+            }
+            Ok(ReadOnlyWindow { start, end })
+        })
+        .collect()
+}
+
+/// Reject mutating requests with 503 while a scheduled read-only window is active
+pub async fn read_only_window_middleware(
+    State(ctx): State<Arc<Ctx>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let is_mutation = matches!(
+        *request.method(),
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    );
+
+    if is_mutation {
+        let now = OffsetDateTime::now_utc();
+        if let Some(window) = ctx.read_only_windows.iter().find(|w| w.contains(now)) {
+            let retry_after = (window.end - now).whole_seconds().max(1) as u64;
+            tracing::warn!(
+                "Rejecting {} {} during read-only window ending {}",
+                request.method(),
+                request.uri().path(),
+                window.end
+            );
+            return Err(ApiError::new(
+                ErrorCode::ReadOnly,
+                "The service is in a scheduled read-only window",
+            )
+            .with_retry_after(retry_after));
+        }
+    }
+
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+use crate::store::{ResourceStore, StoreError};
+
+/// Events forwarded per relay pass
+pub const RELAY_BATCH_SIZE: i64 = 100;
+
+/// Forward one batch of undelivered outbox events and mark them delivered,
+/// returning how many were forwarded
+///
+/// Events are marked only after they were forwarded, so a crash in between
+/// sends them again: delivery is at least once.
+pub async fn relay_once(store: &dyn ResourceStore) -> Result<usize, StoreError> {
+    let events = store.pending_events(RELAY_BATCH_SIZE).await?;
+    for event in &events {
+        tracing::info!(
+            target: "outbox",
+            id = event.id,
+            event_type = %event.event_type,
+            resource_id = ?event.resource_id,
+            payload = %event.payload,
+            "Outbox event"
+        );
+    }
+
+    let ids: Vec<i64> = events.iter().map(|event| event.id).collect();
+    store.mark_delivered(&ids).await?;
+    Ok(ids.len())
+}
+
+/// Relay outbox events every `interval` until the process exits, draining the
+/// backlog in batches on each tick
+pub fn spawn_relay(store: Arc<dyn ResourceStore>, interval: Duration) {
+    if interval.is_zero() {
+        tracing::info!("Outbox relay disabled");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            loop {
+                match relay_once(store.as_ref()).await {
+                    Ok(forwarded) if forwarded as i64 == RELAY_BATCH_SIZE => continue,
+                    Ok(_) => break,
+                    Err(e) => {
+                        tracing::warn!("Failed to relay outbox events: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
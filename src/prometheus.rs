@@ -0,0 +1,74 @@
+use axum::{extract::State, http::header, response::IntoResponse};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::context::Ctx;
+
+/// Install the global Prometheus recorder
+///
+/// Must only be called once per process.
+pub fn install() -> Result<PrometheusHandle, String> {
+    PrometheusBuilder::new()
+        .install_recorder()
+        .map_err(|e| format!("Failed to install Prometheus recorder: {}", e))
+}
+
+/// Type part of an FGA object ID such as `resource:svc/type/org/name`
+fn object_type(object: &str) -> String {
+    object
+        .split_once(':')
+        .map_or(object, |(object_type, _)| object_type)
+        .to_string()
+}
+
+fn outcome<T, E>(result: &Result<T, E>) -> &'static str {
+    if result.is_ok() { "ok" } else { "error" }
+}
+
+/// Record an OpenFGA check and, when it succeeded, whether it was allowed
+pub fn record_check<E>(relation: &str, object: &str, elapsed: Duration, result: &Result<bool, E>) {
+    let decision = match result {
+        Ok(true) => "allowed",
+        Ok(false) => "denied",
+        Err(_) => "none",
+    };
+    let labels = [
+        ("relation", relation.to_string()),
+        ("object_type", object_type(object)),
+        ("outcome", outcome(result).to_string()),
+    ];
+    metrics::counter!("fga_checks_total", &labels).increment(1);
+    metrics::counter!(
+        "fga_check_decisions_total",
+        "relation" => relation.to_string(),
+        "object_type" => object_type(object),
+        "decision" => decision
+    )
+    .increment(1);
+    metrics::histogram!("fga_check_duration_seconds", &labels).record(elapsed.as_secs_f64());
+}
+
+/// Record an OpenFGA ListObjects call
+pub fn record_list_objects<T, E>(
+    relation: &str,
+    object_type: &str,
+    elapsed: Duration,
+    result: &Result<T, E>,
+) {
+    let labels = [
+        ("relation", relation.to_string()),
+        ("object_type", object_type.to_string()),
+        ("outcome", outcome(result).to_string()),
+    ];
+    metrics::counter!("fga_list_objects_total", &labels).increment(1);
+    metrics::histogram!("fga_list_objects_duration_seconds", &labels).record(elapsed.as_secs_f64());
+}
+
+/// Prometheus scrape endpoint
+pub async fn metrics_handler(State(ctx): State<Arc<Ctx>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        ctx.prometheus.render(),
+    )
+}
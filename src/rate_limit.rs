@@ -0,0 +1,156 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::collections::HashMap;
+use std::env;
+use std::hash::Hash;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::auth::AuthUser;
+use crate::context::Ctx;
+use crate::error::{ApiError, ErrorCode};
+
+/// Buckets tracked before idle ones are dropped
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token buckets per key, refilled continuously at a fixed rate
+pub struct RateLimiter<K> {
+    per_second: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<K, Bucket>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    pub fn new(per_second: f64, burst: f64) -> Self {
+        Self {
+            per_second,
+            burst,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Requests allowed per second once the burst is spent
+    pub fn per_second(&self) -> f64 {
+        self.per_second
+    }
+
+    /// Take a token for the key, or return how long until one is available
+    pub fn acquire(&self, key: K) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        // Drop buckets that have refilled completely, they behave like new ones
+        if buckets.len() >= MAX_TRACKED_BUCKETS {
+            let full_after = self.burst / self.per_second;
+            buckets
+                .retain(|_, bucket| now.duration_since(bucket.updated).as_secs_f64() < full_after);
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let refill = now.duration_since(bucket.updated).as_secs_f64() * self.per_second;
+        bucket.tokens = (bucket.tokens + refill).min(self.burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.per_second,
+            ))
+        }
+    }
+}
+
+/// Request rate limits for authenticated users and for anonymous callers
+pub struct RateLimits {
+    /// Buckets per authenticated user ID
+    pub users: RateLimiter<String>,
+    /// Buckets per client IP for callers without an identity
+    pub anonymous: RateLimiter<IpAddr>,
+}
+
+fn rate_from_env(name: &str) -> Result<Option<f64>, String> {
+    match env::var(name) {
+        Ok(value) => match value.trim().parse::<f64>() {
+            Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(Some(rate)),
+            _ => Err(format!("{} must be a positive number", name)),
+        },
+        Err(_) => Ok(None),
+    }
+}
+
+impl RateLimits {
+    /// Read `RATE_LIMIT_PER_SEC`, `RATE_LIMIT_BURST` and
+    /// `RATE_LIMIT_ANONYMOUS_PER_SEC`
+    ///
+    /// Returns `None` when `RATE_LIMIT_PER_SEC` is unset. The burst defaults
+    /// to one second's worth of requests, the anonymous rate to the user rate.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let Some(per_second) = rate_from_env("RATE_LIMIT_PER_SEC")? else {
+            return Ok(None);
+        };
+        let anonymous_per_second =
+            rate_from_env("RATE_LIMIT_ANONYMOUS_PER_SEC")?.unwrap_or(per_second);
+        let burst = rate_from_env("RATE_LIMIT_BURST")?;
+        let burst_for = |rate: f64| burst.unwrap_or(rate.ceil()).max(1.0);
+
+        Ok(Some(Self {
+            users: RateLimiter::new(per_second, burst_for(per_second)),
+            anonymous: RateLimiter::new(anonymous_per_second, burst_for(anonymous_per_second)),
+        }))
+    }
+}
+
+/// Reject callers that exceed their request rate with 429
+///
+/// Runs after authentication: identified users are limited per user ID,
+/// anonymous callers per client IP in a separate set of buckets.
+pub async fn rate_limit_middleware(
+    State(ctx): State<Arc<Ctx>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let Some(limits) = &ctx.rate_limits else {
+        return Ok(next.run(request).await);
+    };
+
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .filter(|user| !user.is_anonymous());
+    let result = match user {
+        Some(user) => limits.users.acquire(user.user_id.clone()),
+        None => match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+            Some(ConnectInfo(addr)) => limits.anonymous.acquire(addr.ip()),
+            None => Ok(()),
+        },
+    };
+
+    if let Err(wait) = result {
+        tracing::warn!(
+            "Rate limit exceeded for {} on {} {}",
+            user.map_or("anonymous caller", |user| user.user_id.as_str()),
+            request.method(),
+            request.uri().path()
+        );
+        return Err(
+            ApiError::new(ErrorCode::RateLimited, "Too many requests, slow down")
+                .with_retry_after(wait.as_secs_f64().ceil() as u64),
+        );
+    }
+
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,90 @@
+use axum::{Json, extract::State, http::StatusCode};
+use openfga_client::client::ReadAuthorizationModelsRequest;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::context::Ctx;
+use crate::fga;
+
+/// Dependencies probed by the readiness check
+pub const DEPENDENCIES: [&str; 2] = ["db", "fga"];
+
+/// Outcome of probing a single dependency
+#[derive(Debug, Serialize)]
+pub struct DependencyStatus {
+    pub required: bool,
+    pub up: bool,
+    pub error: Option<String>,
+}
+
+async fn probe_db(ctx: &Ctx) -> Result<(), String> {
+    sqlx::query("SELECT 1")
+        .execute(&ctx.db)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+async fn probe_fga(ctx: &Ctx) -> Result<(), String> {
+    if ctx.fga_config.store_id.is_empty() {
+        return Err("OpenFGA store ID not configured".to_string());
+    }
+
+    let request = fga::request(
+        ReadAuthorizationModelsRequest {
+            store_id: ctx.fga_config.store_id.clone(),
+            ..Default::default()
+        },
+        ctx.fga_config.timeouts.check,
+    );
+    ctx.fga_client()
+        .read_authorization_models(request)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.message().to_string())
+}
+
+/// Readiness endpoint
+///
+/// Responds 503 when a required dependency is down. Dependencies listed in
+/// `READINESS_OPTIONAL` only downgrade the status to `degraded`, still 200.
+pub async fn readiness_check(State(ctx): State<Arc<Ctx>>) -> (StatusCode, Json<Value>) {
+    let (db, fga) = tokio::join!(probe_db(&ctx), probe_fga(&ctx));
+
+    let mut dependencies = BTreeMap::new();
+    for (name, result) in [("db", db), ("fga", fga)] {
+        let required = !ctx
+            .readiness_optional
+            .iter()
+            .any(|optional| optional == name);
+        if let Err(e) = &result {
+            tracing::warn!("Readiness dependency {} is down: {}", name, e);
+        }
+        dependencies.insert(
+            name,
+            DependencyStatus {
+                required,
+                up: result.is_ok(),
+                error: result.err(),
+            },
+        );
+    }
+
+    let (status_code, status) = if dependencies.values().any(|dep| dep.required && !dep.up) {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    } else if dependencies.values().any(|dep| !dep.up) {
+        (StatusCode::OK, "degraded")
+    } else {
+        (StatusCode::OK, "ready")
+    };
+
+    (
+        status_code,
+        Json(json!({
+            "status": status,
+            "dependencies": dependencies
+        })),
+    )
+}
@@ -1,51 +1,328 @@
+use crate::audit;
 use crate::auth;
+use crate::body_log;
+use crate::concurrency;
 use crate::context::Ctx;
 use crate::controller;
+use crate::error::{self, ApiError, ErrorCode};
+use crate::fga;
+use crate::fields;
+use crate::maintenance;
+use crate::prometheus;
+use crate::rate_limit;
+use crate::readiness;
+use crate::request_id;
+use crate::stats;
 use axum::{
     Json, Router,
-    http::StatusCode,
+    extract::{DefaultBodyLimit, State},
+    http::{HeaderName, Method, StatusCode, Uri, header},
     middleware,
     routing::{get, post},
 };
 use serde_json::{Value, json};
 use std::sync::Arc;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::timeout::TimeoutLayer;
 
 /// Create all routes for the application
 pub fn create_routes<S: Send + Sync>(ctx: Arc<Ctx>) -> Router<S> {
     // Create protected routes that require authentication
-    let protected_routes = Router::new()
+    let mut protected_routes = Router::new()
         .route(
             "/api/resource/{service_name}/{service_type}/{org_id}/{name}",
             post(controller::create_resource)
                 .put(controller::update_resource)
-                .get(controller::get_resource)
                 .delete(controller::delete_resource),
         )
+        .route(
+            "/api/resource/{service_name}/{service_type}/{org_id}/{name}/share",
+            post(controller::share_resource_with_users),
+        )
+        .route(
+            "/api/resource/{service_name}/{service_type}/{org_id}/{name}/users",
+            get(controller::list_resource_users),
+        )
+        .route("/api/shares", post(controller::share_resource))
+        .route(
+            "/api/permissions",
+            post(controller::grant_permission).delete(controller::revoke_permission),
+        )
+        .route("/api/check", post(controller::can))
+        .route(
+            "/api/permissions/batch-check",
+            post(controller::batch_check),
+        )
         .route("/api/list-objects", get(controller::list_objects))
+        .route("/api/resources", get(controller::list_org_resources))
+        .route(
+            "/api/resources/batch",
+            post(controller::create_resources_batch),
+        )
+        .route(
+            "/api/resources/by-id/{id}",
+            get(controller::get_resource_by_id),
+        )
+        .route(
+            "/api/resources/by-id/{id}/can/{action}",
+            get(controller::can_by_resource_id),
+        )
+        .route(
+            "/api/resources/batch-get",
+            post(controller::batch_get_resources),
+        )
+        .route(
+            "/api/resources/bulk-delete",
+            post(controller::bulk_delete_resources),
+        )
+        .route(
+            "/api/resources/with-source",
+            get(controller::list_objects_with_source),
+        )
+        .route(
+            "/api/resources/summary",
+            get(controller::get_access_summary),
+        )
+        .route("/api/access/all", get(controller::get_all_access))
         .route(
             "/api/shared-resources",
             get(controller::get_shared_resources),
         )
+        .route(
+            "/api/shared-resources/outgoing",
+            get(controller::get_outgoing_shares),
+        )
+        .route(
+            "/api/organizations/{org_id}/my-role",
+            get(controller::get_my_org_role),
+        )
+        .route("/api/expand", get(controller::expand_relation))
+        .route(
+            "/api/admin/users/{user_id}/objects",
+            get(controller::admin_list_user_objects),
+        )
+        .route(
+            "/api/admin/compare-access",
+            post(controller::compare_access),
+        )
+        .route(
+            "/api/admin/shared-cache/invalidate/{user_id}",
+            post(controller::invalidate_shared_cache),
+        )
+        .route("/api/admin/diagnose", get(controller::diagnose))
+        .route(
+            "/api/admin/organizations/{org_id}/reparent",
+            post(controller::reparent_organization),
+        )
+        .route("/api/admin/changes", get(controller::get_changes))
+        .route(
+            "/api/admin/operations/{operation_id}",
+            get(controller::get_operation),
+        )
+        .route(
+            "/api/admin/assertions",
+            get(controller::get_assertions).put(controller::put_assertions),
+        )
+        .route(
+            "/api/admin/assertions/run",
+            post(controller::run_assertions),
+        )
+        .route("/api/admin/stats", get(controller::get_stats))
+        .route("/api/admin/stats/reset", post(controller::reset_stats));
+
+    // Create public routes that don't require authentication
+    let mut public_routes = Router::new()
+        .route("/health", get(health_check))
+        .route("/version", get(version))
+        .route("/ready", get(readiness::readiness_check))
+        .route("/metrics", get(prometheus::metrics_handler))
+        .route("/", get(root));
+
+    // The capability manifest is authenticated unless configured as public
+    if ctx.public_capabilities {
+        public_routes = public_routes.route("/api/capabilities", get(controller::get_capabilities));
+    } else {
+        protected_routes =
+            protected_routes.route("/api/capabilities", get(controller::get_capabilities));
+    }
+
+    // Model walkthrough is a teaching aid, only served in the dev profile
+    if ctx.profile == "dev" {
+        protected_routes =
+            protected_routes.route("/api/dev/walkthrough", get(controller::get_walkthrough));
+    }
+
+    // Type discovery is authenticated unless configured as public
+    let type_relations_path = "/api/types/{object_type}/relations";
+    if ctx.public_type_relations {
+        public_routes =
+            public_routes.route(type_relations_path, get(controller::get_type_relations));
+    } else {
+        protected_routes =
+            protected_routes.route(type_relations_path, get(controller::get_type_relations));
+    }
+
+    let protected_routes = protected_routes
+        .route_layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            fga::model_version_middleware,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            audit::action_reason_middleware,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            rate_limit::rate_limit_middleware,
+        ))
         .route_layer(middleware::from_fn_with_state(
             ctx.clone(),
             auth::auth_middleware,
         ));
 
-    // Create public routes that don't require authentication
-    let public_routes = Router::new()
-        .route("/health", get(health_check))
-        .route("/", get(root));
+    // Public reads: callers without an identity are checked as ANONYMOUS_USER
+    let optional_auth_routes = Router::new()
+        .route(
+            "/api/resource/{service_name}/{service_type}/{org_id}/{name}",
+            get(controller::get_resource),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            fga::model_version_middleware,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            rate_limit::rate_limit_middleware,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            auth::optional_auth_middleware,
+        ));
 
-    // Merge all routes
-    public_routes.merge(protected_routes).with_state(ctx)
+    // Merge all routes; the time and body limits sit innermost so the
+    // resulting 408 and 413 responses get the standard error body and are
+    // counted and logged like any other
+    let mut app = public_routes
+        .merge(protected_routes)
+        .merge(optional_auth_routes)
+        .fallback(route_not_found)
+        .method_not_allowed_fallback(method_not_allowed)
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(ctx.request_body_limit))
+        .layer(TimeoutLayer::new(ctx.request_timeout))
+        .layer(middleware::map_response_with_state(
+            ctx.clone(),
+            error::limit_rejection_body,
+        ))
+        .layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            concurrency::route_concurrency_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            maintenance::read_only_window_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            fields::fields_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            error::retry_after_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            stats::stats_middleware,
+        ));
+
+    // Log bodies outermost so rejections from other layers are captured too
+    if ctx.body_logging.is_some() {
+        app = app.layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            body_log::body_log_middleware,
+        ));
+    }
+
+    // Assign the request ID outside the other layers so their logs and errors carry it
+    app = app.layer(middleware::from_fn(request_id::request_id_middleware));
+
+    // CORS goes outside everything so preflight requests are answered before
+    // authentication or any other middleware sees them
+    if let Some(origins) = &ctx.cors_allowed_origins {
+        app = app.layer(cors_layer(origins));
+    }
+
+    app.with_state(ctx)
 }
 
-/// Health check endpoint
+/// Allow browsers on the given origins to call the API with our auth headers
+fn cors_layer(origins: &[String]) -> CorsLayer {
+    let allow_origin = if origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            origins
+                .iter()
+                .map(|origin| origin.parse().expect("origins are validated at startup")),
+        )
+    };
+
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            HeaderName::from_static("x-user-id"),
+            HeaderName::from_static("x-action-reason"),
+            header::IF_MATCH,
+            HeaderName::from_static(request_id::REQUEST_ID_HEADER),
+        ])
+        .expose_headers([
+            header::ETAG,
+            HeaderName::from_static(request_id::REQUEST_ID_HEADER),
+        ])
+}
+
+/// Fallback for unknown routes, so clients always get the JSON error shape
+async fn route_not_found(method: Method, uri: Uri) -> ApiError {
+    ApiError::not_found(format!("No route for {} {}", method, uri.path()))
+}
+
+/// Fallback for known routes called with an unsupported method
+async fn method_not_allowed(method: Method, uri: Uri) -> ApiError {
+    ApiError::new(
+        ErrorCode::MethodNotAllowed,
+        format!("{} is not allowed on {}", method, uri.path()),
+    )
+}
+
+/// Liveness endpoint, healthy while the process serves requests
+///
+/// Deliberately probes no dependencies; `/ready` reports whether the database
+/// and OpenFGA are reachable.
 async fn health_check() -> (StatusCode, Json<Value>) {
     tracing::info!("Health check endpoint called");
     (StatusCode::OK, Json(json!({ "status": "healthy" })))
 }
 
+/// Build and configuration identity, to confirm what a running instance uses
+///
+/// Only identifiers are reported, never credentials.
+async fn version(State(ctx): State<Arc<Ctx>>) -> (StatusCode, Json<Value>) {
+    let store_id = &ctx.fga_config.store_id;
+    (
+        StatusCode::OK,
+        Json(json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "profile": ctx.profile,
+            "store_id": (!store_id.is_empty()).then_some(store_id),
+            "authorization_model_id": ctx.fga_config.authorization_model_id
+        })),
+    )
+}
+
 /// Root endpoint
 async fn root() -> (StatusCode, Json<Value>) {
     tracing::info!("Root endpoint called");
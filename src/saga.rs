@@ -0,0 +1,80 @@
+use serde_json::json;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::error::{ApiError, ErrorCode};
+
+/// Undo of a completed step, resolving to an error message when it fails
+type Compensation = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+
+/// A multi-step mutation that undoes its completed steps when a later one fails
+///
+/// Register the compensation of each step once the step has succeeded; on a
+/// failure `fail` runs them newest first, so e.g. an owner tuple is deleted
+/// before the row it points at.
+pub struct Saga {
+    name: &'static str,
+    compensations: Vec<(&'static str, Compensation)>,
+}
+
+impl Saga {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            compensations: Vec::new(),
+        }
+    }
+
+    /// Record how to undo `step`, which has just completed
+    pub fn completed<F>(&mut self, step: &'static str, compensation: F)
+    where
+        F: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.compensations.push((step, Box::pin(compensation)));
+    }
+
+    /// Run the compensations in reverse order, returning whether all succeeded
+    ///
+    /// A failed compensation is logged and the remaining ones still run.
+    pub async fn compensate(self) -> bool {
+        let mut compensated = true;
+        for (step, compensation) in self.compensations.into_iter().rev() {
+            match compensation.await {
+                Ok(()) => tracing::info!("{}: undid step '{}'", self.name, step),
+                Err(e) => {
+                    tracing::error!("{}: failed to undo step '{}': {}", self.name, step, e);
+                    compensated = false;
+                }
+            }
+        }
+        compensated
+    }
+
+    /// Undo the completed steps after `step` failed with `error`
+    ///
+    /// With nothing to undo the error is returned as is. Otherwise the result
+    /// is a 500 whose `details` name the failed step, carry the original
+    /// error code and say whether every compensation succeeded.
+    pub async fn fail(self, step: &'static str, error: ApiError) -> ApiError {
+        if self.compensations.is_empty() {
+            return error;
+        }
+
+        let name = self.name;
+        tracing::error!("{}: step '{}' failed: {}", name, step, error.message);
+        let compensated = self.compensate().await;
+        let message = if compensated {
+            format!("{} failed and was rolled back: {}", name, error.message)
+        } else {
+            format!(
+                "{} failed and could not be fully rolled back: {}",
+                name, error.message
+            )
+        };
+        ApiError::new(ErrorCode::Internal, message).with_details(json!({
+            "compensated": compensated,
+            "failed_step": step,
+            "cause": error.code,
+        }))
+    }
+}
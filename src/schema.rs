@@ -0,0 +1,100 @@
+use jsonschema::Validator;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// Directory searched when `RESOURCE_SCHEMA_DIR` is unset
+const DEFAULT_SCHEMA_DIR: &str = "config/schemas";
+
+/// Violations reported per rejected payload, the rest are dropped
+const MAX_REPORTED_VIOLATIONS: usize = 20;
+
+/// One way a payload fails its schema
+#[derive(Debug, Serialize)]
+pub struct SchemaViolation {
+    /// JSON pointer to the offending value within `properties`
+    pub path: String,
+    pub message: String,
+}
+
+/// JSON Schemas for resource properties, one per service type
+#[derive(Default)]
+pub struct ResourceSchemas {
+    schemas: HashMap<String, Validator>,
+}
+
+impl ResourceSchemas {
+    /// Compile every `{service_type}.json` in `RESOURCE_SCHEMA_DIR`
+    /// (default `config/schemas`)
+    ///
+    /// A missing directory means no service type is validated.
+    pub fn from_env() -> Result<Self, String> {
+        let dir = PathBuf::from(
+            env::var("RESOURCE_SCHEMA_DIR").unwrap_or_else(|_| DEFAULT_SCHEMA_DIR.to_string()),
+        );
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Cannot read {}: {}", dir.display(), e)),
+        };
+
+        let mut schemas = HashMap::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| format!("Cannot read {}: {}", dir.display(), e))?
+                .path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let Some(service_type) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let contents = fs::read_to_string(&path)
+                .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+            let schema: Value = serde_json::from_str(&contents)
+                .map_err(|e| format!("Invalid JSON in {}: {}", path.display(), e))?;
+            let validator = jsonschema::validator_for(&schema)
+                .map_err(|e| format!("Invalid JSON Schema in {}: {}", path.display(), e))?;
+            schemas.insert(service_type.to_string(), validator);
+        }
+        Ok(Self { schemas })
+    }
+
+    /// Service types that have a schema, sorted
+    pub fn service_types(&self) -> Vec<&str> {
+        let mut service_types: Vec<&str> = self.schemas.keys().map(String::as_str).collect();
+        service_types.sort_unstable();
+        service_types
+    }
+
+    /// Check properties against the service type's schema
+    ///
+    /// Service types without a schema accept any properties.
+    pub fn validate(
+        &self,
+        service_type: &str,
+        properties: &Value,
+    ) -> Result<(), Vec<SchemaViolation>> {
+        let Some(validator) = self.schemas.get(service_type) else {
+            return Ok(());
+        };
+        let violations: Vec<SchemaViolation> = validator
+            .iter_errors(properties)
+            .take(MAX_REPORTED_VIOLATIONS)
+            .map(|error| SchemaViolation {
+                path: error.instance_path.to_string(),
+                message: error.to_string(),
+            })
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::context::Ctx;
+
+/// Upper bound on samples kept per window so bursts cannot grow memory
+const MAX_SAMPLES: usize = 10_000;
+
+/// Samples recorded within the last `window`, oldest first
+struct Window<T> {
+    samples: Mutex<VecDeque<(Instant, T)>>,
+}
+
+impl<T: Clone> Window<T> {
+    fn new() -> Self {
+        Self {
+            samples: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn record(&self, window: Duration, value: T) {
+        let mut samples = self.samples.lock().unwrap();
+        prune(&mut samples, window);
+        if samples.len() == MAX_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back((Instant::now(), value));
+    }
+
+    fn values(&self, window: Duration) -> Vec<T> {
+        let mut samples = self.samples.lock().unwrap();
+        prune(&mut samples, window);
+        samples.iter().map(|(_, value)| value.clone()).collect()
+    }
+
+    fn clear(&self) {
+        self.samples.lock().unwrap().clear();
+    }
+}
+
+fn prune<T>(samples: &mut VecDeque<(Instant, T)>, window: Duration) {
+    while let Some((recorded, _)) = samples.front() {
+        if recorded.elapsed() < window {
+            break;
+        }
+        samples.pop_front();
+    }
+}
+
+/// Latency percentiles in milliseconds over the current window
+#[derive(Debug, Default, Serialize)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+impl LatencySummary {
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort();
+        let percentile = |p: f64| {
+            // Nearest-rank percentile
+            let rank = ((p / 100.0) * samples.len() as f64).ceil() as usize;
+            samples[rank.clamp(1, samples.len()) - 1].as_secs_f64() * 1000.0
+        };
+        Self {
+            count: samples.len(),
+            p50_ms: percentile(50.0),
+            p95_ms: percentile(95.0),
+            p99_ms: percentile(99.0),
+        }
+    }
+}
+
+/// Hit ratio of a cache over the current window
+#[derive(Debug, Serialize)]
+pub struct CacheSummary {
+    pub hits: usize,
+    pub misses: usize,
+    /// `null` until the cache has been consulted
+    pub hit_ratio: Option<f64>,
+}
+
+/// Point-in-time view of the rolling statistics
+#[derive(Debug, Serialize)]
+pub struct StatsSnapshot {
+    pub window_secs: u64,
+    pub check: LatencySummary,
+    pub list_objects: LatencySummary,
+    pub responses_by_status: BTreeMap<u16, usize>,
+    pub shared_resources_cache: CacheSummary,
+}
+
+/// In-memory rolling windows of decision latencies, response statuses and
+/// cache lookups, for a quick health view without a metrics backend
+pub struct Stats {
+    window: Duration,
+    check: Window<Duration>,
+    list_objects: Window<Duration>,
+    statuses: Window<u16>,
+    cache_lookups: Window<bool>,
+}
+
+impl Stats {
+    /// Create empty statistics that only report samples from the last `window`
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            check: Window::new(),
+            list_objects: Window::new(),
+            statuses: Window::new(),
+            cache_lookups: Window::new(),
+        }
+    }
+
+    pub fn record_check(&self, latency: Duration) {
+        self.check.record(self.window, latency);
+    }
+
+    pub fn record_list_objects(&self, latency: Duration) {
+        self.list_objects.record(self.window, latency);
+    }
+
+    pub fn record_status(&self, status: u16) {
+        self.statuses.record(self.window, status);
+    }
+
+    pub fn record_cache_lookup(&self, hit: bool) {
+        self.cache_lookups.record(self.window, hit);
+    }
+
+    /// Summarise the samples currently inside the window
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let mut responses_by_status = BTreeMap::new();
+        for status in self.statuses.values(self.window) {
+            *responses_by_status.entry(status).or_insert(0) += 1;
+        }
+
+        let lookups = self.cache_lookups.values(self.window);
+        let hits = lookups.iter().filter(|hit| **hit).count();
+        let misses = lookups.len() - hits;
+
+        StatsSnapshot {
+            window_secs: self.window.as_secs(),
+            check: LatencySummary::from_samples(self.check.values(self.window)),
+            list_objects: LatencySummary::from_samples(self.list_objects.values(self.window)),
+            responses_by_status,
+            shared_resources_cache: CacheSummary {
+                hits,
+                misses,
+                hit_ratio: (!lookups.is_empty()).then(|| hits as f64 / lookups.len() as f64),
+            },
+        }
+    }
+
+    /// Discard every recorded sample
+    pub fn reset(&self) {
+        self.check.clear();
+        self.list_objects.clear();
+        self.statuses.clear();
+        self.cache_lookups.clear();
+    }
+}
+
+/// Count every response by status code
+pub async fn stats_middleware(
+    State(ctx): State<Arc<Ctx>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+    ctx.stats.record_status(response.status().as_u16());
+    response
+}
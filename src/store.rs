@@ -0,0 +1,799 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Delay before retrying a read that failed on a dropped connection
+const READ_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// Outbox event types, one per kind of resource mutation
+pub const RESOURCE_CREATED: &str = "resource.created";
+pub const RESOURCE_UPDATED: &str = "resource.updated";
+pub const RESOURCE_DELETED: &str = "resource.deleted";
+
+/// Outbox event type of an organisation moved under a new parent
+pub const ORGANISATION_REPARENTED: &str = "organisation.reparented";
+
+/// A stored resource
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Resource {
+    /// Stable identifier that survives renames, the FGA object still uses the key
+    pub id: Uuid,
+    pub name: String,
+    pub service_name: String,
+    pub service_type: String,
+    pub org_id: String,
+    pub properties: Value,
+    /// Starts at 1 and is incremented by every update, clients see it as the ETag
+    pub version: i64,
+}
+
+impl Resource {
+    pub fn key(&self) -> ResourceKey {
+        ResourceKey {
+            service_name: self.service_name.clone(),
+            service_type: self.service_type.clone(),
+            org_id: self.org_id.clone(),
+            name: self.name.clone(),
+        }
+    }
+}
+
+/// A resource mutation recorded in the outbox, written in the same transaction
+/// as the mutation and forwarded downstream by the relay
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OutboxEvent {
+    pub id: i64,
+    /// One of the `RESOURCE_*` or `ORGANISATION_*` event types
+    #[sqlx(rename = "type")]
+    pub event_type: String,
+    /// `None` for organisation events
+    pub resource_id: Option<Uuid>,
+    /// The resource after the mutation, or as it was before a delete; for
+    /// organisation events, the change that was made
+    pub payload: Value,
+    pub created_at: OffsetDateTime,
+}
+
+/// Identifies a resource: its service, service type, organisation and name
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ResourceKey {
+    pub service_name: String,
+    pub service_type: String,
+    pub org_id: String,
+    pub name: String,
+}
+
+/// Failure of a resource store operation
+#[derive(Debug)]
+pub enum StoreError {
+    /// A resource with the same key already exists
+    Conflict,
+    /// The resource exists but is not at the version the update was based on
+    VersionMismatch,
+    /// The backend could not be reached
+    Unavailable(String),
+    /// Any other backend failure
+    Internal(String),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Conflict => f.write_str("resource already exists"),
+            StoreError::VersionMismatch => f.write_str("resource version does not match"),
+            StoreError::Unavailable(message) => write!(f, "store unavailable: {}", message),
+            StoreError::Internal(message) => write!(f, "store error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<sqlx::Error> for StoreError {
+    fn from(e: sqlx::Error) -> Self {
+        if let sqlx::Error::Database(db_error) = &e
+            && db_error.is_unique_violation()
+        {
+            return StoreError::Conflict;
+        }
+        if is_connection_error(&e) {
+            return StoreError::Unavailable(e.to_string());
+        }
+        StoreError::Internal(e.to_string())
+    }
+}
+
+/// Whether the error means the connection was lost rather than the query failing
+fn is_connection_error(e: &sqlx::Error) -> bool {
+    match e {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut => true,
+        // Class 08 is connection exception, 57P01 is admin shutdown
+        sqlx::Error::Database(db_error) => db_error
+            .code()
+            .is_some_and(|code| code.starts_with("08") || code == "57P01"),
+        _ => false,
+    }
+}
+
+/// Persistence for resource metadata
+#[async_trait]
+pub trait ResourceStore: Send + Sync {
+    /// Insert a new resource, failing with `Conflict` if the key is taken
+    async fn create(&self, resource: Resource) -> Result<Resource, StoreError>;
+
+    async fn get(&self, key: &ResourceKey) -> Result<Option<Resource>, StoreError>;
+
+    /// Look a resource up by its stable ID
+    async fn get_by_id(&self, id: Uuid) -> Result<Option<Resource>, StoreError>;
+
+    /// Replace a resource's properties and bump its version, returning `None`
+    /// if it does not exist
+    ///
+    /// With `expected_version` the update fails with `VersionMismatch` unless
+    /// the stored resource is still at that version.
+    async fn update(
+        &self,
+        key: &ResourceKey,
+        properties: Value,
+        expected_version: Option<i64>,
+    ) -> Result<Option<Resource>, StoreError>;
+
+    /// Delete a resource, returning whether it existed
+    async fn delete(&self, key: &ResourceKey) -> Result<bool, StoreError>;
+
+    /// Insert many resources in one transaction, skipping keys that are taken
+    ///
+    /// Returns the stored resource for each input, in order, or `None` where
+    /// the key already existed.
+    async fn create_many(
+        &self,
+        resources: Vec<Resource>,
+    ) -> Result<Vec<Option<Resource>>, StoreError>;
+
+    /// Delete many resources in one transaction, returning the keys that existed
+    async fn delete_many(&self, keys: &[ResourceKey]) -> Result<Vec<ResourceKey>, StoreError>;
+
+    /// Fetch the resources that exist among the given keys
+    async fn list_by_ids(&self, keys: &[ResourceKey]) -> Result<Vec<Resource>, StoreError>;
+
+    /// Fetch every resource of an organisation, ordered by key
+    async fn list_by_org(&self, org_id: &str) -> Result<Vec<Resource>, StoreError>;
+
+    /// Fetch up to `limit` undelivered outbox events, oldest first
+    async fn pending_events(&self, limit: i64) -> Result<Vec<OutboxEvent>, StoreError>;
+
+    /// Mark outbox events as delivered so the relay does not send them again
+    async fn mark_delivered(&self, ids: &[i64]) -> Result<(), StoreError>;
+
+    /// Record an organisation change in the outbox, for changes that are
+    /// made in OpenFGA rather than to a resource row
+    async fn record_organisation_event(
+        &self,
+        event_type: &str,
+        payload: Value,
+    ) -> Result<(), StoreError>;
+}
+
+/// Resources stored in the `resources` table
+pub struct PgResourceStore {
+    db: PgPool,
+}
+
+impl PgResourceStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+}
+
+/// Record a mutation of `resource` in the outbox, on the connection of the
+/// transaction making the mutation
+async fn insert_event(
+    conn: &mut PgConnection,
+    event_type: &str,
+    resource: &Resource,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO outbox (type, resource_id, payload) VALUES ($1, $2, $3)")
+        .bind(event_type)
+        .bind(resource.id)
+        .bind(event_payload(resource))
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Outbox payload of a resource mutation
+fn event_payload(resource: &Resource) -> Value {
+    serde_json::to_value(resource).unwrap_or(Value::Null)
+}
+
+/// Run an idempotent read, retrying once if the connection was dropped so a
+/// Postgres restart does not fail the request while the pool reconnects
+async fn retry_read<T, F, Fut>(mut read: F) -> Result<T, StoreError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    match read().await {
+        Err(e) if is_connection_error(&e) => {
+            tracing::warn!("Retrying read after connection error: {}", e);
+            tokio::time::sleep(READ_RETRY_DELAY).await;
+            Ok(read().await?)
+        }
+        result => Ok(result?),
+    }
+}
+
+#[async_trait]
+impl ResourceStore for PgResourceStore {
+    async fn create(&self, resource: Resource) -> Result<Resource, StoreError> {
+        let mut tx = self.db.begin().await?;
+        let created = sqlx::query_as::<_, Resource>(
+            "INSERT INTO resources (service_name, service_type, org_id, name, properties, version, id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING id, name, service_name, service_type, org_id, properties, version",
+        )
+        .bind(&resource.service_name)
+        .bind(&resource.service_type)
+        .bind(&resource.org_id)
+        .bind(&resource.name)
+        .bind(&resource.properties)
+        .bind(resource.version)
+        .bind(resource.id)
+        .fetch_one(&mut *tx)
+        .await?;
+        insert_event(&mut tx, RESOURCE_CREATED, &created).await?;
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    async fn create_many(
+        &self,
+        resources: Vec<Resource>,
+    ) -> Result<Vec<Option<Resource>>, StoreError> {
+        let mut tx = self.db.begin().await?;
+        let mut created = Vec::with_capacity(resources.len());
+        for resource in &resources {
+            let inserted = sqlx::query_as::<_, Resource>(
+                "INSERT INTO resources (service_name, service_type, org_id, name, properties, version, id)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 ON CONFLICT DO NOTHING
+                 RETURNING id, name, service_name, service_type, org_id, properties, version",
+            )
+            .bind(&resource.service_name)
+            .bind(&resource.service_type)
+            .bind(&resource.org_id)
+            .bind(&resource.name)
+            .bind(&resource.properties)
+            .bind(resource.version)
+            .bind(resource.id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            if let Some(inserted) = &inserted {
+                insert_event(&mut tx, RESOURCE_CREATED, inserted).await?;
+            }
+            created.push(inserted);
+        }
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    async fn get(&self, key: &ResourceKey) -> Result<Option<Resource>, StoreError> {
+        retry_read(move || {
+            sqlx::query_as::<_, Resource>(
+                "SELECT id, name, service_name, service_type, org_id, properties, version
+                 FROM resources
+                 WHERE service_name = $1 AND service_type = $2 AND org_id = $3 AND name = $4",
+            )
+            .bind(&key.service_name)
+            .bind(&key.service_type)
+            .bind(&key.org_id)
+            .bind(&key.name)
+            .fetch_optional(&self.db)
+        })
+        .await
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> Result<Option<Resource>, StoreError> {
+        retry_read(move || {
+            sqlx::query_as::<_, Resource>(
+                "SELECT id, name, service_name, service_type, org_id, properties, version
+                 FROM resources
+                 WHERE id = $1",
+            )
+            .bind(id)
+            .fetch_optional(&self.db)
+        })
+        .await
+    }
+
+    async fn update(
+        &self,
+        key: &ResourceKey,
+        properties: Value,
+        expected_version: Option<i64>,
+    ) -> Result<Option<Resource>, StoreError> {
+        let mut tx = self.db.begin().await?;
+        let updated = sqlx::query_as::<_, Resource>(
+            "UPDATE resources SET properties = $5, version = version + 1, updated_at = now()
+             WHERE service_name = $1 AND service_type = $2 AND org_id = $3 AND name = $4
+               AND ($6::BIGINT IS NULL OR version = $6)
+             RETURNING id, name, service_name, service_type, org_id, properties, version",
+        )
+        .bind(&key.service_name)
+        .bind(&key.service_type)
+        .bind(&key.org_id)
+        .bind(&key.name)
+        .bind(&properties)
+        .bind(expected_version)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(updated) = &updated {
+            insert_event(&mut tx, RESOURCE_UPDATED, updated).await?;
+        }
+        tx.commit().await?;
+
+        // No row matched: tell a missing resource apart from a stale version
+        if updated.is_none() && expected_version.is_some() && self.get(key).await?.is_some() {
+            return Err(StoreError::VersionMismatch);
+        }
+        Ok(updated)
+    }
+
+    async fn delete(&self, key: &ResourceKey) -> Result<bool, StoreError> {
+        let mut tx = self.db.begin().await?;
+        let deleted = sqlx::query_as::<_, Resource>(
+            "DELETE FROM resources
+             WHERE service_name = $1 AND service_type = $2 AND org_id = $3 AND name = $4
+             RETURNING id, name, service_name, service_type, org_id, properties, version",
+        )
+        .bind(&key.service_name)
+        .bind(&key.service_type)
+        .bind(&key.org_id)
+        .bind(&key.name)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(deleted) = &deleted {
+            insert_event(&mut tx, RESOURCE_DELETED, deleted).await?;
+        }
+        tx.commit().await?;
+        Ok(deleted.is_some())
+    }
+
+    async fn delete_many(&self, keys: &[ResourceKey]) -> Result<Vec<ResourceKey>, StoreError> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let service_names: Vec<&str> = keys.iter().map(|key| key.service_name.as_str()).collect();
+        let service_types: Vec<&str> = keys.iter().map(|key| key.service_type.as_str()).collect();
+        let org_ids: Vec<&str> = keys.iter().map(|key| key.org_id.as_str()).collect();
+        let names: Vec<&str> = keys.iter().map(|key| key.name.as_str()).collect();
+
+        let mut tx = self.db.begin().await?;
+        let deleted = sqlx::query_as::<_, Resource>(
+            "DELETE FROM resources
+             USING UNNEST($1::text[], $2::text[], $3::text[], $4::text[])
+                 AS wanted (service_name, service_type, org_id, name)
+             WHERE resources.service_name = wanted.service_name
+               AND resources.service_type = wanted.service_type
+               AND resources.org_id = wanted.org_id
+               AND resources.name = wanted.name
+             RETURNING resources.id, resources.name, resources.service_name,
+                       resources.service_type, resources.org_id,
+                       resources.properties, resources.version",
+        )
+        .bind(&service_names)
+        .bind(&service_types)
+        .bind(&org_ids)
+        .bind(&names)
+        .fetch_all(&mut *tx)
+        .await?;
+        for resource in &deleted {
+            insert_event(&mut tx, RESOURCE_DELETED, resource).await?;
+        }
+        tx.commit().await?;
+
+        Ok(deleted.iter().map(Resource::key).collect())
+    }
+
+    async fn list_by_ids(&self, keys: &[ResourceKey]) -> Result<Vec<Resource>, StoreError> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Bind the keys as parallel arrays and join against them
+        let service_names: Vec<&str> = keys.iter().map(|key| key.service_name.as_str()).collect();
+        let service_types: Vec<&str> = keys.iter().map(|key| key.service_type.as_str()).collect();
+        let org_ids: Vec<&str> = keys.iter().map(|key| key.org_id.as_str()).collect();
+        let names: Vec<&str> = keys.iter().map(|key| key.name.as_str()).collect();
+        let (service_names, service_types, org_ids, names) =
+            (&service_names, &service_types, &org_ids, &names);
+
+        retry_read(move || {
+            sqlx::query_as::<_, Resource>(
+                "SELECT id, name, service_name, service_type, org_id, properties, version
+                 FROM resources
+                 JOIN UNNEST($1::text[], $2::text[], $3::text[], $4::text[])
+                     AS wanted (service_name, service_type, org_id, name)
+                 USING (service_name, service_type, org_id, name)",
+            )
+            .bind(service_names)
+            .bind(service_types)
+            .bind(org_ids)
+            .bind(names)
+            .fetch_all(&self.db)
+        })
+        .await
+    }
+
+    async fn list_by_org(&self, org_id: &str) -> Result<Vec<Resource>, StoreError> {
+        retry_read(move || {
+            sqlx::query_as::<_, Resource>(
+                "SELECT id, name, service_name, service_type, org_id, properties, version
+                 FROM resources
+                 WHERE org_id = $1
+                 ORDER BY service_name, service_type, name",
+            )
+            .bind(org_id)
+            .fetch_all(&self.db)
+        })
+        .await
+    }
+
+    async fn pending_events(&self, limit: i64) -> Result<Vec<OutboxEvent>, StoreError> {
+        retry_read(move || {
+            sqlx::query_as::<_, OutboxEvent>(
+                "SELECT id, type, resource_id, payload, created_at
+                 FROM outbox
+                 WHERE delivered_at IS NULL
+                 ORDER BY id
+                 LIMIT $1",
+            )
+            .bind(limit)
+            .fetch_all(&self.db)
+        })
+        .await
+    }
+
+    async fn mark_delivered(&self, ids: &[i64]) -> Result<(), StoreError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        sqlx::query("UPDATE outbox SET delivered_at = now() WHERE id = ANY($1)")
+            .bind(ids)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn record_organisation_event(
+        &self,
+        event_type: &str,
+        payload: Value,
+    ) -> Result<(), StoreError> {
+        sqlx::query("INSERT INTO outbox (type, payload) VALUES ($1, $2)")
+            .bind(event_type)
+            .bind(payload)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Resources kept in process memory, for development and tests without Postgres
+///
+/// Outbox events are recorded while the resources lock is held, so a mutation
+/// and its event become visible together.
+#[derive(Default)]
+pub struct MemoryResourceStore {
+    resources: Mutex<HashMap<ResourceKey, Resource>>,
+    outbox: Mutex<MemoryOutbox>,
+}
+
+/// Undelivered outbox events of the in-memory store
+#[derive(Default)]
+struct MemoryOutbox {
+    last_id: i64,
+    pending: Vec<OutboxEvent>,
+}
+
+impl MemoryResourceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a mutation of `resource`; call with the resources lock held
+    fn record_event(&self, event_type: &str, resource: &Resource) {
+        self.push_event(event_type, Some(resource.id), event_payload(resource));
+    }
+
+    fn push_event(&self, event_type: &str, resource_id: Option<Uuid>, payload: Value) {
+        let mut outbox = self.outbox.lock().unwrap();
+        outbox.last_id += 1;
+        let event = OutboxEvent {
+            id: outbox.last_id,
+            event_type: event_type.to_string(),
+            resource_id,
+            payload,
+            created_at: OffsetDateTime::now_utc(),
+        };
+        outbox.pending.push(event);
+    }
+}
+
+#[async_trait]
+impl ResourceStore for MemoryResourceStore {
+    async fn create(&self, resource: Resource) -> Result<Resource, StoreError> {
+        let mut resources = self.resources.lock().unwrap();
+        let key = resource.key();
+        if resources.contains_key(&key) {
+            return Err(StoreError::Conflict);
+        }
+        self.record_event(RESOURCE_CREATED, &resource);
+        resources.insert(key, resource.clone());
+        Ok(resource)
+    }
+
+    async fn create_many(
+        &self,
+        resources: Vec<Resource>,
+    ) -> Result<Vec<Option<Resource>>, StoreError> {
+        let mut stored = self.resources.lock().unwrap();
+        Ok(resources
+            .into_iter()
+            .map(|resource| {
+                let key = resource.key();
+                if stored.contains_key(&key) {
+                    return None;
+                }
+                self.record_event(RESOURCE_CREATED, &resource);
+                stored.insert(key, resource.clone());
+                Some(resource)
+            })
+            .collect())
+    }
+
+    async fn get(&self, key: &ResourceKey) -> Result<Option<Resource>, StoreError> {
+        Ok(self.resources.lock().unwrap().get(key).cloned())
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> Result<Option<Resource>, StoreError> {
+        Ok(self
+            .resources
+            .lock()
+            .unwrap()
+            .values()
+            .find(|resource| resource.id == id)
+            .cloned())
+    }
+
+    async fn update(
+        &self,
+        key: &ResourceKey,
+        properties: Value,
+        expected_version: Option<i64>,
+    ) -> Result<Option<Resource>, StoreError> {
+        let mut resources = self.resources.lock().unwrap();
+        let Some(resource) = resources.get_mut(key) else {
+            return Ok(None);
+        };
+        if expected_version.is_some_and(|version| version != resource.version) {
+            return Err(StoreError::VersionMismatch);
+        }
+        resource.properties = properties;
+        resource.version += 1;
+        self.record_event(RESOURCE_UPDATED, resource);
+        Ok(Some(resource.clone()))
+    }
+
+    async fn delete(&self, key: &ResourceKey) -> Result<bool, StoreError> {
+        let mut resources = self.resources.lock().unwrap();
+        let Some(deleted) = resources.remove(key) else {
+            return Ok(false);
+        };
+        self.record_event(RESOURCE_DELETED, &deleted);
+        Ok(true)
+    }
+
+    async fn delete_many(&self, keys: &[ResourceKey]) -> Result<Vec<ResourceKey>, StoreError> {
+        let mut resources = self.resources.lock().unwrap();
+        let mut deleted = Vec::new();
+        for key in keys {
+            if let Some(resource) = resources.remove(key) {
+                self.record_event(RESOURCE_DELETED, &resource);
+                deleted.push(key.clone());
+            }
+        }
+        Ok(deleted)
+    }
+
+    async fn list_by_ids(&self, keys: &[ResourceKey]) -> Result<Vec<Resource>, StoreError> {
+        let resources = self.resources.lock().unwrap();
+        Ok(keys
+            .iter()
+            .filter_map(|key| resources.get(key).cloned())
+            .collect())
+    }
+
+    async fn list_by_org(&self, org_id: &str) -> Result<Vec<Resource>, StoreError> {
+        let mut listed: Vec<Resource> = self
+            .resources
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|resource| resource.org_id == org_id)
+            .cloned()
+            .collect();
+        listed.sort_by(|a, b| {
+            (&a.service_name, &a.service_type, &a.name).cmp(&(
+                &b.service_name,
+                &b.service_type,
+                &b.name,
+            ))
+        });
+        Ok(listed)
+    }
+    async fn pending_events(&self, limit: i64) -> Result<Vec<OutboxEvent>, StoreError> {
+        let outbox = self.outbox.lock().unwrap();
+        Ok(outbox
+            .pending
+            .iter()
+            .take(usize::try_from(limit).unwrap_or(0))
+            .cloned()
+            .collect())
+    }
+
+    async fn mark_delivered(&self, ids: &[i64]) -> Result<(), StoreError> {
+        self.outbox
+            .lock()
+            .unwrap()
+            .pending
+            .retain(|event| !ids.contains(&event.id));
+        Ok(())
+    }
+
+    async fn record_organisation_event(
+        &self,
+        event_type: &str,
+        payload: Value,
+    ) -> Result<(), StoreError> {
+        self.push_event(event_type, None, payload);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ApiError;
+    use axum::http::StatusCode;
+    use sqlx::error::{DatabaseError, ErrorKind};
+    use std::borrow::Cow;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Database error carrying a Postgres SQLSTATE code
+    #[derive(Debug)]
+    struct PgError(&'static str);
+
+    impl fmt::Display for PgError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "database error {}", self.0)
+        }
+    }
+
+    impl std::error::Error for PgError {}
+
+    impl DatabaseError for PgError {
+        fn message(&self) -> &str {
+            "database error"
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> ErrorKind {
+            match self.0 {
+                "23505" => ErrorKind::UniqueViolation,
+                _ => ErrorKind::Other,
+            }
+        }
+    }
+
+    fn database_error(code: &'static str) -> sqlx::Error {
+        sqlx::Error::Database(Box::new(PgError(code)))
+    }
+
+    #[tokio::test]
+    async fn read_is_retried_once_after_a_dropped_connection() {
+        let calls = AtomicUsize::new(0);
+
+        let result = retry_read(|| {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if call == 0 {
+                    Err(sqlx::Error::PoolTimedOut)
+                } else {
+                    Ok(7)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 7);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn read_failing_twice_is_unavailable() {
+        let calls = AtomicUsize::new(0);
+
+        let result: Result<(), _> = retry_read(|| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err(database_error("08006")) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(StoreError::Unavailable(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn failed_query_is_not_retried() {
+        let calls = AtomicUsize::new(0);
+
+        let result: Result<(), _> = retry_read(|| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err(sqlx::Error::RowNotFound) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(StoreError::Internal(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn unique_violation_is_a_conflict() {
+        let error = StoreError::from(database_error("23505"));
+
+        assert!(matches!(error, StoreError::Conflict));
+        assert_eq!(ApiError::from(error).code.status(), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn connection_errors_are_unavailable() {
+        for error in [
+            sqlx::Error::PoolTimedOut,
+            database_error("08006"),
+            database_error("57P01"),
+        ] {
+            assert!(matches!(
+                StoreError::from(error),
+                StoreError::Unavailable(_)
+            ));
+        }
+        assert!(matches!(
+            StoreError::from(database_error("42P01")),
+            StoreError::Internal(_)
+        ));
+    }
+}
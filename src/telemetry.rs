@@ -0,0 +1,88 @@
+use opentelemetry::KeyValue;
+use opentelemetry::propagation::Injector;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::runtime;
+use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+use std::env;
+use tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+
+/// Service name reported on exported spans
+const SERVICE_NAME: &str = "openfga-demo";
+
+/// Handle to the OTLP exporter, used to flush pending spans on shutdown
+pub struct Telemetry {
+    provider: TracerProvider,
+}
+
+impl Telemetry {
+    /// Flush and stop the exporter
+    pub fn shutdown(self) {
+        if let Err(e) = self.provider.shutdown() {
+            tracing::warn!("Failed to shut down OpenTelemetry exporter: {}", e);
+        }
+    }
+}
+
+/// Build the OTLP tracing layer when `OTEL_EXPORTER_OTLP_ENDPOINT` is set
+///
+/// Returns `None` when the endpoint is unset so the subscriber only logs locally.
+pub fn otlp_layer<S>()
+-> Result<Option<(OpenTelemetryLayer<S, Tracer>, Telemetry)>, Box<dyn std::error::Error>>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    let endpoint = match env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+        Ok(endpoint) if !endpoint.trim().is_empty() => endpoint,
+        _ => return Ok(None),
+    };
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()?;
+
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new(vec![KeyValue::new(
+            "service.name",
+            SERVICE_NAME,
+        )]))
+        .build();
+
+    let tracer = provider.tracer(SERVICE_NAME);
+    opentelemetry::global::set_tracer_provider(provider.clone());
+    // Outgoing OpenFGA calls carry `traceparent` so their spans join ours
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+    Ok(Some((
+        tracing_opentelemetry::layer().with_tracer(tracer),
+        Telemetry { provider },
+    )))
+}
+
+/// Add the current span's trace context to outgoing gRPC metadata
+///
+/// A no-op unless `otlp_layer` installed the W3C propagator.
+pub fn inject_trace_context(metadata: &mut MetadataMap) {
+    let context = tracing::Span::current().context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut MetadataInjector(metadata))
+    });
+}
+
+struct MetadataInjector<'a>(&'a mut MetadataMap);
+
+impl Injector for MetadataInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(key), Ok(value)) = (
+            MetadataKey::from_bytes(key.as_bytes()),
+            MetadataValue::try_from(value.as_str()),
+        ) {
+            self.0.insert(key, value);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{
+    CapturedLogs, MOCK_MODEL_ID, MockFga, TestApp, TestResponse, json_request, resource_path,
+};
+use openfga_demo::context::Ctx;
+use serde_json::json;
+use std::sync::Arc;
+use tracing::Level;
+
+/// App on the mock, rejecting mutations without a reason when `required`
+fn app(required: bool) -> TestApp {
+    let mut ctx = Ctx::for_testing().with_fga_client(Arc::new(MockFga::default()));
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.require_action_reason = required;
+    TestApp::with_ctx(ctx)
+}
+
+/// Create the `report` resource as `alice`, stating `reason` when given, and
+/// return the response with the audit log lines written meanwhile
+async fn create_report(app: &TestApp, reason: Option<&str>) -> (TestResponse, String) {
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(Level::INFO)
+        .with_ansi(false)
+        .with_writer(logs.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mut request = json_request(
+        Method::POST,
+        &resource_path("report"),
+        Some("alice"),
+        &json!({ "properties": {} }),
+    );
+    if let Some(reason) = reason {
+        request
+            .headers_mut()
+            .insert("x-action-reason", reason.parse().unwrap());
+    }
+    let response = app.send(request).await;
+
+    let audit = logs
+        .contents()
+        .lines()
+        .filter(|line| line.contains("audit:"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    (response, audit)
+}
+
+#[tokio::test]
+async fn missing_reason_is_rejected_when_required() {
+    let app = app(true);
+
+    let (response, audit) = create_report(&app, None).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_code(), "reason_required");
+    let stored = app.ctx.resources.list_by_org("org-1").await.unwrap();
+    assert!(stored.is_empty());
+    assert!(audit.is_empty(), "{}", audit);
+}
+
+#[tokio::test]
+async fn blank_reason_counts_as_missing() {
+    let app = app(true);
+
+    let (response, _) = create_report(&app, Some("   ")).await;
+
+    assert_eq!(response.error_code(), "reason_required");
+}
+
+#[tokio::test]
+async fn stated_reason_is_recorded_in_the_audit_log() {
+    let app = app(true);
+
+    let (response, audit) = create_report(&app, Some("Ticket OPS-42")).await;
+
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert!(audit.contains("reason=\"Ticket OPS-42\""), "{}", audit);
+    assert!(audit.contains("user_id=alice"), "{}", audit);
+    assert!(audit.contains("method=POST"), "{}", audit);
+    assert!(audit.contains("status=201"), "{}", audit);
+}
+
+#[tokio::test]
+async fn reason_is_optional_by_default() {
+    let app = app(false);
+
+    let (response, audit) = create_report(&app, None).await;
+
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert!(audit.contains("reason=\"\""), "{}", audit);
+}
+
+#[tokio::test]
+async fn reads_never_need_a_reason() {
+    let app = app(true);
+
+    let response = app.get(Some("alice"), "/api/list-objects").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+}
@@ -0,0 +1,211 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{MOCK_MODEL_ID, MockFga, TestApp, resource_object, sample_resource};
+use openfga_demo::context::Ctx;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+/// App answering OpenFGA calls with the mock, with `root` as admin
+fn admin_app(fga: Arc<MockFga>) -> TestApp {
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.admin.user_ids = vec!["root".to_string()];
+    TestApp::with_ctx(ctx)
+}
+
+#[tokio::test]
+async fn admin_lists_objects_of_another_user() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:bob", "editor", &resource_object("report"));
+    fga.grant("user:root", "editor", &resource_object("invoice"));
+    let app = admin_app(fga);
+
+    let response = app
+        .get(
+            Some("root"),
+            "/api/admin/users/bob/objects?type=resource&relation=editor",
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["items"][0], resource_object("report"));
+    assert_eq!(response.body["page"]["size"], 1);
+    assert_eq!(response.body["relation"], "editor");
+    assert_eq!(response.body["object_type"], "resource");
+}
+
+#[tokio::test]
+async fn non_admin_cannot_list_objects_of_another_user() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:bob", "viewer", &resource_object("report"));
+    let app = admin_app(fga.clone());
+
+    let response = app.get(Some("alice"), "/api/admin/users/bob/objects").await;
+
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert_eq!(response.error_code(), "forbidden");
+    assert_eq!(fga.listings.load(Ordering::SeqCst), 0);
+}
+
+/// Compare the viewer access of alice and bob as `caller`
+async fn compare(app: &TestApp, caller: &str) -> common::TestResponse {
+    app.send_json(
+        Method::POST,
+        "/api/admin/compare-access",
+        caller,
+        json!({ "user_a": "alice", "user_b": "bob", "type": "resource", "relation": "viewer" }),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn compare_access_splits_overlapping_sets() {
+    let fga = Arc::new(MockFga::default());
+    for name in ["report", "invoice"] {
+        fga.grant("user:alice", "viewer", &resource_object(name));
+    }
+    for name in ["invoice", "budget"] {
+        fga.grant("user:bob", "viewer", &resource_object(name));
+    }
+    let app = admin_app(fga);
+
+    let response = compare(&app, "root").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["only_a"], json!([resource_object("report")]));
+    assert_eq!(response.body["only_b"], json!([resource_object("budget")]));
+    assert_eq!(response.body["shared"], json!([resource_object("invoice")]));
+    assert_eq!(response.body["truncated"], false);
+}
+
+#[tokio::test]
+async fn compare_access_of_disjoint_sets_shares_nothing() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    fga.grant("user:bob", "viewer", &resource_object("budget"));
+    let app = admin_app(fga.clone());
+
+    let response = compare(&app, "root").await;
+
+    assert_eq!(response.body["only_a"], json!([resource_object("report")]));
+    assert_eq!(response.body["only_b"], json!([resource_object("budget")]));
+    assert_eq!(response.body["shared"], json!([]));
+    assert_eq!(fga.listings.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn compare_access_is_admin_only() {
+    let fga = Arc::new(MockFga::default());
+    let app = admin_app(fga.clone());
+
+    let response = compare(&app, "alice").await;
+
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert_eq!(fga.listings.load(Ordering::SeqCst), 0);
+}
+
+/// Diagnose `user` against the stored `report` resource as `root`
+async fn diagnose(app: &TestApp, user: &str, name: &str) -> common::TestResponse {
+    app.add_resource(sample_resource("report")).await;
+    let path = format!(
+        "/api/admin/diagnose?user={}&resource={}",
+        user,
+        resource_object(name)
+    );
+    app.get(Some("root"), &path).await
+}
+
+/// `(relation, allowed)` of each check in a diagnose report
+fn check_results(report: &Value) -> Vec<(String, Value)> {
+    report["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|check| {
+            assert!(check["latency_ms"].is_u64(), "{}", check);
+            (
+                check["relation"].as_str().unwrap().to_string(),
+                check["allowed"].clone(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn diagnose_reports_each_stage_for_an_allowed_user() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    let app = admin_app(fga);
+
+    let response = diagnose(&app, "alice", "report").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body["user"],
+        json!({ "id": "alice", "valid": true })
+    );
+    assert_eq!(
+        response.body["fga"],
+        json!({ "store_configured": true, "model_configured": true, "object_type_known": true })
+    );
+    assert_eq!(
+        check_results(&response.body),
+        [
+            ("owner".to_string(), json!(false)),
+            ("admin".to_string(), json!(false)),
+            ("editor".to_string(), json!(false)),
+            ("viewer".to_string(), json!(true)),
+        ]
+    );
+    assert_eq!(response.body["db"]["reachable"], true);
+    assert_eq!(response.body["db"]["row_exists"], true);
+    assert!(response.body["db"]["error"].is_null());
+    assert!(response.body["db"]["latency_ms"].is_u64());
+}
+
+#[tokio::test]
+async fn diagnose_reports_each_stage_for_a_denied_user() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    let app = admin_app(fga);
+
+    let response = diagnose(&app, "bob", "invoice").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["user"]["valid"], true);
+    let checks = check_results(&response.body);
+    assert_eq!(checks.len(), 4);
+    assert!(checks.iter().all(|(_, allowed)| *allowed == json!(false)));
+    assert_eq!(response.body["db"]["row_exists"], false);
+}
+
+#[tokio::test]
+async fn diagnose_skips_checks_for_an_invalid_user_id() {
+    let fga = Arc::new(MockFga::default());
+    let app = admin_app(fga.clone());
+
+    let response = diagnose(&app, "bad%23id", "report").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body["user"],
+        json!({ "id": "bad#id", "valid": false })
+    );
+    assert_eq!(response.body["checks"], json!([]));
+    assert_eq!(fga.checks.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn diagnose_is_admin_only() {
+    let app = admin_app(Arc::new(MockFga::default()));
+    let path = format!(
+        "/api/admin/diagnose?user=alice&resource={}",
+        resource_object("report")
+    );
+
+    let response = app.get(Some("alice"), &path).await;
+
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use axum::body::Body;
+use axum::http::{Method, StatusCode};
+use common::{MOCK_MODEL_ID, MockFga, TestApp, request};
+use openfga_demo::context::Ctx;
+use regex::Regex;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn missing_identity_is_unauthenticated() {
+    let app = TestApp::new();
+
+    let response = app.get(None, "/api/list-objects").await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.error_code(), "unauthenticated");
+    assert!(response.body["request_id"].is_string());
+}
+
+#[tokio::test]
+async fn empty_user_id_header_is_rejected() {
+    let app = TestApp::new();
+
+    let response = app.get(Some("   "), "/api/list-objects").await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_code(), "invalid_identity");
+}
+
+#[tokio::test]
+async fn non_utf8_user_id_header_is_rejected() {
+    let app = TestApp::new();
+    let request = request(Method::GET, "/api/list-objects", None)
+        .header("x-user-id", &b"al\xffce"[..])
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.send(request).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_code(), "invalid_identity");
+}
+
+#[tokio::test]
+async fn typed_user_id_is_rejected() {
+    let app = TestApp::new();
+
+    let response = app.get(Some("user:alice"), "/api/list-objects").await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_code(), "invalid_user_id");
+}
+
+#[tokio::test]
+async fn malformed_user_id_is_rejected() {
+    let app = TestApp::new();
+
+    for user_id in ["alice#member", "*", "-alice"] {
+        let response = app.get(Some(user_id), "/api/list-objects").await;
+
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "{}", user_id);
+        assert_eq!(response.error_code(), "invalid_user_id", "{}", user_id);
+    }
+}
+
+#[tokio::test]
+async fn well_formed_user_ids_are_accepted() {
+    let app = TestApp::with_fga(Arc::new(MockFga::default()));
+
+    for user_id in [
+        "alice",
+        "Alice.Smith@example.com",
+        "svc_reports-2",
+        "tenant|alice",
+    ] {
+        let response = app.get(Some(user_id), "/api/list-objects").await;
+
+        assert_eq!(response.status, StatusCode::OK, "{}", user_id);
+    }
+}
+
+#[tokio::test]
+async fn user_ids_with_reserved_characters_are_rejected() {
+    let app = TestApp::new();
+    let too_long = "a".repeat(129);
+
+    for user_id in ["group:eng", "al ice", "alice/bob", too_long.as_str()] {
+        let response = app.get(Some(user_id), "/api/list-objects").await;
+
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "{}", user_id);
+        assert_eq!(response.error_code(), "invalid_user_id", "{}", user_id);
+    }
+}
+
+#[tokio::test]
+async fn configured_user_id_pattern_replaces_the_default() {
+    let mut ctx = Ctx::for_testing().with_fga_client(Arc::new(MockFga::default()));
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.user_id_pattern = Regex::new(r"^emp-[0-9]{4}$").unwrap();
+    let app = TestApp::with_ctx(ctx);
+
+    let response = app.get(Some("emp-0042"), "/api/list-objects").await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = app.get(Some("alice"), "/api/list-objects").await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_code(), "invalid_user_id");
+    assert!(
+        response.body["message"]
+            .as_str()
+            .unwrap()
+            .contains("^emp-[0-9]{4}$")
+    );
+}
+
+#[tokio::test]
+async fn user_id_header_is_refused_when_disabled() {
+    let mut ctx = Ctx::for_testing();
+    ctx.allow_user_id_header = false;
+    let app = TestApp::with_ctx(ctx);
+
+    let response = app.get(Some("alice"), "/api/list-objects").await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.error_code(), "unauthenticated");
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{CapturedLogs, TestApp, json_request};
+use openfga_demo::body_log::BodyLogConfig;
+use openfga_demo::context::Ctx;
+use serde_json::json;
+use std::collections::HashSet;
+use tracing::Level;
+
+fn logging_app(max_bytes: usize) -> TestApp {
+    let mut ctx = Ctx::for_testing();
+    ctx.body_logging = Some(BodyLogConfig {
+        redact_keys: HashSet::from(["secret".to_string()]),
+        max_bytes,
+    });
+    TestApp::with_ctx(ctx)
+}
+
+/// Send a request while collecting debug logs on this thread
+async fn send_logged(app: &TestApp, body: serde_json::Value) -> (StatusCode, String) {
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(logs.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let response = app
+        .send(json_request(
+            Method::POST,
+            "/api/unknown",
+            Some("alice"),
+            &body,
+        ))
+        .await;
+    (response.status, logs.contents())
+}
+
+#[tokio::test]
+async fn request_and_response_bodies_are_logged_with_redaction() {
+    let app = logging_app(4096);
+
+    let (status, logs) = send_logged(
+        &app,
+        json!({ "name": "report", "nested": { "secret": "hunter2" } }),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(logs.contains("request POST /api/unknown body"), "{}", logs);
+    assert!(logs.contains(r#""name":"report""#), "{}", logs);
+    assert!(logs.contains(r#""secret":"[REDACTED]""#), "{}", logs);
+    assert!(!logs.contains("hunter2"), "{}", logs);
+    assert!(
+        logs.contains("response 404 POST /api/unknown body"),
+        "{}",
+        logs
+    );
+}
+
+#[tokio::test]
+async fn bodies_over_the_limit_are_not_logged() {
+    let app = logging_app(8);
+
+    let (status, logs) = send_logged(&app, json!({ "name": "a-rather-long-name" })).await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(!logs.contains("a-rather-long-name"), "{}", logs);
+    assert!(logs.contains("body not logged"), "{}", logs);
+}
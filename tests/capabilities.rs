@@ -0,0 +1,87 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{MOCK_MODEL_ID, MOCK_SCHEMA_VERSION, MockFga, TestApp};
+use openfga_client::client::ConsistencyPreference;
+use openfga_demo::context::Ctx;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// App with documents and folders instead of the default resource model
+fn app(public: bool) -> TestApp {
+    let mut ctx = Ctx::for_testing().with_fga_client(Arc::new(MockFga::default()));
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.fga_config.relations_by_type = BTreeMap::from([
+        (
+            "document".to_string(),
+            vec!["reader".to_string(), "writer".to_string()],
+        ),
+        ("folder".to_string(), vec!["browser".to_string()]),
+    ]);
+    ctx.fga_config.actions = BTreeMap::from([
+        ("read".to_string(), "reader".to_string()),
+        ("edit".to_string(), "writer".to_string()),
+        ("browse".to_string(), "browser".to_string()),
+    ]);
+    ctx.fga_config.consistency = ConsistencyPreference::HigherConsistency;
+    ctx.public_capabilities = public;
+    TestApp::with_ctx(ctx)
+}
+
+#[tokio::test]
+async fn manifest_reflects_the_configured_types_and_actions() {
+    let app = app(false);
+
+    let response = app.get(Some("alice"), "/api/capabilities").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body["types"],
+        json!({
+            "document": {
+                "relations": ["reader", "writer"],
+                "actions": { "edit": "writer", "read": "reader" }
+            },
+            "folder": {
+                "relations": ["browser"],
+                "actions": { "browse": "browser" }
+            }
+        })
+    );
+    assert_eq!(
+        response.body["actions"],
+        json!({ "browse": "browser", "edit": "writer", "read": "reader" })
+    );
+    assert_eq!(response.body["consistency"], "HIGHER_CONSISTENCY");
+    assert_eq!(response.body["api_version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(
+        response.body["model"],
+        json!({ "id": MOCK_MODEL_ID, "schema_version": MOCK_SCHEMA_VERSION })
+    );
+    assert_eq!(response.body["paging"]["cursor"], true);
+    let endpoints = response.body["paging"]["endpoints"].as_array().unwrap();
+    assert!(endpoints.contains(&json!("/api/list-objects")));
+}
+
+#[tokio::test]
+async fn manifest_requires_authentication_by_default() {
+    let app = app(false);
+
+    let response = app.get(None, "/api/capabilities").await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn manifest_can_be_served_publicly() {
+    let app = app(true);
+
+    let response = app.get(None, "/api/capabilities").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body["types"]["folder"]["relations"],
+        json!(["browser"])
+    );
+}
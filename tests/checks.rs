@@ -0,0 +1,147 @@
+mod common;
+
+use axum::http::{StatusCode, header};
+use common::{MOCK_MODEL_ID, MockFga, TestApp, resource_object, resource_path, sample_resource};
+use openfga_demo::context::Ctx;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use tonic::Code;
+
+#[tokio::test]
+async fn granted_check_allows_viewer() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    let app = TestApp::with_fga(fga.clone());
+    app.add_resource(sample_resource("report")).await;
+
+    let response = app.get(Some("alice"), &resource_path("report")).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.headers["x-fga-model-id"], MOCK_MODEL_ID);
+    assert_eq!(fga.checks.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn ungranted_check_denies_viewer() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    let app = TestApp::with_fga(fga);
+    app.add_resource(sample_resource("report")).await;
+
+    let response = app.get(Some("bob"), &resource_path("report")).await;
+
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert_eq!(response.error_code(), "forbidden");
+}
+
+#[tokio::test]
+async fn anonymous_check_uses_anonymous_principal() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:*", "viewer", &resource_object("public"));
+    let app = TestApp::with_fga(fga);
+    app.add_resource(sample_resource("public")).await;
+
+    let response = app.get(None, &resource_path("public")).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn named_anonymous_principal_replaces_the_wildcard() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:anonymous", "viewer", &resource_object("public"));
+    fga.grant("user:*", "viewer", &resource_object("wildcard"));
+    let mut ctx = Ctx::for_testing().with_fga_client(fga.clone());
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.anonymous_user = "user:anonymous".to_string();
+    let app = TestApp::with_ctx(ctx);
+    app.add_resource(sample_resource("public")).await;
+    app.add_resource(sample_resource("wildcard")).await;
+
+    let response = app.get(None, &resource_path("public")).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = app.get(None, &resource_path("wildcard")).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn wildcard_principal_ignores_named_anonymous_tuples() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:anonymous", "viewer", &resource_object("public"));
+    let app = TestApp::with_fga(fga);
+    app.add_resource(sample_resource("public")).await;
+
+    let response = app.get(None, &resource_path("public")).await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn check_results_are_cached() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    let app = TestApp::with_fga(fga.clone());
+    app.add_resource(sample_resource("report")).await;
+
+    app.get(Some("alice"), &resource_path("report")).await;
+    app.get(Some("alice"), &resource_path("report")).await;
+
+    assert_eq!(fga.checks.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn unreachable_openfga_is_service_unavailable() {
+    let fga = Arc::new(MockFga::default());
+    fga.set_unavailable();
+    let app = TestApp::with_fga(fga);
+
+    let response = app.get(Some("alice"), &resource_path("report")).await;
+
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.error_code(), "fga_unavailable");
+    assert!(response.headers.contains_key(header::RETRY_AFTER));
+}
+
+#[tokio::test]
+async fn openfga_rejections_map_to_http_statuses() {
+    let cases = [
+        (
+            Code::InvalidArgument,
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+        ),
+        (Code::NotFound, StatusCode::NOT_FOUND, "not_found"),
+        // Both mean OpenFGA rejected our credentials, not the caller's
+        (
+            Code::PermissionDenied,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "fga_error",
+        ),
+        (
+            Code::Unauthenticated,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "fga_error",
+        ),
+        (
+            Code::DeadlineExceeded,
+            StatusCode::GATEWAY_TIMEOUT,
+            "fga_timeout",
+        ),
+        (
+            Code::Internal,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "fga_error",
+        ),
+    ];
+    for (code, status, error_code) in cases {
+        let fga = Arc::new(MockFga::default());
+        fga.fail_with(code);
+        let app = TestApp::with_fga(fga);
+
+        let response = app.get(Some("alice"), &resource_path("report")).await;
+
+        assert_eq!(response.status, status, "{:?}", code);
+        assert_eq!(response.error_code(), error_code, "{:?}", code);
+    }
+}
@@ -0,0 +1,618 @@
+//! Harness for driving the router in integration tests
+//!
+//! The app runs on `Ctx::for_testing`: resources live in memory and permission
+//! checks are decided up front with `TestApp::decide`, or answered by a
+//! `MockFga` for tests that exercise the OpenFGA calls, so no Postgres or
+//! OpenFGA is needed.
+
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::{HeaderMap, Method, Request, StatusCode, header};
+use openfga_client::client::{
+    AuthorizationModel, BatchCheckRequest, BatchCheckResponse, BatchCheckSingleResult,
+    CheckRequest, CheckResponse, ExpandRequest, ExpandResponse, Leaf, ListObjectsRequest,
+    ListObjectsResponse, ListUsersRequest, ListUsersResponse, Node, ReadAssertionsRequest,
+    ReadAssertionsResponse, ReadAuthorizationModelRequest, ReadAuthorizationModelResponse,
+    ReadAuthorizationModelsRequest, ReadAuthorizationModelsResponse, ReadChangesRequest,
+    ReadChangesResponse, ReadRequest, ReadResponse, Tuple, TupleKey, Users, UsersetTree,
+    WriteAssertionsRequest, WriteAssertionsResponse, WriteRequest, WriteResponse,
+    batch_check_single_result, leaf, node,
+};
+use openfga_demo::context::Ctx;
+use openfga_demo::fga::{FgaApi, FgaResult};
+use openfga_demo::routes;
+use openfga_demo::store::Resource;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tonic::{Code, Status};
+use tower::ServiceExt;
+use tracing_subscriber::fmt::MakeWriter;
+use uuid::Uuid;
+
+/// Model ID reported by `MockFga`
+pub const MOCK_MODEL_ID: &str = "mock-model";
+
+/// Schema version of the model reported by `MockFga`
+pub const MOCK_SCHEMA_VERSION: &str = "1.1";
+
+/// OpenFGA double: checks are allowed for granted tuples and denied otherwise,
+/// reads, writes and expansions go to the granted tuples, calls the tests do
+/// not need fail as unimplemented
+#[derive(Default)]
+pub struct MockFga {
+    granted: Mutex<HashSet<(String, String, String)>>,
+    derived: Mutex<HashSet<(String, String, String)>>,
+    failure: Mutex<Option<Code>>,
+    write_failure: Mutex<Option<Code>>,
+    lose_write_response: Mutex<bool>,
+    listing_delay: Mutex<Option<Duration>>,
+    timeouts: Mutex<Vec<(&'static str, Option<Duration>)>>,
+    users: Mutex<Vec<(&'static str, String)>>,
+    /// Number of `Check` calls received
+    pub checks: AtomicUsize,
+    /// Number of `Read` calls received
+    pub reads: AtomicUsize,
+    /// Number of `Write` calls received
+    pub writes: AtomicUsize,
+    /// Number of `ListObjects` calls received
+    pub listings: AtomicUsize,
+    listings_in_flight: AtomicUsize,
+    /// Most `ListObjects` calls held by `delay_listings` at the same time
+    pub peak_listings: AtomicUsize,
+    /// Number of `BatchCheck` calls received
+    pub batch_checks: AtomicUsize,
+    /// Number of `ReadAuthorizationModel` calls received
+    pub model_reads: AtomicUsize,
+}
+
+impl MockFga {
+    /// Allow checks of `user` (e.g. `user:alice`) for `relation` on `object`
+    pub fn grant(&self, user: &str, relation: &str, object: &str) {
+        self.granted.lock().unwrap().insert((
+            user.to_string(),
+            relation.to_string(),
+            object.to_string(),
+        ));
+    }
+
+    /// Allow checks and listings of `user` for `relation` on `object` without
+    /// storing a tuple, as if the model derived it from a group or parent
+    pub fn derive(&self, user: &str, relation: &str, object: &str) {
+        self.derived.lock().unwrap().insert((
+            user.to_string(),
+            relation.to_string(),
+            object.to_string(),
+        ));
+    }
+
+    /// Whether the tuple is currently stored
+    pub fn has(&self, user: &str, relation: &str, object: &str) -> bool {
+        self.granted.lock().unwrap().contains(&(
+            user.to_string(),
+            relation.to_string(),
+            object.to_string(),
+        ))
+    }
+
+    /// Fail every call as if the server could not be reached
+    pub fn set_unavailable(&self) {
+        self.fail_with(Code::Unavailable);
+    }
+
+    /// Fail every call with the given gRPC code
+    pub fn fail_with(&self, code: Code) {
+        *self.failure.lock().unwrap() = Some(code);
+    }
+
+    /// Fail writes with the given gRPC code, other calls keep working
+    pub fn fail_writes_with(&self, code: Code) {
+        *self.write_failure.lock().unwrap() = Some(code);
+    }
+
+    /// Apply the next write but answer it as if the connection dropped
+    pub fn lose_next_write_response(&self) {
+        *self.lose_write_response.lock().unwrap() = true;
+    }
+
+    /// Hold every `ListObjects` call for `delay` before answering, so
+    /// concurrent requests overlap
+    pub fn delay_listings(&self, delay: Duration) {
+        *self.listing_delay.lock().unwrap() = Some(delay);
+    }
+
+    /// Stop failing calls injected with `fail_with`
+    pub fn recover(&self) {
+        *self.failure.lock().unwrap() = None;
+    }
+
+    /// Deadline the last `call` (e.g. `"Check"`) carried in its `grpc-timeout`
+    /// metadata
+    pub fn last_timeout(&self, call: &str) -> Option<Duration> {
+        self.timeouts
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == call)
+            .and_then(|(_, timeout)| *timeout)
+    }
+
+    fn record_timeout<T>(&self, call: &'static str, request: &tonic::Request<T>) {
+        let timeout = request
+            .metadata()
+            .get("grpc-timeout")
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_grpc_timeout);
+        self.timeouts.lock().unwrap().push((call, timeout));
+    }
+
+    fn record_user(&self, call: &'static str, user: &str) {
+        self.users.lock().unwrap().push((call, user.to_string()));
+    }
+
+    /// The `user` of every check, listing and written tuple received, with
+    /// the call that carried it, in arrival order
+    pub fn seen_users(&self) -> Vec<(&'static str, String)> {
+        self.users.lock().unwrap().clone()
+    }
+
+    /// Whether a check of the tuple passes, directly or derived
+    fn allows(&self, tuple: (String, String, String)) -> bool {
+        self.granted.lock().unwrap().contains(&tuple)
+            || self.derived.lock().unwrap().contains(&tuple)
+    }
+
+    fn reachable(&self) -> Result<(), Status> {
+        match *self.failure.lock().unwrap() {
+            Some(Code::Unavailable) => Err(Status::unavailable("connection refused")),
+            Some(code) => Err(Status::new(code, "injected failure")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parse a `grpc-timeout` value such as `250m` (milliseconds) or `2000000u`
+fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    let (amount, unit) = value.split_at(value.len().checked_sub(1)?);
+    let amount: u64 = amount.parse().ok()?;
+    Some(match unit {
+        "H" => Duration::from_secs(amount * 3600),
+        "M" => Duration::from_secs(amount * 60),
+        "S" => Duration::from_secs(amount),
+        "m" => Duration::from_millis(amount),
+        "u" => Duration::from_micros(amount),
+        "n" => Duration::from_nanos(amount),
+        _ => return None,
+    })
+}
+
+fn unimplemented<T>(call: &str) -> FgaResult<T> {
+    Err(Status::unimplemented(format!(
+        "MockFga does not implement {}",
+        call
+    )))
+}
+
+#[async_trait]
+impl FgaApi for MockFga {
+    async fn check(&self, request: tonic::Request<CheckRequest>) -> FgaResult<CheckResponse> {
+        self.checks.fetch_add(1, Ordering::SeqCst);
+        self.record_timeout("Check", &request);
+        self.reachable()?;
+        let key = request.into_inner().tuple_key.unwrap_or_default();
+        self.record_user("Check", &key.user);
+        let allowed = self.allows((key.user, key.relation, key.object));
+        Ok(tonic::Response::new(CheckResponse {
+            allowed,
+            ..Default::default()
+        }))
+    }
+
+    async fn batch_check(
+        &self,
+        request: tonic::Request<BatchCheckRequest>,
+    ) -> FgaResult<BatchCheckResponse> {
+        self.batch_checks.fetch_add(1, Ordering::SeqCst);
+        self.record_timeout("BatchCheck", &request);
+        self.reachable()?;
+        let result = request
+            .into_inner()
+            .checks
+            .into_iter()
+            .map(|item| {
+                let key = item.tuple_key.unwrap_or_default();
+                self.record_user("BatchCheck", &key.user);
+                let allowed = self.allows((key.user, key.relation, key.object));
+                let outcome = BatchCheckSingleResult {
+                    check_result: Some(batch_check_single_result::CheckResult::Allowed(allowed)),
+                };
+                (item.correlation_id, outcome)
+            })
+            .collect();
+        Ok(tonic::Response::new(BatchCheckResponse { result }))
+    }
+
+    /// Expand to a single leaf naming the users granted the relation directly
+    async fn expand(&self, request: tonic::Request<ExpandRequest>) -> FgaResult<ExpandResponse> {
+        self.reachable()?;
+        let key = request.into_inner().tuple_key.unwrap_or_default();
+        let mut users: Vec<String> = self
+            .granted
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, relation, object)| *relation == key.relation && *object == key.object)
+            .map(|(user, _, _)| user.clone())
+            .collect();
+        users.sort();
+        let root = Node {
+            name: format!("{}#{}", key.object, key.relation),
+            value: Some(node::Value::Leaf(Leaf {
+                value: Some(leaf::Value::Users(Users { users })),
+            })),
+        };
+        Ok(tonic::Response::new(ExpandResponse {
+            tree: Some(UsersetTree { root: Some(root) }),
+        }))
+    }
+
+    async fn list_objects(
+        &self,
+        request: tonic::Request<ListObjectsRequest>,
+    ) -> FgaResult<ListObjectsResponse> {
+        self.listings.fetch_add(1, Ordering::SeqCst);
+        self.record_timeout("ListObjects", &request);
+        let delay = *self.listing_delay.lock().unwrap();
+        if let Some(delay) = delay {
+            let in_flight = self.listings_in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak_listings.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(delay).await;
+            self.listings_in_flight.fetch_sub(1, Ordering::SeqCst);
+        }
+        self.reachable()?;
+        let request = request.into_inner();
+        self.record_user("ListObjects", &request.user);
+        let prefix = format!("{}:", request.r#type);
+        let granted = self.granted.lock().unwrap().clone();
+        let derived = self.derived.lock().unwrap().clone();
+        let mut objects: Vec<String> = granted
+            .union(&derived)
+            .filter(|(user, relation, object)| {
+                *user == request.user
+                    && *relation == request.relation
+                    && object.starts_with(&prefix)
+            })
+            .map(|(_, _, object)| object.clone())
+            .collect();
+        objects.sort();
+        Ok(tonic::Response::new(ListObjectsResponse { objects }))
+    }
+
+    async fn list_users(
+        &self,
+        _request: tonic::Request<ListUsersRequest>,
+    ) -> FgaResult<ListUsersResponse> {
+        unimplemented("ListUsers")
+    }
+
+    async fn read(&self, request: tonic::Request<ReadRequest>) -> FgaResult<ReadResponse> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        self.record_timeout("Read", &request);
+        self.reachable()?;
+        let filter = request.into_inner().tuple_key.unwrap_or_default();
+        // An object of just `type:` matches every object of that type
+        let object_matches = |object: &str| match filter.object.strip_suffix(':') {
+            Some(object_type) => object.split_once(':').map(|(t, _)| t) == Some(object_type),
+            None => filter.object.is_empty() || filter.object == object,
+        };
+        let mut matching: Vec<(String, String, String)> = self
+            .granted
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(user, relation, object)| {
+                (filter.user.is_empty() || filter.user == *user)
+                    && (filter.relation.is_empty() || filter.relation == *relation)
+                    && object_matches(object)
+            })
+            .cloned()
+            .collect();
+        matching.sort();
+        let tuples = matching
+            .into_iter()
+            .map(|(user, relation, object)| Tuple {
+                key: Some(TupleKey {
+                    user,
+                    relation,
+                    object,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .collect();
+        Ok(tonic::Response::new(ReadResponse {
+            tuples,
+            ..Default::default()
+        }))
+    }
+
+    async fn read_changes(
+        &self,
+        _request: tonic::Request<ReadChangesRequest>,
+    ) -> FgaResult<ReadChangesResponse> {
+        unimplemented("ReadChanges")
+    }
+
+    async fn write(&self, request: tonic::Request<WriteRequest>) -> FgaResult<WriteResponse> {
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        self.record_timeout("Write", &request);
+        self.reachable()?;
+        if let Some(code) = *self.write_failure.lock().unwrap() {
+            return Err(Status::new(code, "injected write failure"));
+        }
+        let request = request.into_inner();
+        let writes = request.writes.map(|w| w.tuple_keys).unwrap_or_default();
+        let deletes = request.deletes.map(|d| d.tuple_keys).unwrap_or_default();
+        for user in writes.iter().map(|key| &key.user) {
+            self.record_user("Write", user);
+        }
+        for user in deletes.iter().map(|key| &key.user) {
+            self.record_user("Write", user);
+        }
+
+        // Like OpenFGA, the whole write is rejected if any tuple cannot be applied
+        let mut granted = self.granted.lock().unwrap();
+        for key in &writes {
+            if granted.contains(&(key.user.clone(), key.relation.clone(), key.object.clone())) {
+                return Err(Status::invalid_argument(
+                    "cannot write a tuple which already exists",
+                ));
+            }
+        }
+        for key in &deletes {
+            if !granted.contains(&(key.user.clone(), key.relation.clone(), key.object.clone())) {
+                return Err(Status::invalid_argument(
+                    "cannot delete a tuple which does not exist",
+                ));
+            }
+        }
+        for key in writes {
+            granted.insert((key.user, key.relation, key.object));
+        }
+        for key in deletes {
+            granted.remove(&(key.user, key.relation, key.object));
+        }
+        if std::mem::take(&mut *self.lose_write_response.lock().unwrap()) {
+            return Err(Status::unavailable("connection reset"));
+        }
+        Ok(tonic::Response::new(WriteResponse::default()))
+    }
+
+    async fn read_assertions(
+        &self,
+        _request: tonic::Request<ReadAssertionsRequest>,
+    ) -> FgaResult<ReadAssertionsResponse> {
+        unimplemented("ReadAssertions")
+    }
+
+    async fn write_assertions(
+        &self,
+        _request: tonic::Request<WriteAssertionsRequest>,
+    ) -> FgaResult<WriteAssertionsResponse> {
+        unimplemented("WriteAssertions")
+    }
+
+    async fn read_authorization_model(
+        &self,
+        request: tonic::Request<ReadAuthorizationModelRequest>,
+    ) -> FgaResult<ReadAuthorizationModelResponse> {
+        self.model_reads.fetch_add(1, Ordering::SeqCst);
+        self.reachable()?;
+        Ok(tonic::Response::new(ReadAuthorizationModelResponse {
+            authorization_model: Some(AuthorizationModel {
+                id: request.into_inner().id,
+                schema_version: MOCK_SCHEMA_VERSION.to_string(),
+                ..Default::default()
+            }),
+        }))
+    }
+
+    async fn read_authorization_models(
+        &self,
+        _request: tonic::Request<ReadAuthorizationModelsRequest>,
+    ) -> FgaResult<ReadAuthorizationModelsResponse> {
+        self.reachable()?;
+        Ok(tonic::Response::new(ReadAuthorizationModelsResponse {
+            authorization_models: vec![AuthorizationModel {
+                id: MOCK_MODEL_ID.to_string(),
+                schema_version: MOCK_SCHEMA_VERSION.to_string(),
+                ..Default::default()
+            }],
+            continuation_token: String::new(),
+        }))
+    }
+}
+
+/// Log output collected from a test's subscriber
+#[derive(Clone, Default)]
+pub struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = CapturedLogs;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// A response with its body parsed as JSON, `Null` when empty
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Value,
+}
+
+impl TestResponse {
+    /// The `code` of an error response
+    pub fn error_code(&self) -> &str {
+        self.body["code"].as_str().unwrap_or_default()
+    }
+}
+
+pub struct TestApp {
+    pub ctx: Arc<Ctx>,
+}
+
+impl TestApp {
+    pub fn new() -> Self {
+        Self::with_ctx(Ctx::for_testing())
+    }
+
+    /// Build the app from a context adjusted by the test
+    pub fn with_ctx(ctx: Ctx) -> Self {
+        Self { ctx: Arc::new(ctx) }
+    }
+
+    /// Build the app with OpenFGA calls answered by the mock, against a
+    /// pinned `MOCK_MODEL_ID`
+    pub fn with_fga(fga: Arc<MockFga>) -> Self {
+        let mut ctx = Ctx::for_testing().with_fga_client(fga);
+        ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+        Self::with_ctx(ctx)
+    }
+
+    /// Answer the permission check of `user_id` for `relation` on `object`
+    pub fn decide(&self, user_id: &str, relation: &str, object: &str, allowed: bool) {
+        self.ctx.check_cache.insert(
+            (
+                user_id.to_string(),
+                relation.to_string(),
+                object.to_string(),
+            ),
+            allowed,
+        );
+    }
+
+    /// Store a resource directly, bypassing the create endpoint
+    pub async fn add_resource(&self, resource: Resource) {
+        self.ctx
+            .resources
+            .create(resource)
+            .await
+            .expect("resource is stored");
+    }
+
+    /// Send a request through a freshly built router
+    pub async fn send(&self, request: Request<Body>) -> TestResponse {
+        let response = routes::create_routes(self.ctx.clone())
+            .oneshot(request)
+            .await
+            .expect("router never fails");
+        let (parts, body) = response.into_parts();
+        let bytes = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .expect("response body is readable");
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()))
+        };
+        TestResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        }
+    }
+
+    /// GET as the given user, or without any identity for `None`
+    pub async fn get(&self, user_id: Option<&str>, path: &str) -> TestResponse {
+        self.send(
+            request(Method::GET, path, user_id)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+    }
+
+    /// Send a JSON body as the given user
+    pub async fn send_json(
+        &self,
+        method: Method,
+        path: &str,
+        user_id: &str,
+        body: Value,
+    ) -> TestResponse {
+        self.send(json_request(method, path, Some(user_id), &body))
+            .await
+    }
+}
+
+/// Start a request, authenticated through `X-User-Id` when a user is given
+pub fn request(method: Method, path: &str, user_id: Option<&str>) -> axum::http::request::Builder {
+    let builder = Request::builder().method(method).uri(path);
+    match user_id {
+        Some(user_id) => builder.header("x-user-id", user_id),
+        None => builder,
+    }
+}
+
+/// A complete request carrying a JSON body
+pub fn json_request(
+    method: Method,
+    path: &str,
+    user_id: Option<&str>,
+    body: &Value,
+) -> Request<Body> {
+    request(method, path, user_id)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// A resource in `org-1` with a fixed property
+pub fn sample_resource(name: &str) -> Resource {
+    Resource {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        service_name: "billing".to_string(),
+        service_type: "web".to_string(),
+        org_id: "org-1".to_string(),
+        properties: json!({ "tier": "gold" }),
+        version: 1,
+    }
+}
+
+/// API path of a resource created by `sample_resource`
+pub fn resource_path(name: &str) -> String {
+    format!("/api/resource/billing/web/org-1/{}", name)
+}
+
+/// FGA object of a resource created by `sample_resource`
+pub fn resource_object(name: &str) -> String {
+    format!("resource:billing/web/org-1/{}", name)
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use axum::http::{Method, StatusCode, header};
+use common::{MOCK_MODEL_ID, MockFga, TestApp, resource_object};
+use openfga_demo::concurrency::parse_route_limits;
+use openfga_demo::context::Ctx;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// App allowing one in-flight listing at a time, with listings answered slowly
+fn app(fga: Arc<MockFga>) -> TestApp {
+    fga.delay_listings(Duration::from_millis(300));
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.route_limits = parse_route_limits(&["/api/list-objects=1".to_string()]).unwrap();
+    TestApp::with_ctx(ctx)
+}
+
+#[tokio::test]
+async fn saturated_route_rejects_while_other_routes_serve() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    let app = app(fga);
+
+    let (first, (second, other)) =
+        tokio::join!(app.get(Some("alice"), "/api/list-objects"), async {
+            // Let the first listing take the only slot
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let second = app.get(Some("alice"), "/api/list-objects").await;
+            let other = app
+                .send_json(
+                    Method::POST,
+                    "/api/check",
+                    "alice",
+                    json!({ "relation": "viewer", "object": resource_object("report") }),
+                )
+                .await;
+            (second, other)
+        });
+
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(second.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(second.error_code(), "overloaded");
+    assert!(second.headers.contains_key(header::RETRY_AFTER));
+    assert_eq!(other.status, StatusCode::OK);
+    assert_eq!(other.body["allowed"], true);
+}
+
+#[tokio::test]
+async fn limited_route_serves_again_once_the_slot_frees() {
+    let app = app(Arc::new(MockFga::default()));
+
+    let first = app.get(Some("alice"), "/api/list-objects").await;
+    let second = app.get(Some("alice"), "/api/list-objects").await;
+
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(second.status, StatusCode::OK);
+}
+
+#[test]
+fn malformed_route_limits_are_rejected() {
+    for entry in [
+        "/api/list-objects",
+        "/api/list-objects=0",
+        "/api/list-objects=x",
+    ] {
+        assert!(
+            parse_route_limits(&[entry.to_string()]).is_err(),
+            "{}",
+            entry
+        );
+    }
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{MOCK_MODEL_ID, MockFga, TestApp, resource_object, sample_resource};
+use openfga_demo::context::Ctx;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Assert `list` is `{ items: [...], page: { size, next_token } }` holding `len` items
+fn assert_envelope(list: &Value, len: usize, endpoint: &str) {
+    let items = list["items"]
+        .as_array()
+        .unwrap_or_else(|| panic!("{}: items is not an array in {}", endpoint, list));
+    assert_eq!(items.len(), len, "{}", endpoint);
+    assert_eq!(list["page"]["size"], len, "{}", endpoint);
+    assert!(
+        list["page"].get("next_token").is_some_and(Value::is_null),
+        "{}: {}",
+        endpoint,
+        list
+    );
+}
+
+/// App where `alice` views the stored report and `root` is admin
+async fn app(fga: Arc<MockFga>) -> TestApp {
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.admin.user_ids = vec!["root".to_string()];
+    let app = TestApp::with_ctx(ctx);
+    app.add_resource(sample_resource("report")).await;
+    app
+}
+
+const LISTS: [&str; 3] = [
+    "/api/list-objects?relation=viewer",
+    "/api/resources?org_id=org-1",
+    "/api/resources/with-source?relation=viewer",
+];
+
+#[tokio::test]
+async fn empty_lists_use_the_standard_envelope() {
+    let app = app(Arc::new(MockFga::default())).await;
+
+    for endpoint in LISTS {
+        let response = app.get(Some("alice"), endpoint).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", endpoint);
+        assert_envelope(&response.body, 0, endpoint);
+    }
+
+    let endpoint = "/api/admin/users/alice/objects";
+    let response = app.get(Some("root"), endpoint).await;
+    assert_envelope(&response.body, 0, endpoint);
+
+    let response = app.get(Some("alice"), "/api/shared-resources").await;
+    for section in ["services", "service_types", "resources"] {
+        assert_envelope(&response.body[section], 0, section);
+    }
+}
+
+#[tokio::test]
+async fn non_empty_lists_use_the_standard_envelope() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    let app = app(fga).await;
+
+    for endpoint in LISTS {
+        let response = app.get(Some("alice"), endpoint).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", endpoint);
+        assert_envelope(&response.body, 1, endpoint);
+    }
+
+    let endpoint = "/api/admin/users/alice/objects";
+    let response = app.get(Some("root"), endpoint).await;
+    assert_envelope(&response.body, 1, endpoint);
+
+    let response = app.get(Some("alice"), "/api/shared-resources").await;
+    assert_envelope(&response.body["resources"], 1, "resources");
+}
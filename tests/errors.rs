@@ -0,0 +1,190 @@
+mod common;
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode, header};
+use common::{
+    MOCK_MODEL_ID, MockFga, TestApp, json_request, request, resource_object, resource_path,
+    sample_resource,
+};
+use openfga_demo::context::Ctx;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// App where `alice` administers `org-1` and may view and edit the report,
+/// while `bob` holds nothing
+async fn app() -> TestApp {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "admin", "org:org-1");
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    fga.grant("user:alice", "editor", &resource_object("report"));
+    fga.grant("user:alice", "viewer", &resource_object("missing"));
+    let app = TestApp::with_fga(fga);
+    app.add_resource(sample_resource("report")).await;
+    app
+}
+
+fn empty(method: Method, path: &str, user_id: Option<&str>) -> Request<Body> {
+    request(method, path, user_id).body(Body::empty()).unwrap()
+}
+
+fn if_match(mut request: Request<Body>, version: &str) -> Request<Body> {
+    request
+        .headers_mut()
+        .insert(header::IF_MATCH, version.parse().unwrap());
+    request
+}
+
+#[tokio::test]
+async fn handlers_report_stable_error_codes() {
+    let app = app().await;
+    let report = resource_path("report");
+    let update = json!({ "properties": { "tier": "silver" } });
+    let cases = [
+        (
+            "get_resource without identity",
+            empty(Method::GET, &report, None),
+            StatusCode::UNAUTHORIZED,
+            "unauthenticated",
+        ),
+        (
+            "get_resource with malformed user ID",
+            empty(Method::GET, &report, Some("not valid")),
+            StatusCode::BAD_REQUEST,
+            "invalid_user_id",
+        ),
+        (
+            "get_resource without viewer",
+            empty(Method::GET, &report, Some("bob")),
+            StatusCode::FORBIDDEN,
+            "forbidden",
+        ),
+        (
+            "get_resource of a missing resource",
+            empty(Method::GET, &resource_path("missing"), Some("alice")),
+            StatusCode::NOT_FOUND,
+            "not_found",
+        ),
+        (
+            "create_resource of an existing resource",
+            json_request(Method::POST, &report, Some("alice"), &json!({})),
+            StatusCode::CONFLICT,
+            "conflict",
+        ),
+        (
+            "update_resource without If-Match",
+            json_request(Method::PUT, &report, Some("alice"), &update),
+            StatusCode::PRECONDITION_REQUIRED,
+            "precondition_required",
+        ),
+        (
+            "update_resource with a stale If-Match",
+            if_match(
+                json_request(Method::PUT, &report, Some("alice"), &update),
+                "\"9\"",
+            ),
+            StatusCode::PRECONDITION_FAILED,
+            "precondition_failed",
+        ),
+        (
+            "can with malformed JSON",
+            request(Method::POST, "/api/check", Some("alice"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from("{"))
+                .unwrap(),
+            StatusCode::BAD_REQUEST,
+            "invalid_json",
+        ),
+        (
+            "can without a JSON content type",
+            request(Method::POST, "/api/check", Some("alice"))
+                .header(header::CONTENT_TYPE, "text/plain")
+                .body(Body::from("{}"))
+                .unwrap(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "unsupported_media_type",
+        ),
+        (
+            "grant_permission with a malformed user",
+            json_request(
+                Method::POST,
+                "/api/permissions",
+                Some("alice"),
+                &json!({
+                    "user": "nobody",
+                    "relation": "viewer",
+                    "object": resource_object("report")
+                }),
+            ),
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+        ),
+        (
+            "get_stats without admin",
+            empty(Method::GET, "/api/admin/stats", Some("bob")),
+            StatusCode::FORBIDDEN,
+            "forbidden",
+        ),
+        (
+            "unknown route",
+            empty(Method::GET, "/api/nothing-here", Some("alice")),
+            StatusCode::NOT_FOUND,
+            "not_found",
+        ),
+        (
+            "can with the wrong method",
+            empty(Method::PATCH, "/api/check", Some("alice")),
+            StatusCode::METHOD_NOT_ALLOWED,
+            "method_not_allowed",
+        ),
+    ];
+
+    for (case, request, status, code) in cases {
+        let response = app.send(request).await;
+
+        assert_eq!(response.status, status, "{}: {}", case, response.body);
+        assert_eq!(response.error_code(), code, "{}", case);
+        assert!(response.body["message"].is_string(), "{}", case);
+    }
+}
+
+#[tokio::test]
+async fn request_timeout_has_the_standard_error_body() {
+    let fga = Arc::new(MockFga::default());
+    fga.delay_listings(Duration::from_millis(300));
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.request_timeout = Duration::from_millis(50);
+    let app = TestApp::with_ctx(ctx);
+
+    let response = app.get(Some("alice"), "/api/list-objects").await;
+
+    assert_eq!(response.status, StatusCode::REQUEST_TIMEOUT);
+    assert_eq!(response.error_code(), "timeout");
+    assert!(response.body["message"].is_string(), "{}", response.body);
+    assert!(response.body["request_id"].is_string(), "{}", response.body);
+}
+
+#[tokio::test]
+async fn oversized_body_has_the_standard_error_body() {
+    let mut ctx = Ctx::for_testing();
+    ctx.request_body_limit = 16;
+    let app = TestApp::with_ctx(ctx);
+    let body = json!({ "properties": { "tier": "a value longer than the limit" } }).to_string();
+    // A declared length over the limit is rejected before the body is read
+    let request = request(Method::POST, &resource_path("report"), Some("alice"))
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::CONTENT_LENGTH, body.len())
+        .body(Body::from(body))
+        .unwrap();
+
+    let response = app.send(request).await;
+
+    assert_eq!(response.status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(response.error_code(), "payload_too_large");
+    assert_eq!(
+        response.body["message"],
+        "Request body exceeds the limit of 16 bytes"
+    );
+    assert!(response.body["request_id"].is_string(), "{}", response.body);
+}
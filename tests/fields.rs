@@ -0,0 +1,97 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{MOCK_MODEL_ID, MockFga, TestApp, resource_object, resource_path, sample_resource};
+use openfga_demo::context::Ctx;
+use serde_json::json;
+use std::sync::Arc;
+
+/// App where `alice` views the stored report, rejecting unknown fields when `strict`
+async fn app(strict: bool) -> TestApp {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.strict_fields = strict;
+    let app = TestApp::with_ctx(ctx);
+    app.add_resource(sample_resource("report")).await;
+    app
+}
+
+#[tokio::test]
+async fn fields_project_the_response_to_the_named_keys() {
+    let app = app(false).await;
+    let path = format!("{}?fields=name, version", resource_path("report")).replace(' ', "%20");
+
+    let response = app.get(Some("alice"), &path).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body, json!({ "name": "report", "version": 1 }));
+}
+
+#[tokio::test]
+async fn empty_fields_leave_the_response_untouched() {
+    let app = app(false).await;
+    let full = app.get(Some("alice"), &resource_path("report")).await;
+
+    let path = format!("{}?fields=,", resource_path("report"));
+    let response = app.get(Some("alice"), &path).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body, full.body);
+}
+
+#[tokio::test]
+async fn unknown_fields_are_ignored_by_default() {
+    let app = app(false).await;
+    let path = format!("{}?fields=name,colour", resource_path("report"));
+
+    let response = app.get(Some("alice"), &path).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body, json!({ "name": "report" }));
+}
+
+#[tokio::test]
+async fn unknown_fields_are_rejected_when_strict() {
+    let app = app(true).await;
+    let path = format!("{}?fields=name,colour", resource_path("report"));
+
+    let response = app.get(Some("alice"), &path).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_code(), "validation_failed");
+    assert_eq!(response.body["message"], "Unknown field 'colour'");
+}
+
+#[tokio::test]
+async fn error_responses_are_not_projected() {
+    let app = app(true).await;
+    let path = format!("{}?fields=name", resource_path("missing"));
+
+    let response = app.get(Some("alice"), &path).await;
+
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert!(response.body.get("code").is_some());
+    assert!(response.body.get("message").is_some());
+}
+
+#[tokio::test]
+async fn only_get_requests_are_projected() {
+    let app = app(false).await;
+    let response = app
+        .send_json(
+            Method::POST,
+            "/api/check?fields=allowed",
+            "alice",
+            json!({ "relation": "viewer", "object": resource_object("report") }),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(
+        response.body.as_object().unwrap().len() > 1,
+        "{}",
+        response.body
+    );
+}
@@ -0,0 +1,116 @@
+use openfga_demo::context::Ctx;
+use openfga_demo::listener::{self, ServerConfig};
+use openfga_demo::routes;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A loopback address with a port that was free a moment ago
+async fn free_addr() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.local_addr().unwrap()
+}
+
+/// Serve the app with `config` and return the raw HTTP/1.1 response to
+/// `GET /health`
+async fn health_over(config: ServerConfig) -> String {
+    let addr = free_addr().await;
+    let app = routes::create_routes(Arc::new(Ctx::for_testing()));
+    let server = tokio::spawn(listener::serve(app, addr, config));
+
+    let mut stream = None;
+    for _ in 0..50 {
+        match TcpStream::connect(addr).await {
+            Ok(connected) => {
+                stream = Some(connected);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+    let mut stream = stream.expect("server accepts connections");
+
+    stream
+        .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_string(&mut response))
+        .await
+        .expect("response arrives in time")
+        .unwrap();
+
+    server.abort();
+    response
+}
+
+#[tokio::test]
+async fn server_serves_with_default_settings() {
+    let response = health_over(ServerConfig::default()).await;
+
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.contains("healthy"), "{}", response);
+}
+
+#[tokio::test]
+async fn server_serves_with_custom_connection_settings() {
+    let config = ServerConfig {
+        tcp_nodelay: true,
+        tcp_keepalive: Some(Duration::from_secs(30)),
+        http2: true,
+        http2_max_concurrent_streams: Some(64),
+        keepalive_interval: Some(Duration::from_secs(10)),
+        keepalive_timeout: Some(Duration::from_secs(5)),
+        shutdown_timeout: Duration::from_secs(1),
+    };
+
+    let response = health_over(config).await;
+
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.contains("healthy"), "{}", response);
+}
+
+#[tokio::test]
+async fn server_serves_http1_only_with_tcp_settings() {
+    let config = ServerConfig {
+        tcp_nodelay: true,
+        tcp_keepalive: Some(Duration::from_secs(30)),
+        ..ServerConfig::default()
+    };
+
+    let response = health_over(config).await;
+
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+}
+
+#[tokio::test]
+async fn address_of_another_host_fails_with_a_clear_error() {
+    // 192.0.2.0/24 is reserved for documentation and never assigned locally
+    let addr: SocketAddr = "192.0.2.1:5001".parse().unwrap();
+    let app = routes::create_routes(Arc::new(Ctx::for_testing()));
+
+    let error = listener::serve(app, addr, ServerConfig::default())
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::AddrNotAvailable);
+    assert!(error.to_string().contains("192.0.2.1:5001"), "{}", error);
+    assert!(error.to_string().contains("BIND_ADDR"), "{}", error);
+}
+
+#[tokio::test]
+async fn port_in_use_fails_with_a_clear_error() {
+    let taken = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = taken.local_addr().unwrap();
+    let app = routes::create_routes(Arc::new(Ctx::for_testing()));
+
+    let error = listener::serve(app, addr, ServerConfig::default())
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::AddrInUse);
+    assert!(error.to_string().contains("PORT"), "{}", error);
+}
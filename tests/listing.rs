@@ -0,0 +1,218 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{MOCK_MODEL_ID, MockFga, TestApp, resource_object};
+use openfga_demo::context::Ctx;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+#[tokio::test]
+async fn concurrent_identical_listings_share_one_upstream_call() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    fga.delay_listings(Duration::from_millis(100));
+    let app = TestApp::with_fga(fga.clone());
+    let path = "/api/list-objects?relation=viewer&object_type=resource";
+
+    let (first, second) = tokio::join!(app.get(Some("alice"), path), app.get(Some("alice"), path));
+
+    assert_eq!(fga.listings.load(Ordering::SeqCst), 1);
+    assert_eq!(first.status, second.status);
+    assert_eq!(first.body["items"], second.body["items"]);
+    assert_eq!(first.body["items"][0], resource_object("report"));
+}
+
+#[tokio::test]
+async fn coalescing_is_not_a_cache() {
+    let fga = Arc::new(MockFga::default());
+    let app = TestApp::with_fga(fga.clone());
+    let path = "/api/list-objects?relation=viewer&object_type=resource";
+
+    app.get(Some("alice"), path).await;
+    app.get(Some("alice"), path).await;
+
+    assert_eq!(fga.listings.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn different_listings_are_not_coalesced() {
+    let fga = Arc::new(MockFga::default());
+    fga.delay_listings(Duration::from_millis(100));
+    let app = TestApp::with_fga(fga.clone());
+
+    tokio::join!(
+        app.get(Some("alice"), "/api/list-objects?relation=viewer"),
+        app.get(Some("bob"), "/api/list-objects?relation=viewer"),
+        app.get(Some("alice"), "/api/list-objects?relation=editor")
+    );
+
+    assert_eq!(fga.listings.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn listed_objects_are_classified_as_direct_or_inherited() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("direct"));
+    fga.derive("user:alice", "viewer", &resource_object("inherited"));
+    let app = TestApp::with_fga(fga);
+
+    let response = app
+        .get(Some("alice"), "/api/resources/with-source?relation=viewer")
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body["items"],
+        json!([
+            { "object": resource_object("direct"), "source": "direct" },
+            { "object": resource_object("inherited"), "source": "inherited" }
+        ])
+    );
+    assert_eq!(response.body["classified_count"], 2);
+    assert_eq!(response.body["classification_truncated"], false);
+}
+
+#[tokio::test]
+async fn classification_stops_at_its_cap() {
+    let fga = Arc::new(MockFga::default());
+    for index in 0..101 {
+        fga.derive(
+            "user:alice",
+            "viewer",
+            &resource_object(&format!("report-{:03}", index)),
+        );
+    }
+    let app = TestApp::with_fga(fga.clone());
+
+    let response = app
+        .get(Some("alice"), "/api/resources/with-source?relation=viewer")
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["classified_count"], 100);
+    assert_eq!(response.body["classification_truncated"], true);
+    assert_eq!(response.body["items"][99]["source"], "inherited");
+    assert!(response.body["items"][100]["source"].is_null());
+    assert_eq!(fga.reads.load(Ordering::SeqCst), 100);
+}
+
+#[tokio::test]
+async fn summary_counts_objects_per_relation() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "owner", &resource_object("report"));
+    fga.grant("user:alice", "editor", &resource_object("report"));
+    fga.grant("user:alice", "editor", &resource_object("invoice"));
+    fga.derive("user:alice", "viewer", &resource_object("report"));
+    fga.derive("user:alice", "viewer", &resource_object("invoice"));
+    fga.derive("user:alice", "viewer", &resource_object("budget"));
+    fga.grant("user:bob", "admin", &resource_object("report"));
+    let app = TestApp::with_fga(fga.clone());
+
+    let response = app
+        .get(Some("alice"), "/api/resources/summary?type=resource")
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["object_type"], "resource");
+    assert_eq!(
+        response.body["counts"],
+        json!({ "owner": 1, "admin": 0, "editor": 2, "viewer": 3 })
+    );
+    assert_eq!(response.body["capped"], json!([]));
+    assert_eq!(fga.listings.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn summary_caps_large_counts() {
+    let fga = Arc::new(MockFga::default());
+    for index in 0..1001 {
+        fga.derive(
+            "user:alice",
+            "viewer",
+            &resource_object(&format!("report-{:04}", index)),
+        );
+    }
+    let app = TestApp::with_fga(fga);
+
+    let response = app.get(Some("alice"), "/api/resources/summary").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["counts"]["viewer"], 1000);
+    assert_eq!(response.body["capped"], json!(["viewer"]));
+}
+
+#[tokio::test]
+async fn summary_of_unknown_type_is_not_found() {
+    let app = TestApp::with_fga(Arc::new(MockFga::default()));
+
+    let response = app
+        .get(Some("alice"), "/api/resources/summary?type=widget")
+        .await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+/// App with a model of resources, documents and folders
+fn multi_type_app(fga: Arc<MockFga>) -> TestApp {
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.fga_config.relations_by_type = BTreeMap::from([
+        (
+            "resource".to_string(),
+            vec!["viewer".to_string(), "editor".to_string()],
+        ),
+        (
+            "document".to_string(),
+            vec!["reader".to_string(), "writer".to_string()],
+        ),
+        ("folder".to_string(), vec!["viewer".to_string()]),
+    ]);
+    TestApp::with_ctx(ctx)
+}
+
+#[tokio::test]
+async fn all_access_is_grouped_by_type_with_matched_relations() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    fga.grant("user:alice", "editor", &resource_object("report"));
+    fga.grant("user:alice", "reader", "document:spec");
+    fga.derive("user:alice", "writer", "document:plan");
+    fga.grant("user:bob", "viewer", "folder:shared");
+    let app = multi_type_app(fga.clone());
+
+    let response = app.get(Some("alice"), "/api/access/all").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body["types"],
+        json!({
+            "document": [
+                { "object": "document:plan", "relations": ["writer"] },
+                { "object": "document:spec", "relations": ["reader"] }
+            ],
+            "folder": [],
+            "resource": [
+                { "object": resource_object("report"), "relations": ["editor", "viewer"] }
+            ]
+        })
+    );
+    assert_eq!(response.body["truncated"], false);
+    assert_eq!(response.body["partial"], false);
+    assert_eq!(fga.listings.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn all_access_reports_failed_lookups_as_partial() {
+    let fga = Arc::new(MockFga::default());
+    fga.set_unavailable();
+    let app = multi_type_app(fga);
+
+    let response = app.get(Some("alice"), "/api/access/all").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["types"], json!({}));
+    assert_eq!(response.body["partial"], true);
+}
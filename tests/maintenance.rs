@@ -0,0 +1,101 @@
+mod common;
+
+use axum::http::{Method, StatusCode, header};
+use common::{TestApp, resource_object, resource_path, sample_resource};
+use openfga_demo::context::Ctx;
+use openfga_demo::maintenance::{ReadOnlyWindow, parse_read_only_windows};
+use serde_json::json;
+use time::{Duration, OffsetDateTime};
+
+/// App with a read-only window from `start` to `end` relative to now
+async fn app_with_window(start: Duration, end: Duration) -> TestApp {
+    let now = OffsetDateTime::now_utc();
+    let mut ctx = Ctx::for_testing();
+    ctx.read_only_windows = vec![ReadOnlyWindow {
+        start: now + start,
+        end: now + end,
+    }];
+    let app = TestApp::with_ctx(ctx);
+    app.add_resource(sample_resource("report")).await;
+    app.decide("alice", "viewer", &resource_object("report"), true);
+    app.decide("alice", "editor", &resource_object("report"), true);
+    app
+}
+
+#[tokio::test]
+async fn mutations_are_rejected_inside_the_window() {
+    let app = app_with_window(Duration::minutes(-5), Duration::minutes(10)).await;
+
+    let response = app
+        .send_json(
+            Method::PUT,
+            &resource_path("report"),
+            "alice",
+            json!({ "properties": {} }),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.error_code(), "read_only");
+    let retry_after: i64 = response.headers[header::RETRY_AFTER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((590..=600).contains(&retry_after), "{}", retry_after);
+
+    let response = app.get(Some("alice"), &resource_path("report")).await;
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn mutations_pass_outside_the_window() {
+    for (start, end) in [
+        (Duration::minutes(-10), Duration::minutes(-5)),
+        (Duration::minutes(5), Duration::minutes(10)),
+    ] {
+        let app = app_with_window(start, end).await;
+
+        // Reaches the handler, which asks for If-Match
+        let response = app
+            .send_json(
+                Method::PUT,
+                &resource_path("report"),
+                "alice",
+                json!({ "properties": {} }),
+            )
+            .await;
+
+        assert_eq!(response.status, StatusCode::PRECONDITION_REQUIRED);
+    }
+}
+
+#[test]
+fn windows_are_parsed_with_their_offsets() {
+    let windows = parse_read_only_windows(
+        "2026-01-10T02:00:00+01:00/2026-01-10T04:00:00+01:00, \
+         2026-02-01T00:00:00Z/2026-02-01T01:00:00Z",
+    )
+    .unwrap();
+
+    assert_eq!(windows.len(), 2);
+    let inside = OffsetDateTime::parse(
+        "2026-01-10T01:30:00Z",
+        &time::format_description::well_known::Rfc3339,
+    )
+    .unwrap();
+    assert!(windows[0].contains(inside));
+    assert!(!windows[0].contains(windows[0].end));
+    assert!(windows[0].contains(windows[0].start));
+}
+
+#[test]
+fn malformed_windows_are_rejected() {
+    for value in [
+        "2026-01-10T02:00:00Z",
+        "2026-01-10T04:00:00Z/2026-01-10T02:00:00Z",
+        "yesterday/today",
+    ] {
+        assert!(parse_read_only_windows(value).is_err(), "{}", value);
+    }
+}
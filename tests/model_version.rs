@@ -0,0 +1,92 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{
+    MOCK_MODEL_ID, MOCK_SCHEMA_VERSION, MockFga, TestApp, resource_object, resource_path,
+};
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+#[tokio::test]
+async fn check_responses_carry_the_pinned_model_version() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    let app = TestApp::with_fga(fga);
+
+    let response = app
+        .send_json(
+            Method::POST,
+            "/api/check",
+            "alice",
+            json!({ "relation": "viewer", "object": resource_object("report") }),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.headers["x-fga-model-version"], MOCK_SCHEMA_VERSION);
+    assert_eq!(response.headers["x-fga-model-id"], MOCK_MODEL_ID);
+}
+
+#[tokio::test]
+async fn list_responses_carry_the_pinned_model_version() {
+    let app = TestApp::with_fga(Arc::new(MockFga::default()));
+
+    let response = app.get(Some("alice"), "/api/list-objects").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.headers["x-fga-model-version"], MOCK_SCHEMA_VERSION);
+    assert_eq!(response.headers["x-fga-model-id"], MOCK_MODEL_ID);
+}
+
+#[tokio::test]
+async fn type_relations_report_the_model_in_the_body() {
+    let app = TestApp::with_fga(Arc::new(MockFga::default()));
+
+    let response = app
+        .get(Some("alice"), "/api/types/resource/relations")
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body["model"],
+        json!({ "id": MOCK_MODEL_ID, "schema_version": MOCK_SCHEMA_VERSION })
+    );
+}
+
+#[tokio::test]
+async fn the_model_is_read_once_across_requests() {
+    let fga = Arc::new(MockFga::default());
+    let app = TestApp::with_fga(fga.clone());
+
+    for _ in 0..3 {
+        let response = app.get(Some("alice"), "/api/list-objects").await;
+        assert_eq!(response.headers["x-fga-model-version"], MOCK_SCHEMA_VERSION);
+    }
+
+    assert_eq!(fga.model_reads.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn error_responses_omit_the_model_version() {
+    let app = TestApp::with_fga(Arc::new(MockFga::default()));
+
+    let response = app.get(Some("alice"), &resource_path("missing")).await;
+
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert!(!response.headers.contains_key("x-fga-model-version"));
+}
+
+#[tokio::test]
+async fn unpinned_models_omit_the_model_version() {
+    let mut ctx =
+        openfga_demo::context::Ctx::for_testing().with_fga_client(Arc::new(MockFga::default()));
+    ctx.fga_config.authorization_model_id = None;
+    let app = TestApp::with_ctx(ctx);
+
+    let response = app.get(Some("alice"), "/api/list-objects").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(!response.headers.contains_key("x-fga-model-version"));
+    assert!(!response.headers.contains_key("x-fga-model-id"));
+}
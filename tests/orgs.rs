@@ -0,0 +1,317 @@
+mod common;
+
+use axum::body::Body;
+use axum::http::{Method, StatusCode, header};
+use common::{
+    MOCK_MODEL_ID, MockFga, TestApp, json_request, request, resource_object, resource_path,
+    sample_resource,
+};
+use openfga_demo::cache::TtlCache;
+use openfga_demo::context::Ctx;
+use openfga_demo::store::ORGANISATION_REPARENTED;
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// Mock where alice owns `org-1`, bob administers it and carol is a member;
+/// higher roles also hold the lower ones, as in the model
+fn org_fga() -> Arc<MockFga> {
+    let fga = Arc::new(MockFga::default());
+    for (user, roles) in [
+        ("user:alice", &["owner", "admin", "member"][..]),
+        ("user:bob", &["admin", "member"][..]),
+        ("user:carol", &["member"][..]),
+    ] {
+        for role in roles {
+            fga.grant(user, role, "org:org-1");
+        }
+    }
+    fga
+}
+
+fn org_app(fga: Arc<MockFga>, hide_unknown_orgs: bool) -> TestApp {
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.org_roles = ["owner", "admin", "member"].map(String::from).to_vec();
+    ctx.hide_unknown_orgs = hide_unknown_orgs;
+    TestApp::with_ctx(ctx)
+}
+
+#[tokio::test]
+async fn my_role_reports_the_highest_role_held() {
+    let app = org_app(org_fga(), false);
+
+    for (user, role) in [
+        ("alice", "owner"),
+        ("bob", "admin"),
+        ("carol", "member"),
+        ("dave", "none"),
+    ] {
+        let response = app
+            .get(Some(user), "/api/organizations/org-1/my-role")
+            .await;
+
+        assert_eq!(response.status, StatusCode::OK, "{}", user);
+        assert_eq!(response.body["org_id"], "org-1");
+        assert_eq!(response.body["role"], role, "{}", user);
+    }
+}
+
+#[tokio::test]
+async fn hidden_organisations_are_not_found_without_a_role() {
+    let app = org_app(org_fga(), true);
+
+    let response = app
+        .get(Some("dave"), "/api/organizations/org-1/my-role")
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.error_code(), "not_found");
+
+    let response = app
+        .get(Some("carol"), "/api/organizations/org-1/my-role")
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["role"], "member");
+}
+
+/// App storing the report, with alice a member of `org-1` and bob not, both
+/// holding every relation on the report directly
+async fn membership_app(enforce_org_membership: bool) -> TestApp {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "member", "org:org-1");
+    for user in ["user:alice", "user:bob"] {
+        for relation in ["owner", "admin", "editor", "viewer"] {
+            fga.grant(user, relation, &resource_object("report"));
+        }
+    }
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.enforce_org_membership = enforce_org_membership;
+    let app = TestApp::with_ctx(ctx);
+    app.add_resource(sample_resource("report")).await;
+    app
+}
+
+#[tokio::test]
+async fn resource_access_requires_org_membership_when_enforced() {
+    let app = membership_app(true).await;
+    let path = resource_path("report");
+    let mut update = json_request(
+        Method::PUT,
+        &path,
+        Some("bob"),
+        &json!({ "properties": {} }),
+    );
+    update
+        .headers_mut()
+        .insert(header::IF_MATCH, "\"1\"".parse().unwrap());
+    let requests = [
+        request(Method::GET, &path, Some("bob"))
+            .body(Body::empty())
+            .unwrap(),
+        update,
+        request(Method::DELETE, &path, Some("bob"))
+            .body(Body::empty())
+            .unwrap(),
+    ];
+
+    for request in requests {
+        let method = request.method().clone();
+        let response = app.send(request).await;
+
+        assert_eq!(response.status, StatusCode::FORBIDDEN, "{}", method);
+        assert_eq!(response.error_code(), "org_mismatch", "{}", method);
+    }
+
+    let response = app.get(Some("alice"), &path).await;
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn org_membership_is_not_required_by_default() {
+    let app = membership_app(false).await;
+
+    let response = app.get(Some("bob"), &resource_path("report")).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+/// App with `root` as admin and caching enabled, where `acme` sits under `old`
+fn reparent_app(fga: Arc<MockFga>) -> TestApp {
+    fga.grant("organisation:acme", "child", "organisation:old");
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.admin.user_ids = vec!["root".to_string()];
+    ctx.shared_resources_cache = Arc::new(TtlCache::new(Duration::from_secs(60)));
+    TestApp::with_ctx(ctx)
+}
+
+async fn reparent(app: &TestApp, caller: &str, path: &str, parent: &str) -> common::TestResponse {
+    app.send_json(
+        Method::POST,
+        path,
+        caller,
+        json!({ "parent_org_id": parent }),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn reparent_swaps_the_parent_tuple_and_drops_caches() {
+    let fga = Arc::new(MockFga::default());
+    let app = reparent_app(fga.clone());
+    app.decide("alice", "viewer", &resource_object("report"), true);
+    app.ctx
+        .shared_resources_cache
+        .insert("alice".to_string(), json!({}));
+
+    let response = reparent(
+        &app,
+        "root",
+        "/api/admin/organizations/acme/reparent",
+        "new",
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body,
+        json!({
+            "organisation": "organisation:acme",
+            "parent": "organisation:new",
+            "previous_parents": ["organisation:old"],
+            "changed": true,
+            "invalidated_cache_entries": 2
+        })
+    );
+    assert!(fga.has("organisation:acme", "child", "organisation:new"));
+    assert!(!fga.has("organisation:acme", "child", "organisation:old"));
+    assert_eq!(fga.writes.load(Ordering::SeqCst), 1);
+    assert!(
+        app.ctx
+            .shared_resources_cache
+            .get(&"alice".to_string())
+            .is_none()
+    );
+    let key = (
+        "alice".to_string(),
+        "viewer".to_string(),
+        resource_object("report"),
+    );
+    assert!(app.ctx.check_cache.get(&key).is_none());
+}
+
+#[tokio::test]
+async fn reparent_under_the_current_parent_changes_nothing() {
+    let fga = Arc::new(MockFga::default());
+    let app = reparent_app(fga.clone());
+    app.decide("alice", "viewer", &resource_object("report"), true);
+
+    let response = reparent(
+        &app,
+        "root",
+        "/api/admin/organizations/acme/reparent",
+        "old",
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["changed"], false);
+    assert_eq!(fga.writes.load(Ordering::SeqCst), 0);
+    assert_eq!(app.ctx.check_cache.clear(), 1);
+    assert!(
+        app.ctx
+            .resources
+            .pending_events(10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn reparent_records_an_outbox_event() {
+    let app = reparent_app(Arc::new(MockFga::default()));
+
+    let response = reparent(
+        &app,
+        "root",
+        "/api/admin/organizations/acme/reparent",
+        "new",
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let events = app.ctx.resources.pending_events(10).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, ORGANISATION_REPARENTED);
+    assert_eq!(events[0].resource_id, None);
+    assert_eq!(
+        events[0].payload,
+        json!({
+            "organisation": "organisation:acme",
+            "parent": "organisation:new",
+            "previous_parents": ["organisation:old"],
+            "changed_by": "root"
+        })
+    );
+}
+
+#[tokio::test]
+async fn reparent_dry_run_leaves_the_tuples_alone() {
+    let fga = Arc::new(MockFga::default());
+    let app = reparent_app(fga.clone());
+
+    let response = reparent(
+        &app,
+        "root",
+        "/api/admin/organizations/acme/reparent?dry_run=true",
+        "new",
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(fga.writes.load(Ordering::SeqCst), 0);
+    assert!(fga.has("organisation:acme", "child", "organisation:old"));
+    assert!(
+        app.ctx
+            .resources
+            .pending_events(10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn reparent_under_itself_is_rejected() {
+    let app = reparent_app(Arc::new(MockFga::default()));
+
+    let response = reparent(
+        &app,
+        "root",
+        "/api/admin/organizations/acme/reparent",
+        "acme",
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn reparent_is_admin_only() {
+    let fga = Arc::new(MockFga::default());
+    let app = reparent_app(fga.clone());
+
+    let response = reparent(
+        &app,
+        "alice",
+        "/api/admin/organizations/acme/reparent",
+        "new",
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert_eq!(fga.writes.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{MockFga, TestApp, resource_path, sample_resource};
+use openfga_demo::outbox;
+use openfga_demo::store::{RESOURCE_CREATED, RESOURCE_DELETED, RESOURCE_UPDATED};
+use serde_json::json;
+use std::sync::Arc;
+use tonic::Code;
+
+#[tokio::test]
+async fn create_writes_its_event_together_with_the_row() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "admin", "org:org-1");
+    let app = TestApp::with_fga(fga);
+
+    let response = app
+        .send_json(
+            Method::POST,
+            &resource_path("report"),
+            "alice",
+            json!({ "properties": { "tier": "gold" } }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+
+    let events = app.ctx.resources.pending_events(10).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, RESOURCE_CREATED);
+    assert_eq!(
+        events[0].resource_id.unwrap().to_string(),
+        response.body["resource"]["id"]
+    );
+    assert_eq!(events[0].payload["name"], "report");
+}
+
+#[tokio::test]
+async fn rejected_create_writes_no_event() {
+    let app = TestApp::new();
+    app.add_resource(sample_resource("report")).await;
+    outbox::relay_once(app.ctx.resources.as_ref())
+        .await
+        .unwrap();
+
+    let conflict = app.ctx.resources.create(sample_resource("report")).await;
+
+    assert!(conflict.is_err());
+    assert!(
+        app.ctx
+            .resources
+            .pending_events(10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn every_mutation_is_recorded_in_order() {
+    let app = TestApp::new();
+    let resource = sample_resource("report");
+    let key = resource.key();
+    app.add_resource(resource).await;
+    app.ctx
+        .resources
+        .update(&key, json!({ "tier": "silver" }), None)
+        .await
+        .unwrap();
+    app.ctx.resources.delete(&key).await.unwrap();
+
+    let events = app.ctx.resources.pending_events(10).await.unwrap();
+    let types: Vec<&str> = events
+        .iter()
+        .map(|event| event.event_type.as_str())
+        .collect();
+    assert_eq!(
+        types,
+        [RESOURCE_CREATED, RESOURCE_UPDATED, RESOURCE_DELETED]
+    );
+    assert_eq!(events[1].payload["properties"], json!({ "tier": "silver" }));
+    assert_eq!(events[1].payload["version"], 2);
+}
+
+#[tokio::test]
+async fn relay_marks_forwarded_events_delivered() {
+    let app = TestApp::new();
+    app.add_resource(sample_resource("report")).await;
+    app.add_resource(sample_resource("invoice")).await;
+
+    assert_eq!(
+        outbox::relay_once(app.ctx.resources.as_ref())
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        outbox::relay_once(app.ctx.resources.as_ref())
+            .await
+            .unwrap(),
+        0
+    );
+    assert!(
+        app.ctx
+            .resources
+            .pending_events(10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn failed_owner_write_records_the_removal() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "admin", "org:org-1");
+    fga.fail_writes_with(Code::Internal);
+    let app = TestApp::with_fga(fga);
+
+    let response = app
+        .send_json(
+            Method::POST,
+            &resource_path("report"),
+            "alice",
+            json!({ "properties": {} }),
+        )
+        .await;
+    assert!(response.status.is_server_error());
+
+    let events = app.ctx.resources.pending_events(10).await.unwrap();
+    let types: Vec<&str> = events
+        .iter()
+        .map(|event| event.event_type.as_str())
+        .collect();
+    assert_eq!(types, [RESOURCE_CREATED, RESOURCE_DELETED]);
+}
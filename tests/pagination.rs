@@ -0,0 +1,132 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{MockFga, TestApp, resource_object};
+use openfga_demo::cursor::Cursor;
+use std::sync::Arc;
+
+/// App where `alice` views five reports
+fn app() -> TestApp {
+    let fga = Arc::new(MockFga::default());
+    for name in ["a", "b", "c", "d", "e"] {
+        fga.grant("user:alice", "viewer", &resource_object(name));
+    }
+    TestApp::with_fga(fga)
+}
+
+#[test]
+fn cursor_round_trips_its_token() {
+    let encoded = Cursor::new("list-objects", "alice|resource|viewer", "42").encode();
+
+    assert!(!encoded.contains(['+', '/', '=']), "{}", encoded);
+    let token = Cursor::decode(&encoded, "list-objects", "alice|resource|viewer").unwrap();
+    assert_eq!(token, "42");
+}
+
+#[test]
+fn cursor_is_rejected_for_another_endpoint_or_query() {
+    let encoded = Cursor::new("list-objects", "alice|resource|viewer", "42").encode();
+
+    assert!(Cursor::decode(&encoded, "resources-with-source", "alice|resource|viewer").is_err());
+    assert!(Cursor::decode(&encoded, "list-objects", "bob|resource|viewer").is_err());
+}
+
+#[tokio::test]
+async fn following_next_token_walks_every_page_once() {
+    let app = app();
+    let mut seen = Vec::new();
+    let mut path = "/api/list-objects?relation=viewer&page_size=2".to_string();
+
+    loop {
+        let response = app.get(Some("alice"), &path).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+        let items = response.body["items"].as_array().unwrap();
+        assert!(items.len() <= 2);
+        seen.extend(items.iter().map(|item| item.as_str().unwrap().to_string()));
+
+        match response.body["page"]["next_token"].as_str() {
+            Some(token) => {
+                path = format!(
+                    "/api/list-objects?relation=viewer&page_size=2&cursor={}",
+                    token
+                )
+            }
+            None => break,
+        }
+    }
+
+    let expected: Vec<String> = ["a", "b", "c", "d", "e"]
+        .into_iter()
+        .map(resource_object)
+        .collect();
+    assert_eq!(seen, expected);
+}
+
+/// `next_token` of the first page of alice's viewer listing
+async fn first_cursor(app: &TestApp) -> String {
+    let response = app
+        .get(
+            Some("alice"),
+            "/api/list-objects?relation=viewer&page_size=2",
+        )
+        .await;
+    response.body["page"]["next_token"]
+        .as_str()
+        .expect("a second page exists")
+        .to_string()
+}
+
+#[tokio::test]
+async fn cursor_replayed_against_another_query_is_rejected() {
+    let app = app();
+    let cursor = first_cursor(&app).await;
+
+    let path = format!("/api/list-objects?relation=editor&cursor={}", cursor);
+    let response = app.get(Some("alice"), &path).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_code(), "validation_failed");
+    assert_eq!(
+        response.body["message"],
+        "Cursor was issued for a different endpoint or query"
+    );
+}
+
+#[tokio::test]
+async fn cursor_replayed_by_another_user_is_rejected() {
+    let app = app();
+    let cursor = first_cursor(&app).await;
+
+    let path = format!("/api/list-objects?relation=viewer&cursor={}", cursor);
+    let response = app.get(Some("bob"), &path).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_code(), "validation_failed");
+}
+
+#[tokio::test]
+async fn cursor_replayed_against_another_endpoint_is_rejected() {
+    let app = app();
+    let cursor = first_cursor(&app).await;
+
+    let path = format!(
+        "/api/resources/with-source?relation=viewer&cursor={}",
+        cursor
+    );
+    let response = app.get(Some("alice"), &path).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_code(), "validation_failed");
+}
+
+#[tokio::test]
+async fn malformed_cursor_is_rejected() {
+    let app = app();
+
+    let response = app
+        .get(Some("alice"), "/api/list-objects?cursor=not-a-cursor")
+        .await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["message"], "Malformed cursor");
+}
@@ -0,0 +1,261 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{MOCK_MODEL_ID, MockFga, TestApp, resource_object};
+use openfga_demo::cache::TtlCache;
+use openfga_demo::context::Ctx;
+use openfga_demo::fga::FgaRetry;
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// App with `alice` owning the report and `root` as admin
+fn app(fga: Arc<MockFga>) -> TestApp {
+    fga.grant("user:alice", "owner", &resource_object("report"));
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.admin.user_ids = vec!["root".to_string()];
+    TestApp::with_ctx(ctx)
+}
+
+fn change(user: &str, operation_id: &str) -> serde_json::Value {
+    json!({
+        "user": user,
+        "relation": "viewer",
+        "object": resource_object("report"),
+        "operation_id": operation_id
+    })
+}
+
+#[tokio::test]
+async fn changes_are_listed_under_their_operation_id() {
+    let fga = Arc::new(MockFga::default());
+    let app = app(fga.clone());
+
+    let response = app
+        .send_json(
+            Method::POST,
+            "/api/permissions",
+            "alice",
+            change("user:bob", "onboard-42"),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.body["operation_id"], "onboard-42");
+    assert_eq!(response.body["replayed"], false);
+    let response = app
+        .send_json(
+            Method::DELETE,
+            "/api/permissions",
+            "alice",
+            change("user:carol", "onboard-42"),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    fga.grant("user:carol", "viewer", &resource_object("report"));
+    let response = app
+        .send_json(
+            Method::DELETE,
+            "/api/permissions",
+            "alice",
+            change("user:carol", "onboard-42"),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    app.send_json(
+        Method::POST,
+        "/api/permissions",
+        "alice",
+        change("user:dave", "other"),
+    )
+    .await;
+
+    let response = app
+        .get(Some("root"), "/api/admin/operations/onboard-42")
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["operation_id"], "onboard-42");
+    let items = response.body["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["operation"], "write");
+    assert_eq!(items[0]["user"], "user:bob");
+    assert_eq!(items[0]["changed_by"], "alice");
+    assert_eq!(items[1]["operation"], "delete");
+    assert_eq!(items[1]["user"], "user:carol");
+    assert!(items[1]["timestamp"].is_string());
+}
+
+#[tokio::test]
+async fn retried_change_is_not_applied_twice() {
+    let fga = Arc::new(MockFga::default());
+    let app = app(fga.clone());
+    let grant = change("user:bob", "onboard-42");
+
+    let first = app
+        .send_json(Method::POST, "/api/permissions", "alice", grant.clone())
+        .await;
+    let retry = app
+        .send_json(Method::POST, "/api/permissions", "alice", grant)
+        .await;
+
+    assert_eq!(first.status, StatusCode::CREATED);
+    assert_eq!(retry.status, StatusCode::CREATED);
+    assert_eq!(retry.body["replayed"], true);
+    assert_eq!(fga.writes.load(Ordering::SeqCst), 1);
+
+    // Without an operation ID the repeated grant reaches OpenFGA and is rejected
+    let mut repeated = change("user:bob", "unused");
+    repeated.as_object_mut().unwrap().remove("operation_id");
+    let response = app
+        .send_json(Method::POST, "/api/permissions", "alice", repeated)
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(fga.writes.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn grant_and_revoke_survive_a_lost_write_response() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "owner", &resource_object("report"));
+    let mut ctx = Ctx::for_testing().with_fga_client(fga.clone());
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.fga_config.retry = FgaRetry {
+        max_attempts: 2,
+        base_delay: Duration::from_millis(1),
+    };
+    let app = TestApp::with_ctx(ctx);
+    let mut grant = change("user:bob", "unused");
+    grant.as_object_mut().unwrap().remove("operation_id");
+
+    fga.lose_next_write_response();
+    let response = app
+        .send_json(Method::POST, "/api/permissions", "alice", grant.clone())
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert!(fga.has("user:bob", "viewer", &resource_object("report")));
+
+    fga.lose_next_write_response();
+    let response = app
+        .send_json(Method::DELETE, "/api/permissions", "alice", grant)
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(!fga.has("user:bob", "viewer", &resource_object("report")));
+    assert_eq!(fga.writes.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn malformed_operation_ids_are_rejected() {
+    let fga = Arc::new(MockFga::default());
+    let app = app(fga.clone());
+
+    for operation_id in ["".to_string(), "two words".to_string(), "x".repeat(129)] {
+        let response = app
+            .send_json(
+                Method::POST,
+                "/api/permissions",
+                "alice",
+                change("user:bob", &operation_id),
+            )
+            .await;
+        assert_eq!(
+            response.status,
+            StatusCode::BAD_REQUEST,
+            "{:?}",
+            operation_id
+        );
+        assert_eq!(response.error_code(), "validation_failed");
+    }
+    assert_eq!(fga.writes.load(Ordering::SeqCst), 0);
+
+    let response = app
+        .get(Some("root"), "/api/admin/operations/bad%20id")
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn operation_lookup_is_admin_only() {
+    let fga = Arc::new(MockFga::default());
+    let app = app(fga);
+    app.send_json(
+        Method::POST,
+        "/api/permissions",
+        "alice",
+        change("user:bob", "onboard-42"),
+    )
+    .await;
+
+    let response = app
+        .get(Some("alice"), "/api/admin/operations/onboard-42")
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let response = app.get(Some("root"), "/api/admin/operations/unknown").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn group_grants_and_revokes_drop_every_shared_listing() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "owner", &resource_object("report"));
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.shared_resources_cache = Arc::new(TtlCache::new(Duration::from_secs(60)));
+    let app = TestApp::with_ctx(ctx);
+    let mut grant = change("group:eng#member", "unused");
+    grant.as_object_mut().unwrap().remove("operation_id");
+
+    for method in [Method::POST, Method::DELETE] {
+        // Members of the group cannot be listed, so no user's entry may survive
+        for user in ["bob", "carol"] {
+            app.ctx
+                .shared_resources_cache
+                .insert(user.to_string(), json!({}));
+        }
+
+        let response = app
+            .send_json(method.clone(), "/api/permissions", "alice", grant.clone())
+            .await;
+
+        assert!(response.status.is_success(), "{}", method);
+        for user in ["bob", "carol"] {
+            assert!(
+                app.ctx
+                    .shared_resources_cache
+                    .get(&user.to_string())
+                    .is_none(),
+                "{} {}",
+                method,
+                user
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn user_grants_drop_only_that_users_shared_listing() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "owner", &resource_object("report"));
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.shared_resources_cache = Arc::new(TtlCache::new(Duration::from_secs(60)));
+    let app = TestApp::with_ctx(ctx);
+    for user in ["bob", "carol"] {
+        app.ctx
+            .shared_resources_cache
+            .insert(user.to_string(), json!({}));
+    }
+    let mut grant = change("user:bob", "unused");
+    grant.as_object_mut().unwrap().remove("operation_id");
+
+    let response = app
+        .send_json(Method::POST, "/api/permissions", "alice", grant)
+        .await;
+
+    assert_eq!(response.status, StatusCode::CREATED);
+    let cache = &app.ctx.shared_resources_cache;
+    assert!(cache.get(&"bob".to_string()).is_none());
+    assert!(cache.get(&"carol".to_string()).is_some());
+}
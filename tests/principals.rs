@@ -0,0 +1,71 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{MockFga, TestApp, resource_object, resource_path};
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn bare_user_id_is_sent_as_a_typed_user_everywhere() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    let app = TestApp::with_fga(fga.clone());
+
+    let check = json!({ "relation": "viewer", "object": resource_object("report") });
+    let responses = [
+        app.send_json(Method::POST, "/api/check", "alice", check.clone())
+            .await,
+        app.send_json(
+            Method::POST,
+            "/api/permissions/batch-check",
+            "alice",
+            json!([check]),
+        )
+        .await,
+        app.get(Some("alice"), "/api/list-objects?relation=viewer")
+            .await,
+        app.get(Some("alice"), "/api/list-objects?relation=viewer,editor")
+            .await,
+        app.get(Some("alice"), "/api/shared-resources").await,
+        app.get(Some("alice"), "/api/access/all").await,
+        app.send_json(
+            Method::POST,
+            &resource_path("invoice"),
+            "alice",
+            json!({ "properties": {} }),
+        )
+        .await,
+    ];
+    for response in &responses {
+        assert!(response.status.is_success(), "{}", response.body);
+    }
+
+    let seen = fga.seen_users();
+    for call in ["Check", "BatchCheck", "ListObjects", "Write"] {
+        assert!(
+            seen.iter().any(|(seen_call, _)| *seen_call == call),
+            "no {} call in {:?}",
+            call,
+            seen
+        );
+    }
+    for (call, user) in &seen {
+        assert_eq!(user, "user:alice", "{}", call);
+    }
+    // The listing answered for the typed user, so the prefix reached the mock
+    assert_eq!(
+        responses[2].body["items"],
+        json!([resource_object("report")])
+    );
+}
+
+#[tokio::test]
+async fn anonymous_callers_are_sent_as_the_anonymous_user() {
+    let fga = Arc::new(MockFga::default());
+    let app = TestApp::with_fga(fga.clone());
+
+    let response = app.get(None, &resource_path("report")).await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(fga.seen_users(), [("Check", "user:*".to_string())]);
+}
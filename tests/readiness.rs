@@ -0,0 +1,79 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{MockFga, TestApp};
+use openfga_demo::context::Ctx;
+use serde_json::json;
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// App whose database refuses connections, with FGA answered by `fga` and
+/// the given dependencies marked optional
+fn app(fga: Arc<MockFga>, optional: &[&str]) -> TestApp {
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    // Nothing listens on port 1, so the probe fails fast instead of retrying
+    ctx.db = PgPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(200))
+        .connect_lazy("postgres://127.0.0.1:1/openfga_demo_test")
+        .expect("static database URL is valid");
+    ctx.readiness_optional = optional.iter().map(|name| name.to_string()).collect();
+    TestApp::with_ctx(ctx)
+}
+
+#[tokio::test]
+async fn optional_dependency_down_is_degraded() {
+    let app = app(Arc::new(MockFga::default()), &["db"]);
+
+    let response = app.get(None, "/ready").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["status"], "degraded");
+    let db = &response.body["dependencies"]["db"];
+    assert_eq!(db["required"], false);
+    assert_eq!(db["up"], false);
+    assert!(db["error"].is_string(), "{}", db);
+    assert_eq!(
+        response.body["dependencies"]["fga"],
+        json!({ "required": true, "up": true, "error": null })
+    );
+}
+
+#[tokio::test]
+async fn required_dependency_down_is_unavailable() {
+    let app = app(Arc::new(MockFga::default()), &[]);
+
+    let response = app.get(None, "/ready").await;
+
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.body["status"], "unavailable");
+    assert_eq!(response.body["dependencies"]["db"]["required"], true);
+    assert_eq!(response.body["dependencies"]["fga"]["up"], true);
+}
+
+#[tokio::test]
+async fn required_fga_down_is_unavailable_despite_optional_db() {
+    let fga = Arc::new(MockFga::default());
+    fga.set_unavailable();
+    let app = app(fga, &["db"]);
+
+    let response = app.get(None, "/ready").await;
+
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    let fga = &response.body["dependencies"]["fga"];
+    assert_eq!(fga["required"], true);
+    assert_eq!(fga["up"], false);
+    assert_eq!(fga["error"], "connection refused");
+}
+
+#[tokio::test]
+async fn every_dependency_optional_and_down_is_degraded() {
+    let fga = Arc::new(MockFga::default());
+    fga.set_unavailable();
+    let app = app(fga, &["db", "fga"]);
+
+    let response = app.get(None, "/ready").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["status"], "degraded");
+}
@@ -0,0 +1,468 @@
+mod common;
+
+use axum::body::Body;
+use axum::http::{Method, StatusCode, header};
+use common::{MockFga, TestApp, json_request, resource_object, resource_path, sample_resource};
+use openfga_demo::context::Ctx;
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use tonic::Code;
+
+#[tokio::test]
+async fn allowed_viewer_gets_resource_with_etag() {
+    let app = TestApp::new();
+    app.add_resource(sample_resource("report")).await;
+    app.decide("alice", "viewer", &resource_object("report"), true);
+
+    let response = app.get(Some("alice"), &resource_path("report")).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["resource_id"], resource_object("report"));
+    assert_eq!(response.body["properties"], json!({ "tier": "gold" }));
+    assert_eq!(response.headers[header::ETAG], "\"1\"");
+}
+
+#[tokio::test]
+async fn denied_viewer_is_forbidden() {
+    let app = TestApp::new();
+    app.add_resource(sample_resource("report")).await;
+    app.decide("bob", "viewer", &resource_object("report"), false);
+
+    let response = app.get(Some("bob"), &resource_path("report")).await;
+
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert_eq!(response.error_code(), "forbidden");
+}
+
+#[tokio::test]
+async fn denied_anonymous_caller_must_authenticate() {
+    let app = TestApp::new();
+    app.add_resource(sample_resource("report")).await;
+    app.decide("*", "viewer", &resource_object("report"), false);
+
+    let response = app.get(None, &resource_path("report")).await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.error_code(), "unauthenticated");
+}
+
+#[tokio::test]
+async fn allowed_viewer_of_missing_resource_gets_not_found() {
+    let app = TestApp::new();
+    app.decide("alice", "viewer", &resource_object("missing"), true);
+
+    let response = app.get(Some("alice"), &resource_path("missing")).await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.error_code(), "not_found");
+}
+
+#[tokio::test]
+async fn update_requires_matching_if_match() {
+    let app = TestApp::new();
+    app.add_resource(sample_resource("report")).await;
+    app.decide("alice", "editor", &resource_object("report"), true);
+    let payload = json!({ "properties": { "tier": "silver" } });
+
+    let response = app
+        .send_json(
+            Method::PUT,
+            &resource_path("report"),
+            "alice",
+            payload.clone(),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::PRECONDITION_REQUIRED);
+
+    let mut request = json_request(
+        Method::PUT,
+        &resource_path("report"),
+        Some("alice"),
+        &payload,
+    );
+    request
+        .headers_mut()
+        .insert(header::IF_MATCH, "\"7\"".parse().unwrap());
+    let response = app.send(request).await;
+    assert_eq!(response.status, StatusCode::PRECONDITION_FAILED);
+    assert_eq!(response.error_code(), "precondition_failed");
+
+    let mut request = json_request(
+        Method::PUT,
+        &resource_path("report"),
+        Some("alice"),
+        &payload,
+    );
+    request
+        .headers_mut()
+        .insert(header::IF_MATCH, "\"1\"".parse().unwrap());
+    let response = app.send(request).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.headers[header::ETAG], "\"2\"");
+    assert_eq!(
+        response.body["resource"]["properties"],
+        json!({ "tier": "silver" })
+    );
+}
+
+#[tokio::test]
+async fn malformed_json_body_uses_error_shape() {
+    let app = TestApp::new();
+    let request = common::request(Method::POST, &resource_path("report"), Some("alice"))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(axum::body::Body::from("{\"properties\": "))
+        .unwrap();
+
+    let response = app.send(request).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_code(), "invalid_json");
+}
+
+#[tokio::test]
+async fn delete_removes_the_row_and_its_tuples() {
+    let fga = Arc::new(MockFga::default());
+    let object = resource_object("report");
+    fga.grant("user:alice", "owner", &object);
+    fga.grant("user:bob", "viewer", &object);
+    fga.grant("user:carol", "viewer", &resource_object("other"));
+    let app = TestApp::with_fga(fga.clone());
+    app.add_resource(sample_resource("report")).await;
+
+    let response = app
+        .send(
+            common::request(Method::DELETE, &resource_path("report"), Some("alice"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["tuples_removed"], 2);
+    assert!(!fga.has("user:alice", "owner", &object));
+    assert!(!fga.has("user:bob", "viewer", &object));
+    assert!(fga.has("user:carol", "viewer", &resource_object("other")));
+    let key = sample_resource("report").key();
+    assert!(app.ctx.resources.get(&key).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn delete_of_missing_resource_keeps_tuples() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "owner", &resource_object("report"));
+    let app = TestApp::with_fga(fga.clone());
+
+    let response = app
+        .send(
+            common::request(Method::DELETE, &resource_path("report"), Some("alice"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(fga.writes.load(Ordering::SeqCst), 0);
+}
+
+/// Get, update and delete check the typed `resource:` object, which is what
+/// tuples are written for; the bare `svc/type/org/name` path matches nothing
+#[tokio::test]
+async fn resource_handlers_check_the_typed_object() {
+    let cases = [
+        (Method::GET, "viewer"),
+        (Method::PUT, "editor"),
+        (Method::DELETE, "owner"),
+    ];
+    for (method, relation) in cases {
+        for (object, expected) in [
+            (
+                "billing/web/org-1/report".to_string(),
+                StatusCode::FORBIDDEN,
+            ),
+            (resource_object("report"), StatusCode::OK),
+        ] {
+            let fga = Arc::new(MockFga::default());
+            fga.grant("user:alice", relation, &object);
+            let app = TestApp::with_fga(fga);
+            app.add_resource(sample_resource("report")).await;
+
+            let request = common::request(method.clone(), &resource_path("report"), Some("alice"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::IF_MATCH, "\"1\"");
+            let body = if method == Method::PUT {
+                json!({ "properties": { "tier": "silver" } }).to_string()
+            } else {
+                String::new()
+            };
+            let response = app.send(request.body(Body::from(body)).unwrap()).await;
+
+            assert_eq!(response.status, expected, "{} {}", method, object);
+        }
+    }
+}
+
+#[tokio::test]
+async fn dry_run_delete_reports_tuples_and_row_without_mutating() {
+    let fga = Arc::new(MockFga::default());
+    let object = resource_object("report");
+    fga.grant("user:alice", "owner", &object);
+    fga.grant("user:bob", "viewer", &object);
+    fga.grant("user:carol", "viewer", &resource_object("other"));
+    let app = TestApp::with_fga(fga.clone());
+    app.add_resource(sample_resource("report")).await;
+
+    let response = app
+        .send(
+            common::request(
+                Method::DELETE,
+                &format!("{}?dry_run=true", resource_path("report")),
+                Some("alice"),
+            )
+            .body(Body::empty())
+            .unwrap(),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["dry_run"], true);
+    assert_eq!(response.body["row_exists"], true);
+    assert_eq!(response.body["tuples_truncated"], false);
+    assert_eq!(
+        response.body["tuples"],
+        json!([
+            { "user": "user:alice", "relation": "owner", "object": object },
+            { "user": "user:bob", "relation": "viewer", "object": object }
+        ])
+    );
+    assert_eq!(fga.writes.load(Ordering::SeqCst), 0);
+    let key = sample_resource("report").key();
+    assert!(app.ctx.resources.get(&key).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn dry_run_delete_reports_a_missing_row() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "owner", &resource_object("report"));
+    let app = TestApp::with_fga(fga.clone());
+
+    let response = app
+        .send(
+            common::request(
+                Method::DELETE,
+                &format!("{}?dry_run=true", resource_path("report")),
+                Some("alice"),
+            )
+            .body(Body::empty())
+            .unwrap(),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["row_exists"], false);
+    assert_eq!(response.body["tuples"].as_array().unwrap().len(), 1);
+    assert_eq!(fga.writes.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn batch_get_separates_found_forbidden_and_missing() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    fga.grant("user:alice", "viewer", &resource_object("missing"));
+    let app = TestApp::with_fga(fga.clone());
+    app.add_resource(sample_resource("report")).await;
+    app.add_resource(sample_resource("secret")).await;
+
+    let response = app
+        .send_json(
+            Method::POST,
+            "/api/resources/batch-get",
+            "alice",
+            json!([
+                resource_object("report"),
+                resource_object("secret"),
+                resource_object("missing"),
+                resource_object("unknown")
+            ]),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    let resources = &response.body["resources"];
+    assert_eq!(resources[resource_object("report")]["status"], "found");
+    assert_eq!(
+        resources[resource_object("report")]["resource"]["properties"],
+        json!({ "tier": "gold" })
+    );
+    // Existing and missing resources look the same to a caller without access
+    assert_eq!(resources[resource_object("secret")]["status"], "forbidden");
+    assert_eq!(resources[resource_object("unknown")]["status"], "forbidden");
+    assert_eq!(resources[resource_object("missing")]["status"], "not_found");
+    assert_eq!(fga.batch_checks.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn batch_get_rejects_malformed_ids_and_oversized_batches() {
+    let app = TestApp::with_fga(Arc::new(MockFga::default()));
+
+    let response = app
+        .send_json(
+            Method::POST,
+            "/api/resources/batch-get",
+            "alice",
+            json!(["service:billing"]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_code(), "validation_failed");
+
+    let ids: Vec<String> = (0..101).map(|i| resource_object(&i.to_string())).collect();
+    let response = app
+        .send_json(
+            Method::POST,
+            "/api/resources/batch-get",
+            "alice",
+            json!(ids),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_code(), "validation_failed");
+}
+
+#[tokio::test]
+async fn bulk_delete_reports_each_outcome() {
+    let fga = Arc::new(MockFga::default());
+    for name in ["report", "missing"] {
+        fga.grant("user:alice", "owner", &resource_object(name));
+    }
+    fga.grant("user:bob", "viewer", &resource_object("report"));
+    let app = TestApp::with_fga(fga.clone());
+    app.add_resource(sample_resource("report")).await;
+    app.add_resource(sample_resource("secret")).await;
+
+    let response = app
+        .send_json(
+            Method::POST,
+            "/api/resources/bulk-delete",
+            "alice",
+            json!([
+                resource_object("report"),
+                resource_object("secret"),
+                resource_object("missing")
+            ]),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    let resources = &response.body["resources"];
+    assert_eq!(
+        resources[resource_object("report")],
+        json!({ "status": "deleted", "tuples_removed": 2 })
+    );
+    assert_eq!(resources[resource_object("secret")]["status"], "forbidden");
+    assert_eq!(resources[resource_object("missing")]["status"], "not_found");
+    assert!(!fga.has("user:bob", "viewer", &resource_object("report")));
+    // Tuples of a resource without a row are left for a single delete to report
+    assert!(fga.has("user:alice", "owner", &resource_object("missing")));
+    let secret = sample_resource("secret").key();
+    assert!(app.ctx.resources.get(&secret).await.unwrap().is_some());
+    assert_eq!(fga.batch_checks.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn bulk_delete_reports_failed_tuple_cleanup() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "owner", &resource_object("report"));
+    fga.fail_writes_with(Code::Internal);
+    let app = TestApp::with_fga(fga.clone());
+    app.add_resource(sample_resource("report")).await;
+
+    let response = app
+        .send_json(
+            Method::POST,
+            "/api/resources/bulk-delete",
+            "alice",
+            json!([resource_object("report")]),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body["resources"][resource_object("report")]["status"],
+        "cleanup_failed"
+    );
+    // The row stays so the resource can be deleted again
+    let key = sample_resource("report").key();
+    assert!(app.ctx.resources.get(&key).await.unwrap().is_some());
+    assert!(fga.has("user:alice", "owner", &resource_object("report")));
+}
+
+#[tokio::test]
+async fn resource_is_resolved_by_its_stable_id() {
+    let app = TestApp::new();
+    let resource = sample_resource("report");
+    let id = resource.id;
+    app.add_resource(resource).await;
+    app.decide("alice", "viewer", &resource_object("report"), true);
+    app.decide("bob", "viewer", &resource_object("report"), false);
+
+    let path = format!("/api/resources/by-id/{}", id);
+    let response = app.get(Some("alice"), &path).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["id"], id.to_string());
+    assert_eq!(response.body["resource_id"], resource_object("report"));
+    assert_eq!(response.headers[header::ETAG], "\"1\"");
+
+    let response = app.get(Some("bob"), &path).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert!(!response.body.to_string().contains("billing/web"));
+}
+
+#[tokio::test]
+async fn unknown_or_malformed_stable_id_is_rejected() {
+    let app = TestApp::new();
+
+    let unknown = format!("/api/resources/by-id/{}", uuid::Uuid::new_v4());
+    let response = app.get(Some("alice"), &unknown).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.error_code(), "not_found");
+
+    let response = app
+        .get(Some("alice"), "/api/resources/by-id/not-a-uuid")
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_code(), "validation_failed");
+}
+
+#[tokio::test]
+async fn access_is_checked_by_stable_id_and_action() {
+    let mut ctx = Ctx::for_testing();
+    ctx.fga_config
+        .actions
+        .insert("edit".to_string(), "editor".to_string());
+    let app = TestApp::with_ctx(ctx);
+    let resource = sample_resource("report");
+    let id = resource.id;
+    app.add_resource(resource).await;
+    app.decide("alice", "editor", &resource_object("report"), true);
+    app.decide("bob", "editor", &resource_object("report"), false);
+
+    let path = format!("/api/resources/by-id/{}/can/edit", id);
+    let response = app.get(Some("alice"), &path).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["allowed"], true);
+
+    let response = app.get(Some("bob"), &path).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["allowed"], false);
+    assert!(!response.body.to_string().contains("billing/web"));
+
+    let unknown = format!("/api/resources/by-id/{}/can/edit", uuid::Uuid::new_v4());
+    let response = app.get(Some("alice"), &unknown).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let undefined = format!("/api/resources/by-id/{}/can/archive", id);
+    let response = app.get(Some("alice"), &undefined).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use axum::http::{Method, StatusCode, header};
+use common::{MOCK_MODEL_ID, MockFga, TestApp, resource_object};
+use openfga_demo::context::Ctx;
+use serde_json::json;
+use std::sync::Arc;
+use tonic::Code;
+
+/// App answering OpenFGA calls with the mock and a 7 second base backoff
+fn app(fga: Arc<MockFga>) -> TestApp {
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.retry_after_secs = 7;
+    TestApp::with_ctx(ctx)
+}
+
+#[tokio::test]
+async fn unavailable_listing_sends_retry_after() {
+    let fga = Arc::new(MockFga::default());
+    fga.set_unavailable();
+    let app = app(fga);
+
+    for path in [
+        "/api/list-objects?relation=viewer",
+        "/api/resources/summary",
+        "/api/resources/with-source",
+    ] {
+        let response = app.get(Some("alice"), path).await;
+
+        assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE, "{}", path);
+        assert_eq!(response.headers[header::RETRY_AFTER], "7", "{}", path);
+    }
+}
+
+#[tokio::test]
+async fn unavailable_write_sends_retry_after() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "owner", &resource_object("report"));
+    fga.fail_writes_with(Code::Unavailable);
+    let app = app(fga);
+
+    let response = app
+        .send_json(
+            Method::POST,
+            "/api/permissions",
+            "alice",
+            json!({
+                "user": "user:bob",
+                "relation": "viewer",
+                "object": resource_object("report")
+            }),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.error_code(), "fga_unavailable");
+    assert_eq!(response.headers[header::RETRY_AFTER], "7");
+}
+
+#[tokio::test]
+async fn other_errors_send_no_retry_after() {
+    let fga = Arc::new(MockFga::default());
+    fga.fail_with(Code::Internal);
+    let app = app(fga);
+
+    let response = app
+        .get(Some("alice"), "/api/list-objects?relation=viewer")
+        .await;
+
+    assert_eq!(response.status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(!response.headers.contains_key(header::RETRY_AFTER));
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{MockFga, TestApp, resource_object, resource_path, sample_resource};
+use openfga_demo::error::{ApiError, ErrorCode};
+use openfga_demo::saga::Saga;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use tonic::Code;
+
+const STEPS: [&str; 3] = ["first", "second", "third"];
+
+/// Run the steps in order until `failing` fails, recording every undo in `undone`
+async fn run_until(failing: usize, undone: &Arc<Mutex<Vec<&'static str>>>) -> ApiError {
+    let mut saga = Saga::new("Test saga");
+    for (index, step) in STEPS.into_iter().enumerate() {
+        if index == failing {
+            let error = ApiError::new(ErrorCode::FgaUnavailable, "step failed");
+            return saga.fail(step, error).await;
+        }
+        let undone = undone.clone();
+        saga.completed(step, async move {
+            undone.lock().unwrap().push(step);
+            Ok(())
+        });
+    }
+    unreachable!("one step always fails")
+}
+
+#[tokio::test]
+async fn failure_at_each_step_undoes_the_completed_ones_in_reverse() {
+    for failing in 0..STEPS.len() {
+        let undone = Arc::new(Mutex::new(Vec::new()));
+
+        let error = run_until(failing, &undone).await;
+
+        let mut expected = STEPS[..failing].to_vec();
+        expected.reverse();
+        assert_eq!(*undone.lock().unwrap(), expected);
+        if failing == 0 {
+            // Nothing to undo: the step's own error goes back to the caller
+            assert_eq!(error.code, ErrorCode::FgaUnavailable);
+            assert!(error.details.is_none());
+        } else {
+            assert_eq!(error.code, ErrorCode::Internal);
+            let details = error.details.unwrap();
+            assert_eq!(details["compensated"], true);
+            assert_eq!(details["failed_step"], STEPS[failing]);
+            assert_eq!(details["cause"], "fga_unavailable");
+        }
+    }
+}
+
+#[tokio::test]
+async fn failed_compensation_is_reported_and_the_rest_still_run() {
+    let undone = Arc::new(Mutex::new(Vec::new()));
+    let mut saga = Saga::new("Test saga");
+    let recorded = undone.clone();
+    saga.completed("first", async move {
+        recorded.lock().unwrap().push("first");
+        Ok(())
+    });
+    saga.completed("second", async { Err("gone".to_string()) });
+
+    let error = saga
+        .fail("third", ApiError::new(ErrorCode::Internal, "step failed"))
+        .await;
+
+    assert_eq!(*undone.lock().unwrap(), ["first"]);
+    assert_eq!(error.details.unwrap()["compensated"], false);
+}
+
+#[tokio::test]
+async fn failed_owner_write_removes_the_stored_resource() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "admin", "org:org-1");
+    fga.fail_writes_with(Code::Unavailable);
+    let app = TestApp::with_fga(fga.clone());
+
+    let response = app
+        .send_json(
+            Method::POST,
+            &resource_path("report"),
+            "alice",
+            json!({ "properties": {} }),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(response.body["details"]["compensated"], true);
+    assert_eq!(response.body["details"]["failed_step"], "write owner tuple");
+    assert_eq!(response.body["details"]["cause"], "fga_unavailable");
+    let key = sample_resource("report").key();
+    assert!(app.ctx.resources.get(&key).await.unwrap().is_none());
+    assert!(!fga.has("user:alice", "owner", &resource_object("report")));
+}
+
+#[tokio::test]
+async fn failed_store_step_leaves_the_existing_resource_alone() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "admin", "org:org-1");
+    let app = TestApp::with_fga(fga.clone());
+    app.add_resource(sample_resource("report")).await;
+
+    let response = app
+        .send_json(
+            Method::POST,
+            &resource_path("report"),
+            "alice",
+            json!({ "properties": {} }),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert!(response.body.get("details").is_none());
+    let key = sample_resource("report").key();
+    assert!(app.ctx.resources.get(&key).await.unwrap().is_some());
+    assert!(!fga.has("user:alice", "owner", &resource_object("report")));
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{MOCK_MODEL_ID, MockFga, TestApp, resource_object, resource_path};
+use openfga_demo::cache::TtlCache;
+use openfga_demo::context::Ctx;
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+use tonic::Code;
+
+#[tokio::test]
+async fn shared_resources_carry_their_organisation() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    let app = TestApp::with_fga(fga);
+
+    let response = app.get(Some("alice"), "/api/shared-resources").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body["resources"]["items"],
+        json!([{
+            "id": resource_object("report"),
+            "service_name": "billing",
+            "service_type": "web",
+            "org_id": "org-1",
+            "resource_name": "report",
+            "shared_via": "parent_organization",
+            "permissions": ["viewer"]
+        }])
+    );
+}
+
+/// App whose shared resources are cached for `ttl`
+fn cached_app(fga: Arc<MockFga>, ttl: Duration) -> TestApp {
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.shared_resources_cache = Arc::new(TtlCache::new(ttl));
+    TestApp::with_ctx(ctx)
+}
+
+#[tokio::test]
+async fn repeated_lookups_are_served_from_cache() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    let app = cached_app(fga.clone(), Duration::from_secs(60));
+
+    let first = app.get(Some("alice"), "/api/shared-resources").await;
+    let second = app.get(Some("alice"), "/api/shared-resources").await;
+
+    assert_eq!(first.body, second.body);
+    assert_eq!(fga.listings.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn cached_lookups_expire_after_the_ttl() {
+    let fga = Arc::new(MockFga::default());
+    let app = cached_app(fga.clone(), Duration::from_millis(50));
+
+    app.get(Some("alice"), "/api/shared-resources").await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    app.get(Some("alice"), "/api/shared-resources").await;
+
+    assert_eq!(fga.listings.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn sharing_invalidates_the_recipients_cache() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "admin", &resource_object("report"));
+    let app = cached_app(fga.clone(), Duration::from_secs(60));
+
+    let before = app.get(Some("bob"), "/api/shared-resources").await;
+    assert_eq!(before.body["resources"]["items"], json!([]));
+
+    app.send_json(
+        Method::POST,
+        &format!("{}/share", resource_path("report")),
+        "alice",
+        json!({ "relation": "viewer", "users": ["bob"] }),
+    )
+    .await;
+    let after = app.get(Some("bob"), "/api/shared-resources").await;
+
+    assert_eq!(
+        after.body["resources"]["items"][0]["id"],
+        resource_object("report")
+    );
+}
+
+#[tokio::test]
+async fn partial_results_are_not_cached() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    fga.fail_with(Code::Internal);
+    let app = cached_app(fga.clone(), Duration::from_secs(60));
+
+    let failed = app.get(Some("alice"), "/api/shared-resources").await;
+    assert_eq!(failed.body["resources"]["items"], json!([]));
+
+    fga.recover();
+    let recovered = app.get(Some("alice"), "/api/shared-resources").await;
+
+    assert_eq!(
+        recovered.body["resources"]["items"][0]["id"],
+        resource_object("report")
+    );
+}
+
+#[tokio::test]
+async fn oversized_responses_keep_the_first_resources_by_id() {
+    let fga = Arc::new(MockFga::default());
+    for name in ["c", "a", "b"] {
+        fga.grant("user:alice", "viewer", &resource_object(name));
+    }
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    // Room for a single resource entry
+    ctx.max_response_bytes = Some(250);
+    let app = TestApp::with_ctx(ctx);
+
+    let response = app.get(Some("alice"), "/api/shared-resources").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    let resources = &response.body["resources"];
+    assert_eq!(resources["items"].as_array().unwrap().len(), 1);
+    assert_eq!(resources["items"][0]["id"], resource_object("a"));
+    assert_eq!(resources["page"]["size"], 1);
+    assert_eq!(resources["page"]["truncated"], true);
+    assert_eq!(resources["page"]["omitted"], 2);
+}
+
+#[tokio::test]
+async fn shared_listings_fan_out_concurrently() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    fga.grant("user:alice", "editor", "service:billing");
+    fga.delay_listings(Duration::from_millis(100));
+    let mut ctx = Ctx::for_testing().with_fga_client(fga.clone());
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    let relations = ["viewer", "editor", "owner"].map(String::from).to_vec();
+    for object_type in ["service", "service_type", "resource"] {
+        ctx.fga_config
+            .relations_by_type
+            .insert(object_type.to_string(), relations.clone());
+    }
+    ctx.fga_config.shared_object_types = ["service", "service_type", "resource"]
+        .map(String::from)
+        .to_vec();
+    ctx.fga_config.shared_relations = relations;
+    let app = TestApp::with_ctx(ctx);
+
+    let started = Instant::now();
+    let response = app.get(Some("alice"), "/api/shared-resources").await;
+    let elapsed = started.elapsed();
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["resources"]["page"]["size"], 1);
+    assert_eq!(
+        response.body["services"]["items"][0]["id"],
+        "service:billing"
+    );
+    assert_eq!(fga.listings.load(Ordering::SeqCst), 9);
+    // Nine sequential listings would take 900ms; the fan-out runs eight at once
+    assert_eq!(fga.peak_listings.load(Ordering::SeqCst), 8);
+    assert!(elapsed < Duration::from_millis(450), "took {:?}", elapsed);
+}
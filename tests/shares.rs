@@ -0,0 +1,93 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{MOCK_MODEL_ID, MockFga, TestApp, resource_object, resource_path};
+use openfga_demo::context::Ctx;
+use openfga_demo::fga::FgaRetry;
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+fn share_path(name: &str) -> String {
+    format!("{}/share", resource_path(name))
+}
+
+#[tokio::test]
+async fn sharing_grants_new_users_and_skips_existing_grants() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "admin", &resource_object("report"));
+    fga.grant("user:bob", "viewer", &resource_object("report"));
+    let app = TestApp::with_fga(fga.clone());
+
+    let response = app
+        .send_json(
+            Method::POST,
+            &share_path("report"),
+            "alice",
+            json!({ "relation": "viewer", "users": ["carol", "bob", "dave"] }),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body["results"],
+        json!([
+            { "user": "bob", "status": "already_granted" },
+            { "user": "carol", "status": "granted" },
+            { "user": "dave", "status": "granted" }
+        ])
+    );
+    assert!(fga.has("user:carol", "viewer", &resource_object("report")));
+    assert!(fga.has("user:dave", "viewer", &resource_object("report")));
+    assert_eq!(fga.writes.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn sharing_rejects_entries_that_are_not_plain_user_ids() {
+    for user in ["*", "bob#member", "user:bob"] {
+        let fga = Arc::new(MockFga::default());
+        fga.grant("user:alice", "admin", &resource_object("report"));
+        let app = TestApp::with_fga(fga.clone());
+
+        let response = app
+            .send_json(
+                Method::POST,
+                &share_path("report"),
+                "alice",
+                json!({ "relation": "viewer", "users": ["carol", user] }),
+            )
+            .await;
+
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "{}", user);
+        assert_eq!(response.error_code(), "validation_failed", "{}", user);
+        assert_eq!(fga.writes.load(Ordering::SeqCst), 0, "{}", user);
+    }
+}
+
+#[tokio::test]
+async fn retried_write_that_was_already_applied_succeeds() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "admin", &resource_object("report"));
+    fga.lose_next_write_response();
+    let mut ctx = Ctx::for_testing().with_fga_client(fga.clone());
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.fga_config.retry = FgaRetry {
+        max_attempts: 2,
+        base_delay: Duration::from_millis(1),
+    };
+    let app = TestApp::with_ctx(ctx);
+
+    let response = app
+        .send_json(
+            Method::POST,
+            &share_path("report"),
+            "alice",
+            json!({ "relation": "viewer", "users": ["carol"] }),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(fga.writes.load(Ordering::SeqCst), 2);
+    assert!(fga.has("user:carol", "viewer", &resource_object("report")));
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::TestApp;
+use openfga_demo::context::Ctx;
+use openfga_demo::stats::Stats;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// App with `root` as admin, keeping statistics for `window`
+fn app(window: Duration) -> TestApp {
+    let mut ctx = Ctx::for_testing();
+    ctx.admin.user_ids = vec!["root".to_string()];
+    ctx.stats = Arc::new(Stats::new(window));
+    TestApp::with_ctx(ctx)
+}
+
+/// Assert a latency summary holds `count` samples with the given p50, p95
+/// and p99, allowing for float rounding of the millisecond conversion
+fn assert_percentiles(summary: &Value, count: usize, expected: [f64; 3]) {
+    assert_eq!(summary["count"], count, "{}", summary);
+    for (key, expected) in ["p50_ms", "p95_ms", "p99_ms"].into_iter().zip(expected) {
+        let actual = summary[key].as_f64().unwrap();
+        assert!((actual - expected).abs() < 1e-6, "{}: {}", key, summary);
+    }
+}
+
+#[tokio::test]
+async fn percentiles_reflect_the_recorded_latencies() {
+    let app = app(Duration::from_secs(60));
+    // Recorded out of order so the report has to sort them
+    for ms in (1..=100).rev() {
+        app.ctx.stats.record_check(Duration::from_millis(ms));
+    }
+    app.ctx.stats.record_list_objects(Duration::from_millis(40));
+
+    let response = app.get(Some("root"), "/api/admin/stats").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["window_secs"], 60);
+    assert_percentiles(&response.body["check"], 100, [50.0, 95.0, 99.0]);
+    assert_percentiles(&response.body["list_objects"], 1, [40.0, 40.0, 40.0]);
+}
+
+#[tokio::test]
+async fn percentiles_stay_within_the_recorded_range() {
+    let app = app(Duration::from_secs(60));
+    for ms in [3, 7, 7, 12, 250] {
+        app.ctx.stats.record_check(Duration::from_millis(ms));
+    }
+
+    let response = app.get(Some("root"), "/api/admin/stats").await;
+
+    let check = &response.body["check"];
+    let (p50, p95, p99) = (
+        check["p50_ms"].as_f64().unwrap(),
+        check["p95_ms"].as_f64().unwrap(),
+        check["p99_ms"].as_f64().unwrap(),
+    );
+    assert!((3.0..=250.0).contains(&p50), "{}", check);
+    assert!(p50 <= p95 && p95 <= p99, "{}", check);
+    assert!((p99 - 250.0).abs() < 1e-6, "{}", check);
+}
+
+#[tokio::test]
+async fn empty_windows_report_zero_samples() {
+    let app = app(Duration::from_secs(60));
+
+    let response = app.get(Some("root"), "/api/admin/stats").await;
+
+    assert_percentiles(&response.body["check"], 0, [0.0, 0.0, 0.0]);
+    assert!(response.body["shared_resources_cache"]["hit_ratio"].is_null());
+}
+
+#[tokio::test]
+async fn responses_and_cache_lookups_are_counted() {
+    let app = app(Duration::from_secs(60));
+    for hit in [true, true, true, false] {
+        app.ctx.stats.record_cache_lookup(hit);
+    }
+    app.get(Some("alice"), "/api/unknown").await;
+    app.get(Some("alice"), "/api/admin/stats").await;
+
+    let response = app.get(Some("root"), "/api/admin/stats").await;
+
+    assert_eq!(response.body["responses_by_status"]["404"], 1);
+    assert_eq!(response.body["responses_by_status"]["403"], 1);
+    assert_eq!(
+        response.body["shared_resources_cache"],
+        json!({ "hits": 3, "misses": 1, "hit_ratio": 0.75 })
+    );
+}
+
+#[tokio::test]
+async fn samples_outside_the_window_are_dropped() {
+    let app = app(Duration::from_millis(50));
+    app.ctx.stats.record_check(Duration::from_millis(10));
+
+    tokio::time::sleep(Duration::from_millis(80)).await;
+    let response = app.get(Some("root"), "/api/admin/stats").await;
+
+    assert_eq!(response.body["check"]["count"], 0);
+}
+
+#[tokio::test]
+async fn reset_discards_recorded_samples() {
+    let app = app(Duration::from_secs(60));
+    app.ctx.stats.record_check(Duration::from_millis(10));
+
+    let response = app
+        .send_json(Method::POST, "/api/admin/stats/reset", "root", json!({}))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = app.get(Some("root"), "/api/admin/stats").await;
+    assert_eq!(response.body["check"]["count"], 0);
+}
+
+#[tokio::test]
+async fn stats_are_admin_only() {
+    let app = app(Duration::from_secs(60));
+
+    let response = app.get(Some("alice"), "/api/admin/stats").await;
+
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,23 @@
+use openfga_demo::telemetry;
+use tonic::metadata::MetadataMap;
+use tracing_subscriber::Registry;
+
+// The only test in this binary that touches the environment, so nothing reads
+// OTEL_EXPORTER_OTLP_ENDPOINT concurrently
+#[test]
+fn exporter_is_off_without_an_endpoint() {
+    unsafe { std::env::remove_var("OTEL_EXPORTER_OTLP_ENDPOINT") };
+    assert!(telemetry::otlp_layer::<Registry>().unwrap().is_none());
+
+    unsafe { std::env::set_var("OTEL_EXPORTER_OTLP_ENDPOINT", "  ") };
+    assert!(telemetry::otlp_layer::<Registry>().unwrap().is_none());
+}
+
+#[test]
+fn trace_context_is_not_injected_without_the_exporter() {
+    let mut metadata = MetadataMap::new();
+
+    telemetry::inject_trace_context(&mut metadata);
+
+    assert!(metadata.get("traceparent").is_none());
+}
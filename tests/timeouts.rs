@@ -0,0 +1,74 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{MOCK_MODEL_ID, MockFga, TestApp, resource_object, resource_path, sample_resource};
+use openfga_demo::context::Ctx;
+use openfga_demo::fga::FgaTimeouts;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+const CHECK: Duration = Duration::from_millis(25);
+const LIST: Duration = Duration::from_millis(750);
+const WRITE: Duration = Duration::from_millis(1500);
+
+#[tokio::test]
+async fn each_operation_carries_its_configured_deadline() {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "owner", &resource_object("report"));
+    fga.grant("user:alice", "viewer", &resource_object("report"));
+    let mut ctx = Ctx::for_testing().with_fga_client(fga.clone());
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.fga_config.timeouts = FgaTimeouts {
+        check: CHECK,
+        list: LIST,
+        write: WRITE,
+    };
+    let app = TestApp::with_ctx(ctx);
+    app.add_resource(sample_resource("report")).await;
+
+    let response = app.get(Some("alice"), &resource_path("report")).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(fga.last_timeout("Check"), Some(CHECK));
+
+    let response = app
+        .send_json(
+            Method::POST,
+            "/api/permissions/batch-check",
+            "alice",
+            json!([{ "relation": "viewer", "object": resource_object("report") }]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(fga.last_timeout("BatchCheck"), Some(CHECK));
+
+    let response = app
+        .get(Some("alice"), "/api/resources/with-source?relation=viewer")
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(fga.last_timeout("ListObjects"), Some(LIST));
+    assert_eq!(fga.last_timeout("Read"), Some(LIST));
+
+    let response = app
+        .send_json(
+            Method::POST,
+            "/api/permissions",
+            "alice",
+            json!({
+                "user": "user:bob",
+                "relation": "viewer",
+                "object": resource_object("report")
+            }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(fga.last_timeout("Write"), Some(WRITE));
+}
+
+#[test]
+fn listing_defaults_to_a_longer_deadline_than_checks() {
+    let defaults = FgaTimeouts::default();
+
+    assert!(defaults.list > defaults.check);
+    assert!(defaults.write > defaults.check);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use openfga_demo::context::Ctx;
+use serde_json::json;
+
+#[tokio::test]
+async fn known_type_lists_relations_and_actions() {
+    let mut ctx = Ctx::for_testing();
+    ctx.fga_config
+        .actions
+        .insert("read".to_string(), "viewer".to_string());
+    ctx.fga_config
+        .actions
+        .insert("edit".to_string(), "editor".to_string());
+    ctx.fga_config
+        .actions
+        .insert("approve".to_string(), "approver".to_string());
+    let app = TestApp::with_ctx(ctx);
+
+    let response = app
+        .get(Some("alice"), "/api/types/resource/relations")
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["type"], "resource");
+    assert_eq!(
+        response.body["relations"],
+        json!(["owner", "admin", "editor", "viewer"])
+    );
+    // Actions mapped to relations the type does not define are left out
+    assert_eq!(
+        response.body["actions"],
+        json!({ "edit": "editor", "read": "viewer" })
+    );
+}
+
+#[tokio::test]
+async fn unknown_type_is_not_found() {
+    let app = TestApp::new();
+
+    let response = app.get(Some("alice"), "/api/types/folder/relations").await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.error_code(), "not_found");
+}
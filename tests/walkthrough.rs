@@ -0,0 +1,105 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{MOCK_MODEL_ID, MockFga, TestApp};
+use openfga_demo::context::Ctx;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// App in `profile` where resources have owners and viewers
+fn app(fga: Arc<MockFga>, profile: &str) -> TestApp {
+    let mut ctx = Ctx::for_testing().with_fga_client(fga);
+    ctx.fga_config.authorization_model_id = Some(MOCK_MODEL_ID.to_string());
+    ctx.fga_config.relations_by_type = BTreeMap::from([(
+        "resource".to_string(),
+        vec!["owner".to_string(), "viewer".to_string()],
+    )]);
+    ctx.profile = profile.to_string();
+    TestApp::with_ctx(ctx)
+}
+
+/// Members of acme view the roadmap, and alice is a member of acme
+fn inherited_access() -> Arc<MockFga> {
+    let fga = Arc::new(MockFga::default());
+    fga.grant("user:alice", "member", "organisation:acme");
+    fga.grant("organisation:acme#member", "viewer", "resource:roadmap");
+    fga.grant("user:carol", "owner", "resource:roadmap");
+    fga.derive("user:alice", "viewer", "resource:roadmap");
+    fga
+}
+
+#[tokio::test]
+async fn walkthrough_explains_inherited_access_step_by_step() {
+    let app = app(inherited_access(), "dev");
+
+    let response = app
+        .get(
+            Some("alice"),
+            "/api/dev/walkthrough?user=alice&resource=resource:roadmap",
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["user"], "user:alice");
+    assert_eq!(response.body["resource"], "resource:roadmap");
+    assert_eq!(
+        response.body["steps"],
+        json!([
+            "Tuples stored on resource:roadmap:",
+            "  organisation:acme#member is viewer of resource:roadmap",
+            "  user:carol is owner of resource:roadmap",
+            "Expanding resource:roadmap#owner:",
+            "  resource:roadmap#owner is granted directly to user:carol",
+            "Therefore user:alice does not have owner on resource:roadmap",
+            "Expanding resource:roadmap#viewer:",
+            "  resource:roadmap#viewer is granted directly to organisation:acme#member",
+            "Therefore user:alice has viewer on resource:roadmap"
+        ])
+    );
+}
+
+#[tokio::test]
+async fn walkthrough_of_an_untouched_object_reports_no_tuples() {
+    let app = app(Arc::new(MockFga::default()), "dev");
+
+    let response = app
+        .get(
+            Some("alice"),
+            "/api/dev/walkthrough?user=alice&resource=resource:empty",
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    let steps = response.body["steps"].as_array().unwrap();
+    assert_eq!(steps[0], "No tuples are stored on resource:empty");
+    assert!(steps.contains(&json!("  resource:empty#viewer has no direct grants")));
+}
+
+#[tokio::test]
+async fn walkthrough_rejects_unknown_types() {
+    let app = app(Arc::new(MockFga::default()), "dev");
+
+    let response = app
+        .get(
+            Some("alice"),
+            "/api/dev/walkthrough?user=alice&resource=widget:1",
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn walkthrough_is_only_routed_in_the_dev_profile() {
+    let app = app(inherited_access(), "prod");
+
+    let response = app
+        .get(
+            Some("alice"),
+            "/api/dev/walkthrough?user=alice&resource=resource:roadmap",
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}